@@ -0,0 +1,330 @@
+use bevy::{
+    prelude::*,
+    app::AppExit,
+};
+
+use std::fs;
+
+use crate::history::EditHistory;
+use crate::keybindings::{ Action, Actions, Keybindings };
+use crate::roadsystem::RoadSystem;
+use crate::selection::Selection;
+use crate::theme::{ self, Theme, ThemeMaterials, Themes };
+use crate::ui::{ ButtonMaterials, UiContainerWidget };
+use crate::ui::notifications::Notifications;
+use crate::ui::widgets::{ self, LabelledButton, Panel };
+use crate::view::CameraView;
+
+/// File the city is saved to and loaded from
+const CITY_FILE: &str = "city.txt";
+
+/// Screen the application shows. The road system is only edited in the editing state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AppState {
+    MainMenu,
+
+    /// The saved city replaces the current one before editing starts
+    Loading,
+    Editing,
+    Paused,
+
+    /// Settings go back to the pause menu if they were opened from it
+    Settings { paused: bool }
+}
+
+impl Default for AppState {
+    fn default() -> AppState {
+        AppState::MainMenu
+    }
+}
+
+impl AppState {
+    pub fn menu(&self) -> Option<Menu> {
+        match self {
+            AppState::MainMenu => Some(Menu::Main),
+            AppState::Paused => Some(Menu::Pause),
+            AppState::Settings { .. } => Some(Menu::Settings),
+            AppState::Loading | AppState::Editing => None
+        }
+    }
+
+    /// Returns the state after the entry was clicked. Entries which are not part of the
+    /// menu of the state change nothing.
+    pub fn after(&self, action: MenuAction) -> AppState {
+        if !self.menu().map_or(false, |menu| menu.entries().contains(&action)) {
+            return *self;
+        }
+
+        match action {
+            MenuAction::New | MenuAction::Resume => AppState::Editing,
+            MenuAction::Load => AppState::Loading,
+            MenuAction::Settings => AppState::Settings { paused: *self == AppState::Paused },
+            MenuAction::Back => self.cancelled(),
+            MenuAction::MainMenu => AppState::MainMenu,
+            _ => *self
+        }
+    }
+
+    /// Returns the state after Escape was pressed, which pauses editing and leaves the menus
+    pub fn cancelled(&self) -> AppState {
+        match self {
+            AppState::Editing => AppState::Paused,
+            AppState::Paused => AppState::Editing,
+            AppState::Settings { paused: true } => AppState::Paused,
+            AppState::Settings { paused: false } => AppState::MainMenu,
+            state => *state
+        }
+    }
+}
+
+/// Panel shown over the world, one for each state which is no editing state
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Menu {
+    Main,
+    Pause,
+    Settings
+}
+
+impl Menu {
+    pub const ALL: [Menu; 3] = [Menu::Main, Menu::Pause, Menu::Settings];
+
+    fn title(&self) -> &'static str {
+        match self {
+            Menu::Main => "City builder",
+            Menu::Pause => "Paused",
+            Menu::Settings => "Settings"
+        }
+    }
+
+    pub fn entries(&self) -> &'static [MenuAction] {
+        match self {
+            Menu::Main => &[MenuAction::New, MenuAction::Load, MenuAction::Settings, MenuAction::Quit],
+            Menu::Pause => &[MenuAction::Resume, MenuAction::Save, MenuAction::Settings, MenuAction::MainMenu, MenuAction::Quit],
+            Menu::Settings => &[MenuAction::NextTheme, MenuAction::ReloadKeybindings, MenuAction::Back]
+        }
+    }
+}
+
+/// Entry of a menu, sent as event when it is clicked
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MenuAction {
+    New,
+    Load,
+    Resume,
+    Save,
+    Settings,
+    NextTheme,
+    ReloadKeybindings,
+    Back,
+    MainMenu,
+    Quit
+}
+
+impl MenuAction {
+    fn label(&self) -> &'static str {
+        match self {
+            MenuAction::New => "New city",
+            MenuAction::Load => "Load city",
+            MenuAction::Resume => "Resume",
+            MenuAction::Save => "Save city",
+            MenuAction::Settings => "Settings",
+            MenuAction::NextTheme => "Next theme",
+            MenuAction::ReloadKeybindings => "Reload keybindings",
+            MenuAction::Back => "Back",
+            MenuAction::MainMenu => "Main menu",
+            MenuAction::Quit => "Quit"
+        }
+    }
+}
+
+/// Darkens the world and catches the clicks while a menu is shown
+pub struct MenuOverlay;
+
+fn menu_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    button_materials: Res<ButtonMaterials>,
+    theme_materials: Res<ThemeMaterials>
+) {
+    commands
+    .spawn(ButtonComponents {
+        style: Style {
+            size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+            position_type: PositionType::Absolute,
+            ..Default::default()
+        },
+        material: theme_materials.overlay.clone(),
+        ..Default::default()
+    })
+    .with(MenuOverlay);
+
+    let position = Rect {
+        left: Val::Percent(45.0),
+        top: Val::Percent(35.0),
+        ..Default::default()
+    };
+
+    for menu in Menu::ALL.iter() {
+        let mut panel = Panel::new(menu.title(), position);
+        for action in menu.entries() {
+            panel = panel.with(LabelledButton::new(action.label(), *action));
+        }
+
+        panel.create(&mut commands, &button_materials, &asset_server);
+        commands.with(*menu);
+    }
+}
+
+/// Changes the state with the clicked menu entries and applies the settings
+fn menu_system(
+    mut action_reader: Local<EventReader<MenuAction>>,
+    action_events: Res<Events<MenuAction>>,
+    mut exit_events: ResMut<Events<AppExit>>,
+    mut app_state: ResMut<AppState>,
+    mut keybindings: ResMut<Keybindings>,
+    mut themes: ResMut<Themes>,
+    mut theme: ResMut<Theme>,
+    mut notifications: ResMut<Notifications>
+) {
+    for action in action_reader.iter(&action_events) {
+        match action {
+            MenuAction::Quit => exit_events.send(AppExit),
+            MenuAction::NextTheme => theme::show_next_theme(&mut themes, &mut theme, &mut notifications),
+            MenuAction::ReloadKeybindings => match Keybindings::load() {
+                Ok(loaded) => {
+                    *keybindings = loaded;
+                    notifications.info("Reloaded keybindings");
+                }
+                Err(error) => notifications.error(&format!("Could not load keybindings {}", error))
+            },
+            _ => ()
+        }
+
+        *app_state = app_state.after(*action);
+    }
+}
+
+/// Starts a new city, saves the city from the menu or by its shortcut while editing and loads
+/// it in the loading state
+fn city_system(
+    mut action_reader: Local<EventReader<MenuAction>>,
+    action_events: Res<Events<MenuAction>>,
+    actions: Res<Actions>,
+    mut app_state: ResMut<AppState>,
+    mut notifications: ResMut<Notifications>,
+    mut history: ResMut<EditHistory>,
+    mut selection: ResMut<Selection>,
+    mut view: ResMut<CameraView>,
+    mut graph_query: Query<&mut RoadSystem>
+) {
+    let mut city = None;
+    let mut save = *app_state == AppState::Editing && actions.just_pressed(Action::Save);
+
+    for action in action_reader.iter(&action_events) {
+        match action {
+            MenuAction::New => city = Some(RoadSystem::new()),
+            MenuAction::Save => save = true,
+            _ => ()
+        }
+    }
+
+    if save {
+        for road_system in &mut graph_query.iter() {
+            match fs::write(CITY_FILE, road_system.to_city_text()) {
+                Ok(_) => notifications.info(&format!("Saved city {}", CITY_FILE)),
+                Err(error) => notifications.error(&format!("Could not save city {}: {}", CITY_FILE, error))
+            }
+        }
+    }
+
+    if *app_state == AppState::Loading {
+        let loaded = fs::read_to_string(CITY_FILE)
+            .map_err(|error| error.to_string())
+            .and_then(|text| RoadSystem::parse_city(&text));
+
+        match loaded {
+            Ok(loaded) => {
+                city = Some(loaded);
+                notifications.info(&format!("Loaded city {}", CITY_FILE));
+                *app_state = AppState::Editing;
+            }
+            Err(error) => {
+                notifications.error(&format!("Could not load city {}: {}", CITY_FILE, error));
+                *app_state = AppState::MainMenu;
+            }
+        }
+    }
+
+    let mut city = match city {
+        Some(city) => city,
+        None => return
+    };
+
+    for mut road_system in &mut graph_query.iter() {
+        std::mem::swap(&mut *road_system, &mut city);
+    }
+
+    // nothing of the previous city is kept
+    *history = EditHistory::default();
+    selection.clear();
+    *view = CameraView::default();
+}
+
+/// Shows the menu of the state over the darkened world
+fn menu_display_system(
+    app_state: Res<AppState>,
+    mut overlay_query: Query<With<MenuOverlay, &mut Style>>,
+    mut menu_query: Query<(&Menu, &mut Style)>
+) {
+    for mut style in &mut overlay_query.iter() {
+        let display = if *app_state == AppState::Editing { Display::None } else { Display::Flex };
+
+        if style.display != display {
+            style.display = display;
+        }
+    }
+
+    for (menu, mut style) in &mut menu_query.iter() {
+        let display = if app_state.menu() == Some(*menu) { Display::Flex } else { Display::None };
+
+        if style.display != display {
+            style.display = display;
+        }
+    }
+}
+
+pub struct AppStatePlugin;
+
+impl Plugin for AppStatePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<AppState>();
+        app.add_startup_system(menu_setup.system());
+        app.add_system_to_stage("ui_handling", menu_system.system());
+        app.add_system(city_system.system());
+        app.add_system(menu_display_system.system());
+        widgets::add_button_events::<MenuAction>(app);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn menus_change_the_state() {
+        assert_eq!(AppState::MainMenu.after(MenuAction::New), AppState::Editing);
+        assert_eq!(AppState::MainMenu.after(MenuAction::Load), AppState::Loading);
+        assert_eq!(AppState::MainMenu.after(MenuAction::Resume), AppState::MainMenu);
+        assert_eq!(AppState::Editing.after(MenuAction::MainMenu), AppState::Editing);
+
+        let settings = AppState::Paused.after(MenuAction::Settings);
+        assert_eq!(settings, AppState::Settings { paused: true });
+        assert_eq!(settings.after(MenuAction::NextTheme), settings);
+        assert_eq!(settings.after(MenuAction::Back), AppState::Paused);
+        assert_eq!(AppState::MainMenu.after(MenuAction::Settings).after(MenuAction::Back), AppState::MainMenu);
+
+        assert_eq!(AppState::Editing.cancelled(), AppState::Paused);
+        assert_eq!(AppState::Paused.cancelled(), AppState::Editing);
+        assert_eq!(AppState::MainMenu.cancelled(), AppState::MainMenu);
+    }
+}
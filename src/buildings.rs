@@ -1,2 +1,51 @@
+use crate::math::offset::{ offset_polygon, JoinType };
+use crate::math::polygon::Polygon;
+
+/// Distance of buildings to the center line of the streets around their block
+pub const BUILDING_SETBACK: f32 = 20.0;
+
 pub struct Building;
 
+/// Shrinks a block by the setback to the area where buildings can be placed. Parts of the
+/// block which are too narrow for the setback are dropped which can split the block into
+/// multiple envelopes.
+pub fn building_envelope(block: &Polygon, setback: f32) -> Vec<Polygon> {
+    offset_polygon(block, -setback, JoinType::Miter(2.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use super::*;
+
+    #[test]
+    fn narrow_passage_splits_the_envelope() {
+        // two squares joined by a passage which is narrower than twice the setback
+        let block = Polygon::new(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(100.0, 40.0),
+            Vec2::new(200.0, 40.0),
+            Vec2::new(200.0, 0.0),
+            Vec2::new(300.0, 0.0),
+            Vec2::new(300.0, 100.0),
+            Vec2::new(200.0, 100.0),
+            Vec2::new(200.0, 60.0),
+            Vec2::new(100.0, 60.0),
+            Vec2::new(100.0, 100.0),
+            Vec2::new(0.0, 100.0)
+        ]);
+
+        assert_eq!(building_envelope(&block, 5.0).len(), 1);
+
+        let left = |polygon: &Polygon| polygon.points().iter().map(|point| point.x()).fold(f32::MAX, f32::min);
+        let mut envelopes = building_envelope(&block, 15.0);
+        envelopes.sort_by(|a, b| left(a).partial_cmp(&left(b)).unwrap());
+
+        assert_eq!(envelopes.len(), 2);
+        for (envelope, expected) in envelopes.iter().zip(&[15.0, 215.0]) {
+            assert!((left(envelope) - expected).abs() < 1e-3);
+            assert!(envelope.area() >= 70.0 * 70.0 - 1.0 && envelope.area() < 70.0 * 85.0);
+        }
+    }
+}
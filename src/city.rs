@@ -3,6 +3,9 @@ use bevy::{
     ecs::Entity
 };
 
+use crate::math::operations::Inside;
+use crate::math::polygon::{ Containment, Polygon };
+
 /// Calculates the center position between two vectors in world space
 fn calculate_center_ws(start: Vec2, end: Vec2) -> Vec2 {
     let connection_vec2 = end - start;
@@ -72,6 +75,47 @@ pub struct Intersection {
     pub connected_streets: Vec<Entity>,
 }
 
+/// Area enclosed by streets
+pub struct Block {
+    pub polygon: Polygon
+}
+
+impl Block {
+    pub fn new(polygon: Polygon) -> Block {
+        Block { polygon }
+    }
+
+    pub fn classify(&self, point: Vec2) -> Containment {
+        self.polygon.classify(point)
+    }
+}
+
+impl Inside<Block> for Vec2 {
+    fn inside(&self, other_object: &Block) -> bool {
+        self.inside(&other_object.polygon)
+    }
+}
+
+/// Part of a block which can be built on
+pub struct Lot {
+    pub polygon: Polygon
+}
+
+impl Lot {
+    pub fn new(polygon: Polygon) -> Lot {
+        Lot { polygon }
+    }
+
+    pub fn classify(&self, point: Vec2) -> Containment {
+        self.polygon.classify(point)
+    }
+}
+
+impl Inside<Lot> for Vec2 {
+    fn inside(&self, other_object: &Lot) -> bool {
+        self.inside(&other_object.polygon)
+    }
+}
 
 
 #[cfg(test)]
@@ -107,4 +151,22 @@ mod tests {
 
         assert_eq!(2 + 2, 4);
     }
+
+    #[test]
+    fn point_in_block_and_lot() {
+        let polygon = Polygon::new(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(100.0, 100.0),
+            Vec2::new(0.0, 100.0)
+        ]);
+
+        let block = Block::new(polygon.clone());
+        let lot = Lot::new(polygon);
+
+        assert_eq!(block.classify(Vec2::new(50.0, 50.0)), Containment::Inside);
+        assert_eq!(block.classify(Vec2::new(100.0, 100.0)), Containment::OnBoundary);
+        assert_eq!(lot.classify(Vec2::new(150.0, 50.0)), Containment::Outside);
+        assert_eq!(Vec2::new(50.0, 100.0).inside(&lot), true);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+
+use std::f32::consts::PI;
+
+/// Angle steps the angle constraint cycles through
+const ANGLE_STEPS: [f32; 3] = [PI / 12.0, PI / 4.0, PI / 2.0];
+
+/// Settings of the constraints applied while building streets
+pub struct BuildConstraints {
+    /// Size of a cell of the world grid
+    pub grid_size: f32,
+
+    /// Angle the direction of a street is rounded to
+    pub angle_step: f32,
+
+    /// Maximal distance of a street to the start of a new one to be used as reference
+    pub reference_distance: f32
+}
+
+impl Default for BuildConstraints {
+    fn default() -> BuildConstraints {
+        BuildConstraints {
+            grid_size: 50.0,
+            angle_step: ANGLE_STEPS[0],
+            reference_distance: 100.0
+        }
+    }
+}
+
+impl BuildConstraints {
+    /// Switches to the next of the 15°, 45° and 90° angle steps
+    pub fn next_angle_step(&mut self) {
+        let index = ANGLE_STEPS
+            .iter()
+            .position(|step| (step - self.angle_step).abs() < 1e-6)
+            .map_or(0, |index| (index + 1) % ANGLE_STEPS.len());
+
+        self.angle_step = ANGLE_STEPS[index];
+    }
+}
+
+/// Moves the point to the closest corner of the grid
+pub fn snap_to_grid(point: Vec2, grid_size: f32) -> Vec2 {
+    if grid_size <= 0.0 {
+        return point;
+    }
+
+    Vec2::new(
+        (point.x() / grid_size).round() * grid_size,
+        (point.y() / grid_size).round() * grid_size
+    )
+}
+
+/// Rotates the end around the start so the direction between them is a multiple of the step
+/// relative to the reference angle. The end is projected onto the new direction.
+pub fn snap_angle(start: Vec2, end: Vec2, reference: f32, step: f32) -> Vec2 {
+    let vector = end - start;
+    if vector.length() == 0.0 || step <= 0.0 {
+        return end;
+    }
+
+    let angle = vector.y().atan2(vector.x());
+    let snapped = ((angle - reference) / step).round() * step + reference;
+    let direction = Vec2::new(snapped.cos(), snapped.sin());
+
+    start + direction * vector.dot(direction)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use super::*;
+
+    fn assert_near(value: Vec2, expected: Vec2) {
+        assert!((value - expected).length() < 1e-3, "{:?} != {:?}", value, expected);
+    }
+
+    #[test]
+    fn grid() {
+        assert_eq!(snap_to_grid(Vec2::new(24.0, -26.0), 50.0), Vec2::new(0.0, -50.0));
+        assert_eq!(snap_to_grid(Vec2::new(24.0, -26.0), 0.0), Vec2::new(24.0, -26.0));
+    }
+
+    #[test]
+    fn angle_steps() {
+        let start = Vec2::new(10.0, 10.0);
+
+        assert_near(snap_angle(start, Vec2::new(110.0, 15.0), 0.0, PI / 2.0), Vec2::new(110.0, 10.0));
+        assert_near(snap_angle(start, Vec2::new(110.0, 100.0), 0.0, PI / 4.0), Vec2::new(105.0, 105.0));
+        assert_near(snap_angle(start, Vec2::new(10.0, -90.0), 0.0, PI / 12.0), Vec2::new(10.0, -90.0));
+    }
+
+    #[test]
+    fn perpendicular_to_reference() {
+        // reference street rotated by 45°
+        let end = snap_angle(Vec2::new(0.0, 0.0), Vec2::new(-40.0, 60.0), PI / 4.0, PI / 2.0);
+
+        assert_near(end, Vec2::new(-50.0, 50.0));
+    }
+
+    #[test]
+    fn cycle_angle_steps() {
+        let mut constraints = BuildConstraints::default();
+
+        constraints.next_angle_step();
+        assert_eq!(constraints.angle_step, PI / 4.0);
+        constraints.next_angle_step();
+        constraints.next_angle_step();
+        assert_eq!(constraints.angle_step, PI / 12.0);
+    }
+}
@@ -0,0 +1,294 @@
+use bevy::prelude::*;
+
+use crate::app_state::AppState;
+use crate::history::{ Edit, EditHistory };
+use crate::input;
+use crate::inspector::StreetName;
+use crate::keybindings::{ Action, Actions };
+use crate::roadsystem::{ RoadElement, RoadSystem, RoadType, TrafficControl };
+use crate::selection::Selection;
+use crate::ui;
+use crate::ui::{ ButtonMaterials, UiContainerWidget };
+use crate::ui::widgets::{ self, ButtonValue, LabelledButton, Panel, TextField };
+use crate::view::CameraView;
+
+/// Entry of the context menu, sent as event when it is clicked
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ContextAction {
+    Delete,
+    SplitHere,
+    ChangeType(RoadType),
+    Rename,
+    AddTrafficLight,
+    SelectConnected
+}
+
+impl ContextAction {
+    /// Returns true if the entry is shown in the menu of the element
+    pub fn applies_to(&self, road_system: &RoadSystem, element: RoadElement) -> bool {
+        match (self, element) {
+            (ContextAction::Delete, _) | (ContextAction::SelectConnected, _) => true,
+            (ContextAction::SplitHere, RoadElement::Street(_)) | (ContextAction::Rename, RoadElement::Street(_)) => true,
+            (ContextAction::ChangeType(road_type), RoadElement::Street(street)) => {
+                road_system.road_type(street).map_or(false, |current| current != *road_type)
+            }
+            (ContextAction::AddTrafficLight, RoadElement::Intersection(intersection)) => {
+                road_system.intersection(intersection).map_or(false, |intersection| intersection.control != TrafficControl::TrafficLight)
+            }
+            _ => false
+        }
+    }
+
+    /// Applies the actions which only change the road system. Attribute changes can be undone,
+    /// deleting and splitting forget the recorded edits.
+    pub fn apply(&self, road_system: &mut RoadSystem, history: &mut EditHistory, element: RoadElement, position: Vec2) {
+        match (self, element) {
+            (ContextAction::Delete, RoadElement::Street(street)) => road_system.remove_street(street),
+            (ContextAction::Delete, RoadElement::Intersection(intersection)) => road_system.remove_intersection(intersection),
+            (ContextAction::SplitHere, RoadElement::Street(street)) => {
+                if let Some(line) = road_system.street_line(street) {
+                    road_system.split_street(street, line.closest_point(position));
+                }
+            }
+            (ContextAction::ChangeType(road_type), RoadElement::Street(street)) => {
+                if let Some(before) = road_system.street(street).cloned() {
+                    let mut after = before.clone();
+                    after.set_road_type(*road_type);
+                    history.apply(road_system, Edit::Street { street, before, after });
+                }
+            }
+            (ContextAction::AddTrafficLight, RoadElement::Intersection(intersection)) => {
+                if let Some(before) = road_system.intersection(intersection).map(|intersection| intersection.control) {
+                    history.apply(road_system, Edit::Intersection { intersection, before, after: TrafficControl::TrafficLight });
+                }
+            }
+            _ => ()
+        }
+    }
+}
+
+/// Element the menu was opened for
+struct Target {
+    element: RoadElement,
+
+    /// Clicked position in the world
+    position: Vec2,
+
+    /// Clicked position on the screen, where the menu is shown
+    screen_position: Vec2
+}
+
+/// Menu of the street or intersection which was right-clicked with the select tool or no tool
+#[derive(Default)]
+pub struct ContextMenu {
+    target: Option<Target>,
+    action_reader: EventReader<ContextAction>
+}
+
+/// Marks the panel showing the context menu
+pub struct ContextMenuPanel;
+
+fn context_menu_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    button_materials: Res<ButtonMaterials>
+) {
+    let mut panel = Panel::new("Edit", Rect::default())
+        .with(LabelledButton::new("Delete", ContextAction::Delete))
+        .with(LabelledButton::new("Split here", ContextAction::SplitHere));
+
+    for road_type in RoadType::ALL.iter() {
+        let label = format!("Change to {}", road_type.name());
+        panel = panel.with(LabelledButton::new(&label, ContextAction::ChangeType(*road_type)));
+    }
+
+    panel
+        .with(LabelledButton::new("Rename", ContextAction::Rename))
+        .with(LabelledButton::new("Add traffic light", ContextAction::AddTrafficLight))
+        .with(LabelledButton::new("Select connected", ContextAction::SelectConnected))
+        .create(&mut commands, &button_materials, &asset_server);
+    commands.with(ContextMenuPanel);
+}
+
+/// Applies the clicked entry and opens the menu of the right-clicked element. Any other
+/// click closes the menu.
+fn context_menu_system(
+    app_state: Res<AppState>,
+    mut current_action: ResMut<ui::RoadActions>,
+    state: Res<input::MouseState>,
+    view: Res<CameraView>,
+    actions: Res<Actions>,
+    action_events: Res<Events<ContextAction>>,
+    mut menu: ResMut<ContextMenu>,
+    mut history: ResMut<EditHistory>,
+    mut selection: ResMut<Selection>,
+    mut name_query: Query<&mut TextField<StreetName>>,
+    mut graph_query: Query<&mut RoadSystem>
+) {
+    if *app_state != AppState::Editing {
+        return;
+    }
+
+    let menu = &mut *menu;
+
+    for mut road_system in &mut graph_query.iter() {
+        for action in menu.action_reader.iter(&action_events) {
+            let target = match menu.target.take() {
+                Some(target) if road_system.contains(target.element) => target,
+                _ => continue
+            };
+
+            match action {
+                ContextAction::Rename => {
+                    let street = match target.element {
+                        RoadElement::Street(street) => street,
+                        _ => continue
+                    };
+
+                    *current_action = ui::RoadActions::Select;
+                    selection.clear();
+                    selection.elements.insert(target.element);
+
+                    // the inspector shows the selected street with its name field focused
+                    for mut field in &mut name_query.iter() {
+                        field.value = road_system.street(street).unwrap().name.clone();
+                        field.focused = true;
+                    }
+                }
+                ContextAction::SelectConnected => {
+                    *current_action = ui::RoadActions::Select;
+                    selection.clear();
+                    selection.elements.extend(road_system.connected_elements(target.element));
+                }
+                _ => {
+                    action.apply(&mut road_system, &mut history, target.element, target.position);
+                    selection.retain_valid(&road_system);
+                }
+            }
+        }
+
+        if actions.just_pressed(Action::Primary) && menu.target.is_some() {
+            menu.target = None;
+        }
+
+        let opened = match *current_action {
+            ui::RoadActions::Nothing | ui::RoadActions::Select => actions.just_pressed(Action::Secondary),
+            _ => false
+        };
+
+        if opened {
+            let position = crate::mouse_pos_ws(&state, &view);
+
+            menu.target = road_system.pick(position).map(|element| Target {
+                element,
+                position,
+                screen_position: state.mouse_position
+            });
+        }
+    }
+}
+
+/// Shows the entries applying to the target of the menu next to the cursor
+fn context_menu_display_system(
+    menu: ChangedRes<ContextMenu>,
+    mut graph_query: Query<&RoadSystem>,
+    mut panel_query: Query<With<ContextMenuPanel, &mut Style>>,
+    mut button_query: Query<(&ButtonValue<ContextAction>, &mut Style)>
+) {
+    for road_system in &mut graph_query.iter() {
+        let target = menu.target.as_ref().filter(|target| road_system.contains(target.element));
+
+        for mut style in &mut panel_query.iter() {
+            let display = if target.is_some() { Display::Flex } else { Display::None };
+
+            if style.display != display {
+                style.display = display;
+            }
+
+            if let Some(target) = target {
+                let position = Rect {
+                    left: Val::Px(target.screen_position.x()),
+                    top: Val::Px(crate::WINDOW_HEIGHT as f32 - target.screen_position.y()),
+                    ..Default::default()
+                };
+
+                if style.position != position {
+                    style.position = position;
+                }
+            }
+        }
+
+        let target = match target {
+            Some(target) => target,
+            None => continue
+        };
+
+        for (action, mut style) in &mut button_query.iter() {
+            let display = if action.0.applies_to(&road_system, target.element) { Display::Flex } else { Display::None };
+
+            if style.display != display {
+                style.display = display;
+            }
+        }
+    }
+}
+
+pub struct ContextMenuPlugin;
+
+impl Plugin for ContextMenuPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<ContextMenu>();
+        app.add_startup_system(context_menu_setup.system());
+        app.add_system(context_menu_system.system());
+        app.add_system(context_menu_display_system.system());
+        widgets::add_button_events::<ContextAction>(app);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use super::*;
+    use crate::roadsystem::Street;
+
+    #[test]
+    fn entries_depend_on_the_element() {
+        let mut road_system = RoadSystem::new();
+        let streets = road_system.insert_street(Vec2::new(0.0, 0.0), Vec2::new(200.0, 0.0), Street::default());
+        let street = RoadElement::Street(streets[0]);
+        let intersection = RoadElement::Intersection(road_system.intersections()[0]);
+
+        assert!(ContextAction::SplitHere.applies_to(&road_system, street));
+        assert!(ContextAction::ChangeType(RoadType::Avenue).applies_to(&road_system, street));
+        assert!(!ContextAction::ChangeType(RoadType::Residential).applies_to(&road_system, street));
+        assert!(!ContextAction::AddTrafficLight.applies_to(&road_system, street));
+
+        assert!(ContextAction::AddTrafficLight.applies_to(&road_system, intersection));
+        assert!(!ContextAction::Rename.applies_to(&road_system, intersection));
+        assert!(ContextAction::Delete.applies_to(&road_system, intersection));
+    }
+
+    #[test]
+    fn apply_entries() {
+        let mut road_system = RoadSystem::new();
+        let mut history = EditHistory::default();
+        let streets = road_system.insert_street(Vec2::new(0.0, 0.0), Vec2::new(200.0, 0.0), Street::default());
+        let intersection = road_system.intersections()[0];
+
+        ContextAction::AddTrafficLight.apply(&mut road_system, &mut history, RoadElement::Intersection(intersection), Vec2::zero());
+        assert_eq!(road_system.intersection(intersection).unwrap().control, TrafficControl::TrafficLight);
+        assert!(!ContextAction::AddTrafficLight.applies_to(&road_system, RoadElement::Intersection(intersection)));
+
+        history.undo(&mut road_system);
+        assert_eq!(road_system.intersection(intersection).unwrap().control, TrafficControl::Uncontrolled);
+
+        ContextAction::SplitHere.apply(&mut road_system, &mut history, RoadElement::Street(streets[0]), Vec2::new(50.0, 20.0));
+        assert_eq!(road_system.streets().len(), 2);
+        assert!(road_system.intersections().iter().any(|node| road_system.intersection_position(*node) == Some(Vec2::new(50.0, 0.0))));
+
+        // the edits refer to indices which may belong to other elements after deleting
+        ContextAction::ChangeType(RoadType::Avenue).apply(&mut road_system, &mut history, RoadElement::Street(streets[0]), Vec2::zero());
+        ContextAction::Delete.apply(&mut road_system, &mut history, RoadElement::Street(streets[0]), Vec2::zero());
+        assert!(!history.undo(&mut road_system));
+    }
+}
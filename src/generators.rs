@@ -0,0 +1,477 @@
+use bevy::prelude::*;
+
+use crate::app_state::AppState;
+use crate::input;
+use crate::keybindings::{ Action, Actions };
+use crate::math::line::{ Line, Parallel };
+use crate::math::offset::{ offset_polyline, EndType, JoinType };
+use crate::math::polygon::Polygon;
+use crate::roadsystem::{ RoadElement, RoadSystem, Street };
+use crate::selection::{ SelectionArea, ROTATION_STEP };
+use crate::shapes::spawn_polygons;
+use crate::theme::ThemeMaterials;
+use crate::ui;
+use crate::ui::{ ButtonMaterials, UiContainerWidget };
+use crate::ui::widgets::{ self, NumericField, Panel, Slider };
+use crate::view::CameraView;
+
+/// Change of spacing and block size per key press
+const SIZE_STEP: f32 = 10.0;
+
+const MIN_SIZE: f32 = 50.0;
+
+/// Settings of the parallel street, grid and roundabout tools
+pub struct GeneratorSettings {
+    /// Distance between a street and its parallel streets
+    pub spacing: f32,
+
+    /// Number of parallel streets created at once
+    pub parallel_count: usize,
+
+    /// Distance between the streets of a grid
+    pub block_size: f32,
+
+    /// Orientation of the grid
+    pub grid_angle: f32,
+
+    /// Radius of the ring of new roundabouts
+    pub roundabout_radius: f32
+}
+
+impl Default for GeneratorSettings {
+    fn default() -> GeneratorSettings {
+        GeneratorSettings {
+            spacing: 150.0,
+            parallel_count: 1,
+            block_size: 150.0,
+            grid_angle: 0.0,
+            roundabout_radius: 60.0
+        }
+    }
+}
+
+/// Returns the parallels of the line. A positive side creates them on the right side of the
+/// line when looking from its first to its second point.
+pub fn parallel_lines(line: &Line, spacing: f32, count: usize, side: f32) -> Vec<Line> {
+    (1..=count)
+        .map(|index| line.parralel(side.signum() * spacing * index as f32))
+        .collect()
+}
+
+/// Returns the parts of the infinite line through origin which lie inside the area
+fn clip_line(area: &Polygon, origin: Vec2, direction: Vec2) -> Vec<Line> {
+    let normal = Vec2::new(-direction.y(), direction.x());
+
+    let mut crossings: Vec<f32> = area
+        .lines()
+        .iter()
+        .filter_map(|edge| {
+            let distance1 = (edge.point1 - origin).dot(normal);
+            let distance2 = (edge.point2 - origin).dot(normal);
+
+            // half open so lines through a vertex are only counted once
+            if (distance1 > 0.0) == (distance2 > 0.0) {
+                return None;
+            }
+
+            let crossing = edge.point1 + (edge.point2 - edge.point1) * (distance1 / (distance1 - distance2));
+            Some((crossing - origin).dot(direction))
+        })
+        .collect();
+
+    crossings.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    crossings
+        .chunks(2)
+        .filter(|pair| pair.len() == 2 && pair[1] - pair[0] > 1e-3)
+        .map(|pair| Line::new(origin + direction * pair[0], origin + direction * pair[1]))
+        .collect()
+}
+
+/// Fills the area with streets along its boundary and a grid of streets with the given
+/// orientation. The grid is aligned to the world origin so neighbouring grids line up.
+pub fn grid_lines(area: &Polygon, angle: f32, block_size: f32) -> Vec<Line> {
+    let mut lines = area.lines();
+    if block_size <= 0.0 {
+        return lines;
+    }
+
+    let direction = Vec2::new(angle.cos(), angle.sin());
+    let normal = Vec2::new(-direction.y(), direction.x());
+
+    for (direction, normal) in &[(direction, normal), (normal, direction)] {
+        let offsets: Vec<f32> = area.points().iter().map(|point| point.dot(*normal)).collect();
+        let min = offsets.iter().cloned().fold(f32::MAX, f32::min);
+        let max = offsets.iter().cloned().fold(f32::MIN, f32::max);
+
+        let mut index = (min / block_size).ceil() as i32;
+        while index as f32 * block_size <= max {
+            let offset = index as f32 * block_size;
+            index += 1;
+
+            // lines on the extent of the area would run along the boundary
+            if offset - min < 1e-3 || max - offset < 1e-3 {
+                continue;
+            }
+
+            lines.extend(clip_line(area, *normal * offset, *direction));
+        }
+    }
+
+    lines
+}
+
+/// Markers of the widgets editing the generator settings
+pub struct Spacing;
+pub struct ParallelCount;
+pub struct BlockSize;
+pub struct RoundaboutRadius;
+
+fn generator_panel_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    button_materials: Res<ButtonMaterials>,
+    settings: Res<GeneratorSettings>
+) {
+    let position = Rect {
+        right: Val::Px(10.0),
+        top: Val::Px(10.0),
+        ..Default::default()
+    };
+
+    Panel::new("Generators", position)
+        .collapsible()
+        .with(Slider::<Spacing>::new("Spacing", MIN_SIZE, 500.0, SIZE_STEP, settings.spacing))
+        .with(NumericField::<ParallelCount>::new("Parallels", 1.0, 10.0, 1.0, settings.parallel_count as f32))
+        .with(Slider::<BlockSize>::new("Block size", MIN_SIZE, 500.0, SIZE_STEP, settings.block_size))
+        .with(Slider::<RoundaboutRadius>::new("Radius", MIN_SIZE, 200.0, SIZE_STEP, settings.roundabout_radius))
+        .create(&mut commands, &button_materials, &asset_server);
+}
+
+/// Keeps the panel and the settings in sync, they are changed by the widgets and the keyboard
+fn generator_panel_system(
+    mut settings: ResMut<GeneratorSettings>,
+    mut spacing_query: Query<&mut Slider<Spacing>>,
+    mut count_query: Query<&mut NumericField<ParallelCount>>,
+    mut block_size_query: Query<&mut Slider<BlockSize>>,
+    mut radius_query: Query<&mut Slider<RoundaboutRadius>>,
+    mut changed_spacing_query: Query<Mutated<Slider<Spacing>>>,
+    mut changed_count_query: Query<Mutated<NumericField<ParallelCount>>>,
+    mut changed_block_size_query: Query<Mutated<Slider<BlockSize>>>,
+    mut changed_radius_query: Query<Mutated<Slider<RoundaboutRadius>>>
+) {
+    for slider in &mut changed_spacing_query.iter() {
+        settings.spacing = slider.value;
+    }
+
+    for field in &mut changed_count_query.iter() {
+        settings.parallel_count = field.value as usize;
+    }
+
+    for slider in &mut changed_block_size_query.iter() {
+        settings.block_size = slider.value;
+    }
+
+    for slider in &mut changed_radius_query.iter() {
+        settings.roundabout_radius = slider.value;
+    }
+
+    for mut slider in &mut spacing_query.iter() {
+        if slider.value != settings.spacing {
+            slider.value = settings.spacing;
+        }
+    }
+
+    for mut field in &mut count_query.iter() {
+        if field.value as usize != settings.parallel_count {
+            field.value = settings.parallel_count as f32;
+        }
+    }
+
+    for mut slider in &mut block_size_query.iter() {
+        if slider.value != settings.block_size {
+            slider.value = settings.block_size;
+        }
+    }
+
+    for mut slider in &mut radius_query.iter() {
+        if slider.value != settings.roundabout_radius {
+            slider.value = settings.roundabout_radius;
+        }
+    }
+}
+
+/// Area which is currently dragged with the grid tool
+#[derive(Default)]
+pub struct GridTool {
+    area: Option<SelectionArea>,
+
+    /// Area, grid angle and block size of the visible preview
+    preview: Option<(SelectionArea, f32, f32)>
+}
+
+/// Marks the entities showing the streets of the grid tool
+pub struct GridPreview;
+
+fn generator_settings_system(
+    app_state: Res<AppState>,
+    current_action: Res<ui::RoadActions>,
+    actions: Res<Actions>,
+    mut settings: ResMut<GeneratorSettings>
+) {
+    if *app_state != AppState::Editing {
+        return;
+    }
+
+    match *current_action {
+        ui::RoadActions::Parallel => {
+            if actions.just_pressed(Action::Increase) {
+                settings.spacing += SIZE_STEP;
+            } else if actions.just_pressed(Action::Decrease) {
+                settings.spacing = (settings.spacing - SIZE_STEP).max(MIN_SIZE);
+            } else if actions.just_pressed(Action::More) {
+                settings.parallel_count += 1;
+            } else if actions.just_pressed(Action::Fewer) && settings.parallel_count > 1 {
+                settings.parallel_count -= 1;
+            }
+        }
+        ui::RoadActions::Grid => {
+            if actions.just_pressed(Action::Increase) {
+                settings.block_size += SIZE_STEP;
+            } else if actions.just_pressed(Action::Decrease) {
+                settings.block_size = (settings.block_size - SIZE_STEP).max(MIN_SIZE);
+            } else if actions.just_pressed(Action::RotateLeft) {
+                settings.grid_angle += ROTATION_STEP;
+            } else if actions.just_pressed(Action::RotateRight) {
+                settings.grid_angle -= ROTATION_STEP;
+            }
+        }
+        ui::RoadActions::Roundabout => {
+            if actions.just_pressed(Action::Increase) {
+                settings.roundabout_radius += SIZE_STEP;
+            } else if actions.just_pressed(Action::Decrease) {
+                settings.roundabout_radius = (settings.roundabout_radius - SIZE_STEP).max(MIN_SIZE);
+            }
+        }
+        _ => ()
+    }
+}
+
+/// Creates parallels of the clicked street on the side of the cursor
+fn parallel_street_system(
+    app_state: Res<AppState>,
+    current_action: Res<ui::RoadActions>,
+    settings: Res<GeneratorSettings>,
+    state: Res<input::MouseState>,
+    view: Res<CameraView>,
+    actions: Res<Actions>,
+    mut graph_query: Query<&mut RoadSystem>
+) {
+    if *app_state != AppState::Editing || *current_action != ui::RoadActions::Parallel || !actions.just_pressed(Action::Primary) {
+        return;
+    }
+
+    let mouse_pos_ws = crate::mouse_pos_ws(&state, &view);
+
+    for mut road_system in &mut graph_query.iter() {
+        let street = match road_system.pick(mouse_pos_ws) {
+            Some(RoadElement::Street(street)) => street,
+            _ => continue
+        };
+
+        let line = road_system.street_line(street).unwrap();
+        let road_type = road_system.road_type(street).unwrap();
+        let direction = line.point2 - line.point1;
+        let side = (mouse_pos_ws - line.point1).dot(Vec2::new(direction.y(), -direction.x()));
+
+        for parallel in parallel_lines(&line, settings.spacing, settings.parallel_count, side) {
+            road_system.insert_street(parallel.point1, parallel.point2, Street::new(road_type));
+        }
+    }
+}
+
+/// Replaces the clicked intersection with a roundabout or places a new one
+fn roundabout_system(
+    app_state: Res<AppState>,
+    current_action: Res<ui::RoadActions>,
+    settings: Res<GeneratorSettings>,
+    state: Res<input::MouseState>,
+    view: Res<CameraView>,
+    actions: Res<Actions>,
+    mut graph_query: Query<&mut RoadSystem>
+) {
+    if *app_state != AppState::Editing || *current_action != ui::RoadActions::Roundabout || !actions.just_pressed(Action::Primary) {
+        return;
+    }
+
+    let mouse_pos_ws = crate::mouse_pos_ws(&state, &view);
+
+    for mut road_system in &mut graph_query.iter() {
+        road_system.insert_roundabout(mouse_pos_ws, settings.roundabout_radius);
+    }
+}
+
+fn grid_street_system(
+    app_state: Res<AppState>,
+    current_action: Res<ui::RoadActions>,
+    settings: Res<GeneratorSettings>,
+    state: Res<input::MouseState>,
+    view: Res<CameraView>,
+    actions: Res<Actions>,
+    mut tool: ResMut<GridTool>,
+    mut graph_query: Query<&mut RoadSystem>
+) {
+    if *app_state != AppState::Editing {
+        return;
+    }
+
+    if *current_action != ui::RoadActions::Grid {
+        if tool.area.is_some() {
+            tool.area = None;
+        }
+
+        return;
+    }
+
+    let mouse_pos_ws = crate::mouse_pos_ws(&state, &view);
+
+    if actions.just_pressed(Action::Primary) {
+        tool.area = Some(SelectionArea::new(mouse_pos_ws, actions.pressed(Action::Lasso)));
+    }
+
+    match &mut tool.area {
+        Some(area) => area.update(mouse_pos_ws),
+        None => return
+    }
+
+    if !actions.just_released(Action::Primary) {
+        return;
+    }
+
+    if let Some(area) = tool.area.take().and_then(|area| area.polygon()) {
+        for mut road_system in &mut graph_query.iter() {
+            for line in grid_lines(&area, settings.grid_angle, settings.block_size) {
+                road_system.insert_street(line.point1, line.point2, Street::default());
+            }
+        }
+    }
+}
+
+fn grid_preview_system(
+    mut commands: Commands,
+    settings: Res<GeneratorSettings>,
+    mut tool: ResMut<GridTool>,
+    theme_materials: Res<ThemeMaterials>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut preview_query: Query<With<GridPreview, Entity>>
+) {
+    let area = tool.area.as_ref().filter(|area| area.polygon().is_some());
+    let unchanged = match (area, &tool.preview) {
+        (Some(area), Some((shown, angle, block_size))) => {
+            area == shown && settings.grid_angle == *angle && settings.block_size == *block_size
+        }
+        (None, None) => true,
+        _ => false
+    };
+
+    if unchanged {
+        return;
+    }
+
+    for entity in &mut preview_query.iter() {
+        commands.despawn(entity);
+    }
+
+    let area = match area {
+        Some(area) => area.clone(),
+        None => {
+            tool.preview = None;
+            return;
+        }
+    };
+
+    let polygon = area.polygon().unwrap();
+    tool.preview = Some((area, settings.grid_angle, settings.block_size));
+
+    let preview_material = theme_materials.preview.clone();
+    for line in grid_lines(&polygon, settings.grid_angle, settings.block_size) {
+        let outline = offset_polyline(&[line.point1, line.point2], 1.0, JoinType::Miter(2.0), EndType::Butt);
+
+        spawn_polygons(&mut commands, &outline, preview_material.clone(), &mut meshes, 2.5)
+        .with(GridPreview);
+    }
+}
+
+pub struct GeneratorPlugin;
+
+impl Plugin for GeneratorPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<GeneratorSettings>();
+        app.init_resource::<GridTool>();
+        app.add_startup_system(generator_panel_setup.system());
+        app.add_system_to_stage("do_things", generator_panel_system.system());
+        widgets::add_slider::<Spacing>(app);
+        widgets::add_numeric_field::<ParallelCount>(app);
+        widgets::add_slider::<BlockSize>(app);
+        widgets::add_slider::<RoundaboutRadius>(app);
+        app.add_system_to_stage("do_things", generator_settings_system.system());
+        app.add_system_to_stage("do_things", parallel_street_system.system());
+        app.add_system_to_stage("do_things", roundabout_system.system());
+        app.add_system_to_stage("do_things", grid_street_system.system());
+        app.add_system_to_stage("do_things", grid_preview_system.system());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use std::f32::consts::PI;
+    use super::*;
+    use crate::math::operations::Center;
+    use crate::math::rectangle::Rectangle;
+
+    fn total_length(lines: &[Line]) -> f32 {
+        lines.iter().map(|line| line.length()).sum()
+    }
+
+    #[test]
+    fn parallels_on_both_sides() {
+        let line = Line::new(Vec2::new(0.0, 0.0), Vec2::new(100.0, 0.0));
+
+        let right = parallel_lines(&line, 50.0, 2, 1.0);
+        assert_eq!(right.len(), 2);
+        assert_eq!(right[1], Line::new(Vec2::new(0.0, -100.0), Vec2::new(100.0, -100.0)));
+
+        let left = parallel_lines(&line, 50.0, 1, -3.0);
+        assert_eq!(left, vec![Line::new(Vec2::new(0.0, 50.0), Vec2::new(100.0, 50.0))]);
+    }
+
+    #[test]
+    fn grid_in_rectangle() {
+        let area = Rectangle::from_corners(Vec2::new(0.0, 0.0), Vec2::new(300.0, 200.0)).to_polygon();
+        let lines = grid_lines(&area, 0.0, 100.0);
+
+        // boundary, two vertical and one horizontal street inside
+        assert_eq!(lines.len(), 4 + 2 + 1);
+        assert!((total_length(&lines) - (1000.0 + 2.0 * 200.0 + 300.0)).abs() < 1e-2);
+    }
+
+    #[test]
+    fn rotated_grid_is_clipped_to_concave_area() {
+        let l_shape = Polygon::new(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(300.0, 0.0),
+            Vec2::new(300.0, 100.0),
+            Vec2::new(100.0, 100.0),
+            Vec2::new(100.0, 300.0),
+            Vec2::new(0.0, 300.0)
+        ]);
+
+        let lines = grid_lines(&l_shape, PI / 4.0, 50.0);
+
+        assert!(lines.len() > 6);
+        for line in &lines[6..] {
+            assert_ne!(l_shape.classify(line.center()), crate::math::polygon::Containment::Outside);
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use bevy::prelude::*;
+
+use petgraph::graph::{ EdgeIndex, NodeIndex };
+
+use crate::app_state::AppState;
+use crate::keybindings::{ Action, Actions };
+use crate::roadsystem::{ RoadSystem, Street, TrafficControl };
+
+/// Number of edits which are remembered
+const MAX_EDITS: usize = 100;
+
+/// Change of the road system which can be undone
+#[derive(Clone, Debug, PartialEq)]
+pub enum Edit {
+    Street {
+        street: EdgeIndex,
+        before: Street,
+        after: Street
+    },
+    /// Changes the traffic control, moving an intersection changes the streets around it
+    /// and can't be undone
+    Intersection {
+        intersection: NodeIndex,
+        before: TrafficControl,
+        after: TrafficControl
+    },
+
+    /// Swaps the intersections of the street
+    Reverse(EdgeIndex)
+}
+
+impl Edit {
+    fn apply(&self, road_system: &mut RoadSystem) {
+        match self {
+            Edit::Street { street, after, .. } => road_system.set_street(*street, after.clone()),
+            Edit::Intersection { intersection, after, .. } => road_system.set_control(*intersection, *after),
+            Edit::Reverse(street) => road_system.reverse_street(*street)
+        }
+    }
+
+    fn revert(&self, road_system: &mut RoadSystem) {
+        match self {
+            Edit::Street { street, before, .. } => road_system.set_street(*street, before.clone()),
+            Edit::Intersection { intersection, before, .. } => road_system.set_control(*intersection, *before),
+            Edit::Reverse(street) => road_system.reverse_street(*street)
+        }
+    }
+}
+
+/// Edits which can be undone and redone. Only edits of attributes are recorded, so they are
+/// forgotten as soon as streets or intersections are removed and their indices may be reused.
+#[derive(Default)]
+pub struct EditHistory {
+    done: Vec<Edit>,
+    undone: Vec<Edit>,
+
+    /// Revision of the road system the edits refer to
+    revision: usize
+}
+
+impl EditHistory {
+    fn forget_outdated(&mut self, road_system: &RoadSystem) {
+        if self.revision != road_system.revision() {
+            self.done.clear();
+            self.undone.clear();
+            self.revision = road_system.revision();
+        }
+    }
+
+    /// Forgets all edits. Used after structural changes which keep the indices, but which the
+    /// edits can't be undone across.
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+
+    /// Applies the edit and remembers it. Undone edits can not be redone afterwards.
+    pub fn apply(&mut self, road_system: &mut RoadSystem, edit: Edit) {
+        self.forget_outdated(road_system);
+        edit.apply(road_system);
+
+        self.undone.clear();
+        self.done.push(edit);
+
+        if self.done.len() > MAX_EDITS {
+            self.done.remove(0);
+        }
+    }
+
+    /// Reverts the last edit. Returns false if there is nothing to undo.
+    pub fn undo(&mut self, road_system: &mut RoadSystem) -> bool {
+        self.forget_outdated(road_system);
+
+        match self.done.pop() {
+            Some(edit) => {
+                edit.revert(road_system);
+                self.undone.push(edit);
+                true
+            }
+            None => false
+        }
+    }
+
+    /// Applies the last undone edit again. Returns false if there is nothing to redo.
+    pub fn redo(&mut self, road_system: &mut RoadSystem) -> bool {
+        self.forget_outdated(road_system);
+
+        match self.undone.pop() {
+            Some(edit) => {
+                edit.apply(road_system);
+                self.done.push(edit);
+                true
+            }
+            None => false
+        }
+    }
+}
+
+/// Undoes the last edit or redoes the last undone edit
+fn undo_system(
+    app_state: Res<AppState>,
+    actions: Res<Actions>,
+    mut history: ResMut<EditHistory>,
+    mut graph_query: Query<&mut RoadSystem>
+) {
+    let undo = actions.just_pressed(Action::Undo);
+    let redo = actions.just_pressed(Action::Redo);
+
+    if *app_state != AppState::Editing || (!undo && !redo) {
+        return;
+    }
+
+    for mut road_system in &mut graph_query.iter() {
+        if undo {
+            history.undo(&mut road_system);
+        } else {
+            history.redo(&mut road_system);
+        }
+    }
+}
+
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<EditHistory>();
+        app.add_system_to_stage("do_things", undo_system.system());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use super::*;
+    use crate::roadsystem::RoadType;
+
+    #[test]
+    fn undo_and_redo_edits() {
+        let mut road_system = RoadSystem::new();
+        let street = road_system.insert_street(Vec2::new(0.0, 0.0), Vec2::new(200.0, 0.0), Street::default())[0];
+        let intersection = road_system.nearest_intersection(Vec2::new(0.0, 0.0), 1.0).unwrap();
+
+        let mut history = EditHistory::default();
+        history.apply(&mut road_system, Edit::Street { street, before: Street::default(), after: Street::new_one_way(RoadType::Avenue) });
+        history.apply(&mut road_system, Edit::Intersection { intersection, before: TrafficControl::Uncontrolled, after: TrafficControl::Roundabout });
+        history.apply(&mut road_system, Edit::Reverse(street));
+
+        assert_eq!(road_system.street_line(street).unwrap().point1, Vec2::new(200.0, 0.0));
+
+        assert!(history.undo(&mut road_system));
+        assert!(history.undo(&mut road_system));
+        assert_eq!(road_system.street_line(street).unwrap().point1, Vec2::new(0.0, 0.0));
+        assert_eq!(road_system.intersection(intersection).unwrap().control, TrafficControl::Uncontrolled);
+
+        assert!(history.redo(&mut road_system));
+        assert_eq!(road_system.intersection(intersection).unwrap().control, TrafficControl::Roundabout);
+
+        assert!(history.undo(&mut road_system));
+        assert!(history.undo(&mut road_system));
+        assert!(!history.undo(&mut road_system));
+        assert_eq!(road_system.street(street), Some(&Street::default()));
+    }
+
+    #[test]
+    fn removing_streets_forgets_edits() {
+        let mut road_system = RoadSystem::new();
+        let street = road_system.insert_street(Vec2::new(0.0, 0.0), Vec2::new(200.0, 0.0), Street::default())[0];
+
+        let mut history = EditHistory::default();
+        history.apply(&mut road_system, Edit::Street { street, before: Street::default(), after: Street::new_one_way(RoadType::Avenue) });
+
+        road_system.remove_street(street);
+        let new_street = road_system.insert_street(Vec2::new(0.0, 100.0), Vec2::new(200.0, 100.0), Street::new_one_way(RoadType::Highway))[0];
+
+        // the index of the removed street may belong to the new one
+        assert!(!history.undo(&mut road_system));
+        assert_eq!(road_system.street(new_street), Some(&Street::new_one_way(RoadType::Highway)));
+    }
+}
@@ -0,0 +1,268 @@
+use bevy::prelude::*;
+
+use crate::app_state::AppState;
+use crate::input;
+use crate::math::offset::{ offset_polyline, EndType, JoinType };
+use crate::measure::Units;
+use crate::roadsystem::{ RoadElement, RoadSystem, TrafficControl, INTERSECTION_SIZE };
+use crate::shapes::spawn_polygons;
+use crate::theme::ThemeMaterials;
+use crate::ui;
+use crate::view::CameraView;
+
+/// Width of the rim around hovered elements
+const HOVER_RIM: f32 = 3.0;
+
+/// Element below the cursor
+#[derive(Default)]
+pub struct Hover {
+    pub element: Option<RoadElement>,
+
+    /// Element of the visible highlight
+    highlighted: Option<RoadElement>
+}
+
+/// Marks the entities which highlight the hovered element
+pub struct HoverHighlight;
+
+/// Marks the panel showing the properties of the hovered element
+pub struct TooltipPanel;
+
+pub struct TooltipText;
+
+/// Returns the properties of the element shown in its tooltip
+pub fn describe(road_system: &RoadSystem, element: RoadElement, units: &Units) -> Option<String> {
+    match element {
+        RoadElement::Street(street) => {
+            let line = road_system.street_line(street)?;
+            let street = road_system.street(street)?;
+
+            let name = if street.name.is_empty() { "Unnamed street" } else { &street.name };
+            let direction = if street.one_way { ", one-way" } else { "" };
+
+            Some(format!("{} | {}{} | length {}", name, street.road_type.name(), direction, units.format_length(line.length())))
+        }
+        RoadElement::Intersection(node) => {
+            let intersection = road_system.intersection(node)?;
+
+            let control = match intersection.control {
+                TrafficControl::Uncontrolled => "Intersection",
+                TrafficControl::Roundabout => "Roundabout",
+                TrafficControl::TrafficLight => "Traffic light"
+            };
+
+            Some(format!("{} | {} streets", control, road_system.degree(node)))
+        }
+    }
+}
+
+fn hover_system(
+    app_state: Res<AppState>,
+    state: Res<input::MouseState>,
+    view: Res<CameraView>,
+    mut hover: ResMut<Hover>,
+    mut graph_query: Query<&RoadSystem>
+) {
+    // nothing can be picked while another state covers the world
+    if *app_state != AppState::Editing {
+        if hover.element.is_some() {
+            hover.element = None;
+        }
+
+        return;
+    }
+
+    let mouse_pos_ws = crate::mouse_pos_ws(&state, &view);
+
+    for road_system in &mut graph_query.iter() {
+        let element = road_system.pick(mouse_pos_ws);
+
+        if element != hover.element {
+            hover.element = element;
+        }
+    }
+}
+
+fn hover_highlight_system(
+    mut commands: Commands,
+    mut hover: ResMut<Hover>,
+    theme_materials: Res<ThemeMaterials>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut mutated_query: Query<Mutated<RoadSystem>>,
+    mut highlight_query: Query<With<HoverHighlight, Entity>>,
+    mut graph_query: Query<&RoadSystem>
+) {
+    // the hovered element could have been moved
+    let mut mutated = false;
+    for _ in &mut mutated_query.iter() {
+        mutated = true;
+    }
+
+    if hover.element == hover.highlighted && !mutated {
+        return;
+    }
+
+    for entity in &mut highlight_query.iter() {
+        commands.despawn(entity);
+    }
+
+    hover.highlighted = hover.element;
+
+    let hover_material = theme_materials.hover.clone();
+
+    for road_system in &mut graph_query.iter() {
+        match hover.element {
+            Some(RoadElement::Intersection(intersection)) => {
+                if let Some(position) = road_system.intersection_position(intersection) {
+                    commands
+                    .spawn(SpriteComponents {
+                        material: hover_material.clone(),
+                        transform: Transform::from_translation(Vec3::new(position.x(), position.y(), 1.2)),
+                        sprite: Sprite::new(Vec2::new(INTERSECTION_SIZE + 2.0 * HOVER_RIM, INTERSECTION_SIZE + 2.0 * HOVER_RIM)),
+                        ..Default::default()
+                    })
+                    .with(HoverHighlight);
+                }
+            }
+            Some(RoadElement::Street(street)) => {
+                if let (Some(line), Some(road_type)) = (road_system.street_line(street), road_system.road_type(street)) {
+                    let outline = offset_polyline(&[line.point1, line.point2], road_type.width() / 2.0 + HOVER_RIM, JoinType::Miter(2.0), EndType::Butt);
+
+                    spawn_polygons(&mut commands, &outline, hover_material.clone(), &mut meshes, 0.5)
+                    .with(HoverHighlight);
+                }
+            }
+            None => ()
+        }
+    }
+}
+
+fn tooltip_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    button_materials: Res<ui::ButtonMaterials>,
+    state: Res<input::MouseState>,
+    units: Res<Units>,
+    hover: Res<Hover>,
+    mut graph_query: Query<&RoadSystem>,
+    mut panel_query: Query<With<TooltipPanel, (Entity, &mut Style)>>,
+    mut text_query: Query<With<TooltipText, (Entity, &mut Text)>>
+) {
+    let mut description = None;
+    for road_system in &mut graph_query.iter() {
+        description = hover.element.and_then(|element| describe(&road_system, element, &units));
+    }
+
+    let description = match description {
+        Some(description) => description,
+        None => {
+            for (entity, _) in &mut panel_query.iter() {
+                commands.despawn(entity);
+            }
+
+            for (entity, _) in &mut text_query.iter() {
+                commands.despawn(entity);
+            }
+
+            return;
+        }
+    };
+
+    let position = Rect {
+        left: Val::Px(state.mouse_position.x() + 15.0),
+        bottom: Val::Px(state.mouse_position.y() - 40.0),
+        ..Default::default()
+    };
+
+    let mut shown = false;
+    for (_, mut style) in &mut panel_query.iter() {
+        style.position = position;
+        shown = true;
+    }
+
+    for (_, mut text) in &mut text_query.iter() {
+        if text.value != description {
+            text.value = description.clone();
+        }
+    }
+
+    if shown {
+        return;
+    }
+
+    commands
+    .spawn(NodeComponents {
+        style: ui::panel_style(position),
+        material: button_materials.normal.clone(),
+        ..Default::default()
+    })
+    .with(TooltipPanel)
+    .with_children(|parent| {
+        parent
+        .spawn(TextComponents {
+            text: ui::ui_text(&description, &asset_server),
+            ..Default::default()
+        })
+        .with(TooltipText)
+        .with(ui::PanelText);
+    });
+}
+
+pub struct HoverPlugin;
+
+impl Plugin for HoverPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<Hover>();
+        app.add_system(hover_system.system());
+        app.add_system(hover_highlight_system.system());
+        app.add_system(tooltip_system.system());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use super::*;
+    use crate::roadsystem::{ RoadType, Street };
+
+    #[test]
+    fn named_one_way_street() {
+        let mut road_system = RoadSystem::new();
+        let mut street = Street::new_one_way(RoadType::Avenue);
+        street.name = String::from("Main Street");
+        let streets = road_system.insert_street(Vec2::new(0.0, 0.0), Vec2::new(1500.0, 0.0), street);
+
+        assert_eq!(
+            describe(&road_system, RoadElement::Street(streets[0]), &Units::default()),
+            Some(String::from("Main Street | Avenue, one-way | length 1.50 km"))
+        );
+    }
+
+    #[test]
+    fn intersection_controls() {
+        let mut road_system = RoadSystem::new();
+        road_system.insert_street(Vec2::new(0.0, 0.0), Vec2::new(100.0, 0.0), Street::default());
+        road_system.insert_street(Vec2::new(0.0, 0.0), Vec2::new(0.0, 100.0), Street::default());
+
+        let center = road_system
+            .intersections()
+            .into_iter()
+            .find(|intersection| road_system.degree(*intersection) == 2)
+            .unwrap();
+
+        let controls = [
+            (TrafficControl::Uncontrolled, "Intersection | 2 streets"),
+            (TrafficControl::Roundabout, "Roundabout | 2 streets"),
+            (TrafficControl::TrafficLight, "Traffic light | 2 streets")
+        ];
+
+        for (control, expected) in &controls {
+            road_system.set_control(center, *control);
+
+            assert_eq!(
+                describe(&road_system, RoadElement::Intersection(center), &Units::default()),
+                Some(String::from(*expected))
+            );
+        }
+    }
+}
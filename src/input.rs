@@ -3,7 +3,6 @@ use bevy::{
     input::mouse::{MouseButtonInput, MouseMotion},
 };
 
-#[derive(Default)]
 pub struct MouseState {
     pub mouse_button_event_reader: EventReader<MouseButtonInput>,
     pub mouse_motion_event_reader: EventReader<MouseMotion>,
@@ -13,6 +12,18 @@ pub struct MouseState {
     pub mouse_position: Vec2
 }
 
+impl Default for MouseState {
+    fn default() -> MouseState {
+        MouseState {
+            mouse_button_event_reader: Default::default(),
+            mouse_motion_event_reader: Default::default(),
+            cursor_moved_event_reader: Default::default(),
+            last_mouse_left_pressed_position: Vec2::zero(),
+            mouse_position: Vec2::zero()
+        }
+    }
+}
+
 
 /// This system prints out all mouse events as they come in
 pub fn print_mouse_events_system(
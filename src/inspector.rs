@@ -0,0 +1,339 @@
+use bevy::prelude::*;
+
+use petgraph::graph::{ EdgeIndex, NodeIndex };
+
+use crate::history::{ Edit, EditHistory };
+use crate::roadsystem::{ RoadElement, RoadIntersection, RoadSystem, RoadType, Street, TrafficControl };
+use crate::selection::Selection;
+use crate::ui::{ ButtonMaterials, UiContainerWidget };
+use crate::ui::widgets::{ self, Checkbox, Dropdown, LabelledButton, NumericField, Panel, TextField };
+
+/// Largest coordinate which can be entered
+const MAX_COORDINATE: f32 = 10000.0;
+
+/// Change of the position per click
+const POSITION_STEP: f32 = 10.0;
+
+const MAX_LANES: f32 = 6.0;
+
+/// Highest speed limit in km/h which can be entered
+const MAX_SPEED_LIMIT: f32 = 130.0;
+
+/// Change of the speed limit in km/h per click
+const SPEED_LIMIT_STEP: f32 = 10.0;
+
+/// Road type chosen in the inspector
+#[derive(PartialEq, Copy, Clone, Default)]
+pub struct InspectedRoadType(pub RoadType);
+
+/// Traffic control chosen in the inspector
+#[derive(PartialEq, Copy, Clone, Default)]
+pub struct InspectedControl(pub TrafficControl);
+
+/// Sent by the button reversing the inspected street
+#[derive(Clone)]
+pub struct ReverseStreet;
+
+/// Marks the panel showing the attributes of a street
+pub struct StreetInspector;
+
+/// Marks the panel showing the attributes of an intersection
+pub struct IntersectionInspector;
+
+pub struct OneWay;
+pub struct StreetName;
+pub struct Lanes;
+pub struct SpeedLimit;
+pub struct PositionX;
+pub struct PositionY;
+
+/// Element with the attributes its widgets show
+#[derive(Clone, PartialEq)]
+enum Inspected {
+    Street(EdgeIndex, Street),
+    Intersection(NodeIndex, RoadIntersection)
+}
+
+/// Element shown in the inspector, which is the only selected element
+#[derive(Default)]
+pub struct Inspector {
+    shown: Option<Inspected>,
+    reverse_reader: EventReader<ReverseStreet>
+}
+
+fn inspector_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    button_materials: Res<ButtonMaterials>
+) {
+    let position = Rect {
+        right: Val::Px(10.0),
+        bottom: Val::Px(10.0),
+        ..Default::default()
+    };
+
+    let road_types = RoadType::ALL
+        .iter()
+        .map(|road_type| (InspectedRoadType(*road_type), road_type.name()))
+        .collect();
+
+    Panel::new("Street", position)
+        .with(Dropdown::new("Type", road_types))
+        .with(NumericField::<Lanes>::new("Lanes", 1.0, MAX_LANES, 1.0, 1.0))
+        .with(NumericField::<SpeedLimit>::new("Speed limit", SPEED_LIMIT_STEP, MAX_SPEED_LIMIT, SPEED_LIMIT_STEP, 30.0))
+        .with(Checkbox::<OneWay>::new("One-way", false))
+        .with(LabelledButton::new("Reverse", ReverseStreet))
+        .with(TextField::<StreetName>::new("Name", ""))
+        .create(&mut commands, &button_materials, &asset_server);
+    commands.with(StreetInspector);
+
+    Panel::new("Intersection", position)
+        .with(NumericField::<PositionX>::new("X", -MAX_COORDINATE, MAX_COORDINATE, POSITION_STEP, 0.0))
+        .with(NumericField::<PositionY>::new("Y", -MAX_COORDINATE, MAX_COORDINATE, POSITION_STEP, 0.0))
+        .with(Dropdown::new("Control", vec![
+            (InspectedControl(TrafficControl::Uncontrolled), "Uncontrolled"),
+            (InspectedControl(TrafficControl::Roundabout), "Roundabout"),
+            (InspectedControl(TrafficControl::TrafficLight), "Traffic light")
+        ]))
+        .create(&mut commands, &button_materials, &asset_server);
+    commands.with(IntersectionInspector);
+}
+
+/// Keeps the widgets and the inspected element in sync. Changed widgets are applied as
+/// undoable edits except for moving intersections, changes of the element from elsewhere
+/// are shown in the widgets.
+fn inspector_system(
+    mut selection: ResMut<Selection>,
+    reverse_events: Res<Events<ReverseStreet>>,
+    mut inspector: ResMut<Inspector>,
+    mut history: ResMut<EditHistory>,
+    mut road_type: ResMut<InspectedRoadType>,
+    mut control: ResMut<InspectedControl>,
+    mut one_way_query: Query<&mut Checkbox<OneWay>>,
+    mut name_query: Query<&mut TextField<StreetName>>,
+    mut lanes_query: Query<&mut NumericField<Lanes>>,
+    mut speed_limit_query: Query<&mut NumericField<SpeedLimit>>,
+    mut x_query: Query<&mut NumericField<PositionX>>,
+    mut y_query: Query<&mut NumericField<PositionY>>,
+    mut graph_query: Query<&mut RoadSystem>
+) {
+    let inspector = &mut *inspector;
+    let reversed = inspector.reverse_reader.iter(&reverse_events).count() > 0;
+
+    for mut road_system in &mut graph_query.iter() {
+        let element = match selection.elements.iter().next() {
+            Some(element) if selection.elements.len() == 1 => *element,
+            _ => {
+                inspector.shown = None;
+                continue;
+            }
+        };
+
+        let current = match element {
+            RoadElement::Street(street) => road_system.street(street).map(|attributes| Inspected::Street(street, attributes.clone())),
+            RoadElement::Intersection(intersection) => road_system.intersection(intersection).map(|attributes| Inspected::Intersection(intersection, attributes.clone()))
+        };
+
+        let current = match current {
+            Some(current) => current,
+            None => {
+                inspector.shown = None;
+                continue;
+            }
+        };
+
+        // the widgets show the attributes of another element
+        let same_element = match (&inspector.shown, &current) {
+            (Some(Inspected::Street(shown, _)), Inspected::Street(street, _)) => shown == street,
+            (Some(Inspected::Intersection(shown, _)), Inspected::Intersection(intersection, _)) => shown == intersection,
+            _ => false
+        };
+
+        let edited = match (&inspector.shown, &current) {
+            (Some(Inspected::Street(street, shown)), _) if same_element => {
+                if reversed {
+                    history.apply(&mut road_system, Edit::Reverse(*street));
+                }
+
+                let mut edited = shown.clone();
+
+                // a new road type resets the lanes and the speed limit the fields still show
+                if edited.road_type != road_type.0 {
+                    edited.set_road_type(road_type.0);
+                } else {
+                    for field in &mut lanes_query.iter() {
+                        edited.lanes = field.value as usize;
+                    }
+
+                    for field in &mut speed_limit_query.iter() {
+                        edited.speed_limit = field.value;
+                    }
+                }
+
+                for checkbox in &mut one_way_query.iter() {
+                    edited.one_way = checkbox.checked;
+                }
+
+                // the name is applied when the input ends
+                for field in &mut name_query.iter() {
+                    if !field.focused {
+                        edited.name = field.value.clone();
+                    }
+                }
+
+                Some(Inspected::Street(*street, edited))
+            }
+            (Some(Inspected::Intersection(intersection, shown)), _) if same_element => {
+                let intersection = *intersection;
+                let mut position = shown.position;
+
+                for field in &mut x_query.iter() {
+                    position.set_x(field.value);
+                }
+
+                for field in &mut y_query.iter() {
+                    position.set_y(field.value);
+                }
+
+                // moving splits crossed streets and can merge the intersection into another
+                // one, like dragging it, which can't be undone
+                if position != shown.position {
+                    let moved = road_system.move_intersection(intersection, position);
+                    history.clear();
+
+                    if moved != intersection {
+                        selection.elements.remove(&element);
+                        selection.elements.insert(RoadElement::Intersection(moved));
+                    }
+
+                    // the widgets show the moved intersection in the next frame
+                    inspector.shown = None;
+                    continue;
+                }
+
+                let mut edited = shown.clone();
+                edited.control = control.0;
+
+                Some(Inspected::Intersection(intersection, edited))
+            }
+            _ => None
+        };
+
+        match edited {
+            Some(edited) if Some(&edited) != inspector.shown.as_ref() => {
+                let edit = match (&current, &edited) {
+                    (Inspected::Street(street, before), Inspected::Street(_, after)) => {
+                        Edit::Street { street: *street, before: before.clone(), after: after.clone() }
+                    }
+                    (Inspected::Intersection(intersection, before), Inspected::Intersection(_, after)) => {
+                        Edit::Intersection { intersection: *intersection, before: before.control, after: after.control }
+                    }
+                    _ => continue
+                };
+
+                history.apply(&mut road_system, edit);
+                inspector.shown = Some(edited);
+            }
+            _ if inspector.shown.as_ref() != Some(&current) => inspector.shown = Some(current),
+            _ => continue
+        }
+
+        // show the attributes of the element in the widgets
+        match &inspector.shown {
+            Some(Inspected::Street(_, street)) => {
+                if road_type.0 != street.road_type {
+                    road_type.0 = street.road_type;
+                }
+
+                for mut checkbox in &mut one_way_query.iter() {
+                    if checkbox.checked != street.one_way {
+                        checkbox.checked = street.one_way;
+                    }
+                }
+
+                for mut field in &mut name_query.iter() {
+                    if !field.focused && field.value != street.name {
+                        field.value = street.name.clone();
+                    }
+                }
+
+                for mut field in &mut lanes_query.iter() {
+                    if field.value != street.lanes as f32 {
+                        field.value = street.lanes as f32;
+                    }
+                }
+
+                for mut field in &mut speed_limit_query.iter() {
+                    if field.value != street.speed_limit {
+                        field.value = street.speed_limit;
+                    }
+                }
+            }
+            Some(Inspected::Intersection(_, intersection)) => {
+                if control.0 != intersection.control {
+                    control.0 = intersection.control;
+                }
+
+                for mut field in &mut x_query.iter() {
+                    if field.value != intersection.position.x() {
+                        field.value = intersection.position.x();
+                    }
+                }
+
+                for mut field in &mut y_query.iter() {
+                    if field.value != intersection.position.y() {
+                        field.value = intersection.position.y();
+                    }
+                }
+            }
+            None => ()
+        }
+    }
+}
+
+/// Shows the panel matching the inspected element and hides the other one
+fn inspector_panel_system(
+    inspector: Res<Inspector>,
+    mut street_panel_query: Query<With<StreetInspector, &mut Style>>,
+    mut intersection_panel_query: Query<With<IntersectionInspector, &mut Style>>
+) {
+    let (street, intersection) = match inspector.shown {
+        Some(Inspected::Street(_, _)) => (Display::Flex, Display::None),
+        Some(Inspected::Intersection(_, _)) => (Display::None, Display::Flex),
+        None => (Display::None, Display::None)
+    };
+
+    for mut style in &mut street_panel_query.iter() {
+        if style.display != street {
+            style.display = street;
+        }
+    }
+
+    for mut style in &mut intersection_panel_query.iter() {
+        if style.display != intersection {
+            style.display = intersection;
+        }
+    }
+}
+
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<Inspector>();
+        app.init_resource::<InspectedRoadType>();
+        app.init_resource::<InspectedControl>();
+        app.add_startup_system(inspector_setup.system());
+        app.add_system_to_stage("do_things", inspector_system.system());
+        app.add_system_to_stage("do_things", inspector_panel_system.system());
+        widgets::add_choice_widgets::<InspectedRoadType>(app);
+        widgets::add_choice_widgets::<InspectedControl>(app);
+        widgets::add_button_events::<ReverseStreet>(app);
+        widgets::add_checkbox::<OneWay>(app);
+        widgets::add_text_field::<StreetName>(app);
+        widgets::add_numeric_field::<Lanes>(app);
+        widgets::add_numeric_field::<SpeedLimit>(app);
+        widgets::add_numeric_field::<PositionX>(app);
+        widgets::add_numeric_field::<PositionY>(app);
+    }
+}
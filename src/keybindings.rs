@@ -0,0 +1,525 @@
+use bevy::prelude::*;
+
+use std::collections::HashSet;
+use std::fs;
+
+use crate::ui::notifications::Notifications;
+use crate::ui::widgets::TextFocus;
+
+/// File the keybindings of the user are loaded from
+const KEYBINDINGS_FILE: &str = "keybindings.txt";
+
+/// Named action which is triggered by keys and mouse buttons
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub enum Action {
+    /// Builds, selects or places depending on the tool
+    Primary,
+
+    /// Finishes or reverses the primary action depending on the tool
+    Secondary,
+    Cancel,
+    Undo,
+    Redo,
+    Copy,
+    Paste,
+
+    /// Saves the city while editing
+    Save,
+
+    /// Saves the template of the stamp tool to the library
+    SaveTemplate,
+    Delete,
+    CycleRoadType,
+    RotateLeft,
+    RotateRight,
+    Increase,
+    Decrease,
+    More,
+    Fewer,
+
+    /// Adds to the selection or applies to the whole stretch
+    Extend,
+
+    /// Selects with a lasso instead of a box
+    Lasso,
+    SnapToGrid,
+    ConstrainAngle,
+    AlignToStreet,
+    NextAngleStep,
+    PanUp,
+    PanDown,
+    PanLeft,
+    PanRight,
+    ZoomIn,
+    ZoomOut,
+    NextTheme,
+    SelectTool,
+    BuildTool,
+    PolylineTool,
+    DemolishTool,
+    UpgradeTool,
+    StampTool,
+    ParallelTool,
+    GridTool,
+    RoundaboutTool,
+    MeasureTool,
+
+    /// Picks one of the first nine templates of the library
+    Template(usize)
+}
+
+/// Actions without the templates, which are numbered from 1 to 9
+const ACTIONS: &[(Action, &str)] = &[
+    (Action::Primary, "primary"),
+    (Action::Secondary, "secondary"),
+    (Action::Cancel, "cancel"),
+    (Action::Undo, "undo"),
+    (Action::Redo, "redo"),
+    (Action::Copy, "copy"),
+    (Action::Paste, "paste"),
+    (Action::Save, "save"),
+    (Action::SaveTemplate, "save_template"),
+    (Action::Delete, "delete"),
+    (Action::CycleRoadType, "cycle_road_type"),
+    (Action::RotateLeft, "rotate_left"),
+    (Action::RotateRight, "rotate_right"),
+    (Action::Increase, "increase"),
+    (Action::Decrease, "decrease"),
+    (Action::More, "more"),
+    (Action::Fewer, "fewer"),
+    (Action::Extend, "extend"),
+    (Action::Lasso, "lasso"),
+    (Action::SnapToGrid, "snap_to_grid"),
+    (Action::ConstrainAngle, "constrain_angle"),
+    (Action::AlignToStreet, "align_to_street"),
+    (Action::NextAngleStep, "next_angle_step"),
+    (Action::PanUp, "pan_up"),
+    (Action::PanDown, "pan_down"),
+    (Action::PanLeft, "pan_left"),
+    (Action::PanRight, "pan_right"),
+    (Action::ZoomIn, "zoom_in"),
+    (Action::ZoomOut, "zoom_out"),
+    (Action::NextTheme, "next_theme"),
+    (Action::SelectTool, "select_tool"),
+    (Action::BuildTool, "build_tool"),
+    (Action::PolylineTool, "polyline_tool"),
+    (Action::DemolishTool, "demolish_tool"),
+    (Action::UpgradeTool, "upgrade_tool"),
+    (Action::StampTool, "stamp_tool"),
+    (Action::ParallelTool, "parallel_tool"),
+    (Action::GridTool, "grid_tool"),
+    (Action::RoundaboutTool, "roundabout_tool"),
+    (Action::MeasureTool, "measure_tool")
+];
+
+/// Bindings used for actions which are not in the keybindings file
+const DEFAULT_BINDINGS: &str = "
+primary mouse_left
+secondary mouse_right
+cancel escape
+undo ctrl+z
+redo ctrl+y ctrl+shift+z
+copy ctrl+c
+paste ctrl+v
+save ctrl+s
+save_template ctrl+shift+s
+delete delete
+cycle_road_type t
+rotate_left q
+rotate_right e
+increase up
+decrease down
+more right
+fewer left
+extend lshift rshift
+lasso lalt
+snap_to_grid lcontrol
+constrain_angle lshift
+align_to_street lalt
+next_angle_step tab
+pan_up w
+pan_down s
+pan_left a
+pan_right d
+zoom_in equals
+zoom_out minus
+next_theme f11
+select_tool f1
+build_tool f2
+polyline_tool f3
+demolish_tool f4
+upgrade_tool f5
+stamp_tool f6
+parallel_tool f7
+grid_tool f8
+roundabout_tool f9
+measure_tool f10
+template_1 1
+template_2 2
+template_3 3
+template_4 4
+template_5 5
+template_6 6
+template_7 7
+template_8 8
+template_9 9
+";
+
+const KEYS: &[(&str, KeyCode)] = &[
+    ("a", KeyCode::A), ("b", KeyCode::B), ("c", KeyCode::C), ("d", KeyCode::D),
+    ("e", KeyCode::E), ("f", KeyCode::F), ("g", KeyCode::G), ("h", KeyCode::H),
+    ("i", KeyCode::I), ("j", KeyCode::J), ("k", KeyCode::K), ("l", KeyCode::L),
+    ("m", KeyCode::M), ("n", KeyCode::N), ("o", KeyCode::O), ("p", KeyCode::P),
+    ("q", KeyCode::Q), ("r", KeyCode::R), ("s", KeyCode::S), ("t", KeyCode::T),
+    ("u", KeyCode::U), ("v", KeyCode::V), ("w", KeyCode::W), ("x", KeyCode::X),
+    ("y", KeyCode::Y), ("z", KeyCode::Z),
+    ("0", KeyCode::Key0), ("1", KeyCode::Key1), ("2", KeyCode::Key2), ("3", KeyCode::Key3),
+    ("4", KeyCode::Key4), ("5", KeyCode::Key5), ("6", KeyCode::Key6), ("7", KeyCode::Key7),
+    ("8", KeyCode::Key8), ("9", KeyCode::Key9),
+    ("f1", KeyCode::F1), ("f2", KeyCode::F2), ("f3", KeyCode::F3), ("f4", KeyCode::F4),
+    ("f5", KeyCode::F5), ("f6", KeyCode::F6), ("f7", KeyCode::F7), ("f8", KeyCode::F8),
+    ("f9", KeyCode::F9), ("f10", KeyCode::F10), ("f11", KeyCode::F11), ("f12", KeyCode::F12),
+    ("escape", KeyCode::Escape), ("tab", KeyCode::Tab), ("space", KeyCode::Space),
+    ("return", KeyCode::Return), ("backspace", KeyCode::Back), ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert), ("home", KeyCode::Home), ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp), ("pagedown", KeyCode::PageDown),
+    ("up", KeyCode::Up), ("down", KeyCode::Down), ("left", KeyCode::Left), ("right", KeyCode::Right),
+    ("lshift", KeyCode::LShift), ("rshift", KeyCode::RShift),
+    ("lcontrol", KeyCode::LControl), ("rcontrol", KeyCode::RControl),
+    ("lalt", KeyCode::LAlt), ("ralt", KeyCode::RAlt),
+    ("equals", KeyCode::Equals), ("minus", KeyCode::Minus),
+    ("comma", KeyCode::Comma), ("period", KeyCode::Period)
+];
+
+const MOUSE_BUTTONS: &[(&str, MouseButton)] = &[
+    ("mouse_left", MouseButton::Left),
+    ("mouse_right", MouseButton::Right),
+    ("mouse_middle", MouseButton::Middle)
+];
+
+impl Action {
+    pub fn name(&self) -> String {
+        match self {
+            Action::Template(number) => format!("template_{}", number),
+            action => ACTIONS.iter().find(|(other, _)| other == action).unwrap().1.to_string()
+        }
+    }
+
+    fn parse(name: &str) -> Option<Action> {
+        if let Some((action, _)) = ACTIONS.iter().find(|(_, other)| *other == name) {
+            return Some(*action);
+        }
+
+        let number = name.strip_prefix("template_")?.parse::<usize>().ok()?;
+        if number >= 1 && number <= 9 {
+            Some(Action::Template(number))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub enum Button {
+    Key(KeyCode),
+    Mouse(MouseButton)
+}
+
+/// Modifier keys which are held down, either the left or the right one
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub struct Modifiers {
+    pub control: bool,
+    pub shift: bool,
+    pub alt: bool
+}
+
+impl Modifiers {
+    /// Returns true if all modifiers of the other ones are held as well
+    fn contains(&self, other: &Modifiers) -> bool {
+        (self.control || !other.control) && (self.shift || !other.shift) && (self.alt || !other.alt)
+    }
+}
+
+/// Button which has to be pressed while holding the modifiers
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct Binding {
+    pub button: Button,
+    pub modifiers: Modifiers
+}
+
+impl Binding {
+    /// Reads a binding like `ctrl+shift+z` or `mouse_left`
+    fn parse(text: &str) -> Option<Binding> {
+        let mut modifiers = Modifiers::default();
+        let mut parts: Vec<&str> = text.split('+').collect();
+        let button = parts.pop()?;
+
+        for part in parts {
+            match part {
+                "ctrl" => modifiers.control = true,
+                "shift" => modifiers.shift = true,
+                "alt" => modifiers.alt = true,
+                _ => return None
+            }
+        }
+
+        let button = KEYS
+            .iter()
+            .find(|(name, _)| *name == button)
+            .map(|(_, key)| Button::Key(*key))
+            .or_else(|| MOUSE_BUTTONS.iter().find(|(name, _)| *name == button).map(|(_, mouse_button)| Button::Mouse(*mouse_button)))?;
+
+        Some(Binding { button, modifiers })
+    }
+}
+
+/// Keys and mouse buttons triggering each action
+pub struct Keybindings {
+    bindings: Vec<(Action, Binding)>
+}
+
+impl Default for Keybindings {
+    fn default() -> Keybindings {
+        Keybindings::parse(DEFAULT_BINDINGS).unwrap()
+    }
+}
+
+impl Keybindings {
+    /// Reads one action per line followed by its bindings:
+    ///
+    /// ```text
+    /// undo ctrl+z
+    /// redo ctrl+y ctrl+shift+z
+    /// ```
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn parse(text: &str) -> Result<Keybindings, String> {
+        let mut bindings = Vec::new();
+
+        for (number, line) in text.lines().enumerate() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let error = || format!("invalid line {}: {}", number + 1, line);
+
+            match words.as_slice() {
+                [] => (),
+                [comment, ..] if comment.starts_with('#') => (),
+                [action, buttons @ ..] if !buttons.is_empty() => {
+                    let action = Action::parse(action).ok_or_else(error)?;
+
+                    for button in buttons {
+                        bindings.push((action, Binding::parse(button).ok_or_else(error)?));
+                    }
+                }
+                _ => return Err(error())
+            }
+        }
+
+        Ok(Keybindings { bindings })
+    }
+
+    /// Loads the keybindings file. Actions which are not in the file keep their default
+    /// bindings, all actions keep them if the file does not exist.
+    pub fn load() -> Result<Keybindings, String> {
+        let mut keybindings = Keybindings::default();
+
+        let text = match fs::read_to_string(KEYBINDINGS_FILE) {
+            Ok(text) => text,
+            Err(_) => return Ok(keybindings)
+        };
+
+        let user_bindings = Keybindings::parse(&text).map_err(|error| format!("{}: {}", KEYBINDINGS_FILE, error))?;
+        keybindings.replace(user_bindings);
+
+        Ok(keybindings)
+    }
+
+    /// Replaces the bindings of all actions which are bound by the other keybindings
+    pub fn replace(&mut self, other: Keybindings) {
+        let actions: HashSet<Action> = other.bindings.iter().map(|(action, _)| *action).collect();
+
+        self.bindings.retain(|(action, _)| !actions.contains(action));
+        self.bindings.extend(other.bindings);
+    }
+
+    /// Returns the actions triggered by the button while holding the modifiers. A binding
+    /// requiring more of the held modifiers takes precedence, so `ctrl+z` does not trigger
+    /// the action bound to `z`.
+    pub fn triggered(&self, button: Button, modifiers: Modifiers) -> Vec<Action> {
+        let matching: Vec<&(Action, Binding)> = self.bindings
+            .iter()
+            .filter(|(_, binding)| binding.button == button && modifiers.contains(&binding.modifiers))
+            .collect();
+
+        matching
+            .iter()
+            .filter(|(_, binding)| {
+                !matching.iter().any(|(_, other)| other.modifiers != binding.modifiers && other.modifiers.contains(&binding.modifiers))
+            })
+            .map(|(action, _)| *action)
+            .collect()
+    }
+
+    fn buttons(&self) -> HashSet<Button> {
+        self.bindings.iter().map(|(_, binding)| binding.button).collect()
+    }
+}
+
+/// State of the actions in the current frame. Systems query the actions instead of the
+/// keys and mouse buttons.
+#[derive(Default)]
+pub struct Actions {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+    just_released: HashSet<Action>,
+
+    /// Mouse buttons which were pressed over the user interface. They trigger no actions in
+    /// the world until they are released.
+    captured: HashSet<MouseButton>
+}
+
+impl Actions {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    pub fn just_released(&self, action: Action) -> bool {
+        self.just_released.contains(&action)
+    }
+}
+
+fn action_system(
+    keybindings: Res<Keybindings>,
+    text_focus: Res<TextFocus>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_button_input: Res<Input<MouseButton>>,
+    mut actions: ResMut<Actions>,
+    mut interaction_query: Query<&Interaction>
+) {
+    let mut over_ui = false;
+    for interaction in &mut interaction_query.iter() {
+        over_ui |= *interaction != Interaction::None;
+    }
+
+    let modifiers = Modifiers {
+        control: keyboard_input.pressed(KeyCode::LControl) || keyboard_input.pressed(KeyCode::RControl),
+        shift: keyboard_input.pressed(KeyCode::LShift) || keyboard_input.pressed(KeyCode::RShift),
+        alt: keyboard_input.pressed(KeyCode::LAlt) || keyboard_input.pressed(KeyCode::RAlt)
+    };
+
+    let previously_pressed = std::mem::take(&mut actions.pressed);
+    actions.just_pressed.clear();
+    actions.just_released.clear();
+
+    for button in keybindings.buttons() {
+        if let Button::Mouse(mouse_button) = button {
+            if over_ui && mouse_button_input.just_pressed(mouse_button) {
+                actions.captured.insert(mouse_button);
+            } else if !mouse_button_input.pressed(mouse_button) {
+                actions.captured.remove(&mouse_button);
+            }
+        }
+    }
+
+    for button in keybindings.buttons() {
+        let (pressed, just_pressed) = match button {
+            Button::Key(_) if text_focus.0.is_some() => (false, false),
+            Button::Key(key) => (keyboard_input.pressed(key), keyboard_input.just_pressed(key)),
+            Button::Mouse(mouse_button) if actions.captured.contains(&mouse_button) => (false, false),
+            Button::Mouse(mouse_button) => (mouse_button_input.pressed(mouse_button), mouse_button_input.just_pressed(mouse_button))
+        };
+
+        if !pressed {
+            continue;
+        }
+
+        for action in keybindings.triggered(button, modifiers) {
+            actions.pressed.insert(action);
+
+            if just_pressed {
+                actions.just_pressed.insert(action);
+            }
+        }
+    }
+
+    let released: Vec<Action> = previously_pressed.difference(&actions.pressed).cloned().collect();
+    actions.just_released.extend(released);
+}
+
+/// Loads the keybindings of the user and tells the user why they could not be loaded
+fn keybinding_setup(
+    mut keybindings: ResMut<Keybindings>,
+    mut notifications: ResMut<Notifications>
+) {
+    match Keybindings::load() {
+        Ok(loaded) => *keybindings = loaded,
+        Err(error) => notifications.error(&format!("Could not load keybindings {}", error))
+    }
+}
+
+pub struct KeybindingPlugin;
+
+impl Plugin for KeybindingPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<Keybindings>();
+        app.add_startup_system(keybinding_setup.system());
+        app.init_resource::<Actions>();
+        app.add_system_to_stage(stage::PRE_UPDATE, action_system.system());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use super::*;
+
+    const CONTROL: Modifiers = Modifiers { control: true, shift: false, alt: false };
+    const CONTROL_SHIFT: Modifiers = Modifiers { control: true, shift: true, alt: false };
+
+    #[test]
+    fn all_actions_have_default_bindings() {
+        let keybindings = Keybindings::default();
+
+        for (action, name) in ACTIONS.iter() {
+            assert_eq!(Action::parse(name), Some(*action));
+            assert!(keybindings.bindings.iter().any(|(other, _)| other == action), "{} is not bound", name);
+        }
+
+        assert_eq!(Action::parse("template_3"), Some(Action::Template(3)));
+        assert_eq!(Action::Template(3).name(), "template_3");
+        assert_eq!(Action::parse("template_10"), None);
+    }
+
+    #[test]
+    fn bindings_with_more_modifiers_take_precedence() {
+        let keybindings = Keybindings::default();
+        let z = Button::Key(KeyCode::Z);
+        let s = Button::Key(KeyCode::S);
+        let left = Button::Mouse(MouseButton::Left);
+
+        assert_eq!(keybindings.triggered(z, CONTROL), vec![Action::Undo]);
+        assert_eq!(keybindings.triggered(z, CONTROL_SHIFT), vec![Action::Redo]);
+        assert_eq!(keybindings.triggered(s, Modifiers::default()), vec![Action::PanDown]);
+        assert_eq!(keybindings.triggered(s, CONTROL), vec![Action::Save]);
+        assert_eq!(keybindings.triggered(s, CONTROL_SHIFT), vec![Action::SaveTemplate]);
+        assert_eq!(keybindings.triggered(left, CONTROL_SHIFT), vec![Action::Primary]);
+    }
+
+    #[test]
+    fn user_bindings_replace_defaults() {
+        let mut keybindings = Keybindings::default();
+        keybindings.replace(Keybindings::parse("# comment\n\nundo ctrl+u backspace\n").unwrap());
+
+        assert!(keybindings.triggered(Button::Key(KeyCode::Z), CONTROL).is_empty());
+        assert_eq!(keybindings.triggered(Button::Key(KeyCode::U), CONTROL), vec![Action::Undo]);
+        assert_eq!(keybindings.triggered(Button::Key(KeyCode::Back), Modifiers::default()), vec![Action::Undo]);
+        assert_eq!(keybindings.triggered(Button::Key(KeyCode::Y), CONTROL), vec![Action::Redo]);
+
+        assert!(Keybindings::parse("undo").is_err());
+        assert!(Keybindings::parse("jump space").is_err());
+        assert!(Keybindings::parse("undo hyper+z").is_err());
+    }
+}
@@ -1,12 +1,33 @@
-use bevy::{
-    prelude::*,
-    render::pass::ClearColor,
-};
+use bevy::prelude::*;
+
+use petgraph::graph::NodeIndex;
+
+use app_state::AppState;
+use keybindings::Action;
+use view::CameraView;
 
 mod input;
+mod app_state;
+mod buildings;
 mod city;
+mod constraints;
+mod context_menu;
+mod generators;
+mod history;
+mod hover;
+mod inspector;
+mod keybindings;
+mod measure;
+mod minimap;
 mod roadsystem;
+mod routing;
+mod selection;
+mod shapes;
+mod statistics;
+mod templates;
+mod theme;
 mod ui;
+mod view;
 
 mod math;
 
@@ -17,19 +38,106 @@ const WINDOW_HEIGHT: u32 = 1080;
 
 struct TempStraightStreet;
 
+/// Streets shorter than this are not built
+const MIN_STREET_LENGTH: f32 = 100.0;
+
+/// Distance the view moves per second while panning, in pixels on the screen
+const PAN_SPEED: f32 = 600.0;
+
+/// Factor the zoom changes by per key press
+const ZOOM_STEP: f32 = 1.25;
+const MIN_ZOOM: f32 = 0.25;
+const MAX_ZOOM: f32 = 8.0;
+
+/// Street chain which is currently built with the polyline tool
+#[derive(Default)]
+struct StreetChain {
+    /// Position of the first click if no street was built yet
+    start: Option<Vec2>,
+
+    /// Intersection the next street is connected to
+    last: Option<NodeIndex>
+}
+
+/// What a click of the polyline tool did
+#[derive(PartialEq, Debug)]
+enum ChainClick {
+    Started,
+    Extended,
+
+    /// Nothing was built because the street would be too short
+    TooShort
+}
+
+impl StreetChain {
+    fn is_active(&self) -> bool {
+        self.start.is_some() || self.last.is_some()
+    }
+
+    /// Returns the position the next street of the chain starts at
+    fn start_position(&self, road_system: &roadsystem::RoadSystem) -> Option<Vec2> {
+        match (self.start, self.last) {
+            (_, Some(last)) => road_system.intersection_position(last),
+            (start, None) => start
+        }
+    }
+
+    /// Starts the chain at the position or builds the next street from the end of the chain
+    /// to the position. The intersection the street ends at is the start of the next one.
+    fn click(&mut self, position: Vec2, road_system: &mut roadsystem::RoadSystem) -> ChainClick {
+        let start = match self.start_position(road_system) {
+            Some(start) => start,
+            None => {
+                // the end of the chain may have been removed in the meantime
+                self.start = Some(position);
+                self.last = None;
+                return ChainClick::Started;
+            }
+        };
+
+        if (position - start).length() < MIN_STREET_LENGTH {
+            return ChainClick::TooShort;
+        }
+
+        let from = match self.last {
+            Some(last) => last,
+            None => road_system.find_or_insert_intersection(start)
+        };
+        let to = road_system.find_or_insert_intersection(position);
+
+        road_system.connect_intersections(from, to, roadsystem::Street::default());
+
+        self.start = None;
+        self.last = Some(to);
+        ChainClick::Extended
+    }
+
+    /// Ends the chain, returns false if there was no chain to finish
+    fn finish(&mut self) -> bool {
+        let active = self.is_active();
+
+        self.start = None;
+        self.last = None;
+        active
+    }
+}
+
+/// Shows the length of the temporary street
+struct StreetLengthLabel;
+
 // Transforms the mouse position from screen into world coordinate system
-fn mouse_pos_ws(mouse_pos: Vec2) -> Vec2 {
+fn mouse_pos_ws(state: &input::MouseState, view: &CameraView) -> Vec2 {
     Vec2::new(
-        mouse_pos.x() - (WINDOW_WIDTH as f32) / 2.0,
-        mouse_pos.y() - (WINDOW_HEIGHT as f32) / 2.0      
-    )
+        state.mouse_position.x() - (WINDOW_WIDTH as f32) / 2.0,
+        state.mouse_position.y() - (WINDOW_HEIGHT as f32) / 2.0
+    ) * view.zoom + view.position
 }
 
-fn spawn_temp_street(commands: &mut Commands, materials: &mut ResMut<Assets<ColorMaterial>>) {
+fn spawn_temp_street(commands: &mut Commands, theme_materials: &theme::ThemeMaterials) {
         // create temp street for visualization
         commands
         .spawn(SpriteComponents {
-            material: materials.add(Color::rgb(0.0, 0.1, 0.0).into()),            
+            material: theme_materials.temp_street.clone(),
             transform: Transform::from_translation_rotation(Vec3::new(std::f32::MIN, std::f32::MIN, 0.0), Quat::from_rotation_z(0.0)),            
             sprite: Sprite::new(Vec2::new(30.0, 30.0)),
             ..Default::default()
@@ -43,25 +151,37 @@ fn spawn_temp_street(commands: &mut Commands, materials: &mut ResMut<Assets<Colo
 fn road_network_change_tracking_system(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
+    theme_materials: Res<theme::ThemeMaterials>,
     mut q1: Query<Mutated<roadsystem::RoadSystem>>,
+    mut intersection_query: Query<With<roadsystem::RoadIntersection, Entity>>,
+    mut road_query: Query<With<roadsystem::StreetOutline, Entity>>,
 ) {
     for a in &mut q1.iter() {
-        a.update(&mut commands, &mut materials, &mut meshes);
+        for entity in &mut intersection_query.iter() {
+            commands.despawn(entity);
+        }
+
+        for entity in &mut road_query.iter() {
+            commands.despawn(entity);
+        }
+
+        a.update(&mut commands, &theme_materials, &mut meshes);
     }    
 }
 
 fn destroy_street(
     mut commands: Commands,    
+    app_state: Res<AppState>,
     current_action: Res<ui::RoadActions>,
     mut state: ResMut<input::MouseState>,
+    view: Res<CameraView>,
     mut graph_query: Query<(&Graph, &mut roadsystem::RoadSystem)>
 ) {
-    if *current_action != ui::RoadActions::Demolish {
+    if *app_state != AppState::Editing || *current_action != ui::RoadActions::Demolish {
         return;
     }
 
-    let mouse_pos_ws = mouse_pos_ws(state.mouse_position);
+    let mouse_pos_ws = mouse_pos_ws(&state, &view);
 
     for (_, mut road_system) in &mut graph_query.iter() { 
         road_system.point_intersect_connection(mouse_pos_ws);
@@ -71,35 +191,95 @@ fn destroy_street(
     
 }
 
-fn build_street( 
-    mut commands: Commands,    
+/// Left click upgrades and right click downgrades the clicked street, with shift the whole
+/// stretch up to the next junctions
+fn upgrade_street(
+    app_state: Res<AppState>,
     current_action: Res<ui::RoadActions>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    mut state: ResMut<input::MouseState>,
-    mouse_button_input: Res<Input<MouseButton>>,
-    mut temp_query: Query<With<TempStraightStreet, (Entity, &mut Sprite, &mut Transform, &mut city::StraightStreet)>>,
-    mut intersection_query: Query<With<roadsystem::RoadIntersection, Entity>>,
-    mut road_query: Query<With<math::line::Line, Entity>>,
+    state: Res<input::MouseState>,
+    view: Res<CameraView>,
+    actions: Res<keybindings::Actions>,
     mut graph_query: Query<(&Graph, &mut roadsystem::RoadSystem)>
-) {     
-    if *current_action != ui::RoadActions::Build {
+) {
+    if *app_state != AppState::Editing || *current_action != ui::RoadActions::Upgrade {
         return;
     }
 
-    let mouse_pos_ws = mouse_pos_ws(state.mouse_position);
+    let upgrade = actions.just_pressed(Action::Primary);
+    if !upgrade && !actions.just_pressed(Action::Secondary) {
+        return;
+    }
 
-    if mouse_button_input.just_pressed(MouseButton::Left) {
-        state.last_mouse_left_pressed_position = mouse_pos_ws;
+    let mouse_pos_ws = mouse_pos_ws(&state, &view);
 
-        spawn_temp_street(&mut commands, &mut materials);
+    for (_, mut road_system) in &mut graph_query.iter() {
+        let street = match road_system.pick(mouse_pos_ws) {
+            Some(roadsystem::RoadElement::Street(street)) => street,
+            _ => continue
+        };
+
+        let streets = if actions.pressed(Action::Extend) {
+            road_system.street_stretch(street)
+        } else {
+            vec![street]
+        };
+
+        for street in streets {
+            let road_type = road_system.road_type(street).unwrap();
+            let changed = if upgrade { road_type.upgraded() } else { road_type.downgraded() };
+
+            if changed != road_type {
+                road_system.set_road_type(street, changed);
+            }
+        }
+    }
+}
+
+/// Snaps the position to the grid while control is pressed
+fn snap_position(position: Vec2, actions: &keybindings::Actions, build_constraints: &constraints::BuildConstraints) -> Vec2 {
+    if actions.pressed(Action::SnapToGrid) {
+        return constraints::snap_to_grid(position, build_constraints.grid_size);
+    }
+
+    position
+}
+
+/// Shift constrains the angle of a new street, alt builds it perpendicular or parallel to
+/// the closest street
+fn constrain_direction(
+    start: Vec2,
+    end: Vec2,
+    actions: &keybindings::Actions,
+    build_constraints: &constraints::BuildConstraints,
+    road_system: &roadsystem::RoadSystem
+) -> Vec2 {
+    if actions.pressed(Action::ConstrainAngle) {
+        return constraints::snap_angle(start, end, 0.0, build_constraints.angle_step);
     }
 
-    let street_vector = mouse_pos_ws - state.last_mouse_left_pressed_position;
+    if actions.pressed(Action::AlignToStreet) {
+        if let Some(street) = road_system.nearest_street(start, build_constraints.reference_distance) {
+            let line = road_system.street_line(street).unwrap();
+            let direction = line.point2 - line.point1;
+
+            return constraints::snap_angle(start, end, direction.y().atan2(direction.x()), std::f32::consts::FRAC_PI_2);
+        }
+    }
+
+    end
+}
+
+/// Updates length, orientation and position of the temp street
+fn update_temp_street(
+    start: Vec2,
+    end: Vec2,
+    temp_query: &mut Query<With<TempStraightStreet, (Entity, &mut Sprite, &mut Transform, &mut city::StraightStreet)>>
+) {
+    let street_vector = end - start;
     let street_length = street_vector.length();
-    let street_center = state.last_mouse_left_pressed_position + street_vector / street_length * street_length / 2.0;
+    let street_center = start + street_vector / street_length * street_length / 2.0;
     let rotation = -street_vector.angle_between(Vec2::new(1.0, 0.0)); 
 
-    // update temp street length, orientation and position
     for (_, mut sprite, mut transform, mut temp_street) in &mut temp_query.iter() {
         *transform = Transform::from_translation_rotation(Vec3::new(street_center.x(), street_center.y(), 0.0), Quat::from_rotation_z(rotation));
         sprite.size.set_x(street_length);
@@ -107,34 +287,188 @@ fn build_street(
         temp_street.set_start(street_center - street_vector.normalize() * street_length / 2.0);
         temp_street.set_end(street_center + street_vector.normalize() * street_length / 2.0); 
     }
+}
 
+fn street_too_short(units: &measure::Units) -> String {
+    format!("Street too short, streets need a length of at least {}", units.format_length(MIN_STREET_LENGTH))
+}
+
+fn build_street( 
+    mut commands: Commands,    
+    app_state: Res<AppState>,
+    current_action: Res<ui::RoadActions>,
+    theme_materials: Res<theme::ThemeMaterials>,
+    mut state: ResMut<input::MouseState>,
+    view: Res<CameraView>,
+    mut build_constraints: ResMut<constraints::BuildConstraints>,
+    actions: Res<keybindings::Actions>,
+    units: Res<measure::Units>,
+    mut notifications: ResMut<ui::notifications::Notifications>,
+    mut temp_query: Query<With<TempStraightStreet, (Entity, &mut Sprite, &mut Transform, &mut city::StraightStreet)>>,
+    mut graph_query: Query<(&Graph, &mut roadsystem::RoadSystem)>
+) {     
+    if *app_state != AppState::Editing || *current_action != ui::RoadActions::Build {
+        return;
+    }
+
+    if actions.just_pressed(Action::NextAngleStep) {
+        build_constraints.next_angle_step();
+    }
+
+    let mut mouse_pos_ws = snap_position(mouse_pos_ws(&state, &view), &actions, &build_constraints);
+
+    if actions.just_pressed(Action::Primary) {
+        state.last_mouse_left_pressed_position = mouse_pos_ws;
+
+        spawn_temp_street(&mut commands, &theme_materials);
+    }
+
+    let start = state.last_mouse_left_pressed_position;
+    for (_, road_system) in &mut graph_query.iter() {
+        mouse_pos_ws = constrain_direction(start, mouse_pos_ws, &actions, &build_constraints, &road_system);
+    }
+
+    let street_length = (mouse_pos_ws - start).length();
+    update_temp_street(start, mouse_pos_ws, &mut temp_query);
     
-    if mouse_button_input.just_released(MouseButton::Left) {
+    if actions.just_released(Action::Primary) {
         // remove temp street entity
         for (entity, _, _, _) in &mut temp_query.iter() { 
             commands.despawn(entity);
         }
 
-        // ignore streets where start and end are too close to each other
-        if street_length < 100.0 {
+        // ignore streets where start and end are too close to each other, a click without
+        // moving the mouse is no attempt to build a street
+        if street_length < MIN_STREET_LENGTH {
+            if street_length > 0.0 {
+                notifications.warning(&street_too_short(&units));
+            }
+
             return;
         }
 
         for (_, mut road_system) in &mut graph_query.iter() { 
-            for entity in &mut intersection_query.iter() {
-                commands.despawn(entity);
-            }
+            let node1_index = road_system.find_or_insert_intersection(state.last_mouse_left_pressed_position);
+            let node2_index = road_system.find_or_insert_intersection(mouse_pos_ws);
 
-            for entity in &mut road_query.iter() {
+            road_system.connect_intersections(node1_index, node2_index, roadsystem::Street::default());
+        }
+    }       
+}
+
+/// Builds connected streets where each click adds another street starting at the end of the
+/// previous one. Right click finishes the chain.
+fn build_street_chain(
+    mut commands: Commands,
+    app_state: Res<AppState>,
+    current_action: Res<ui::RoadActions>,
+    theme_materials: Res<theme::ThemeMaterials>,
+    state: Res<input::MouseState>,
+    view: Res<CameraView>,
+    build_constraints: Res<constraints::BuildConstraints>,
+    actions: Res<keybindings::Actions>,
+    units: Res<measure::Units>,
+    mut notifications: ResMut<ui::notifications::Notifications>,
+    mut chain: ResMut<StreetChain>,
+    mut temp_query: Query<With<TempStraightStreet, (Entity, &mut Sprite, &mut Transform, &mut city::StraightStreet)>>,
+    mut graph_query: Query<(&Graph, &mut roadsystem::RoadSystem)>
+) {
+    // the chain is kept while the editor is paused
+    if *app_state != AppState::Editing {
+        return;
+    }
+
+    let finished = *current_action != ui::RoadActions::Polyline || actions.just_pressed(Action::Secondary);
+
+    // the build tool uses the temp street as well, so it is only removed if it belongs to
+    // a chain or the polyline tool is active
+    if (finished && chain.is_active()) || (!finished && !chain.is_active()) {
+        for (entity, _, _, _) in &mut temp_query.iter() {
+            commands.despawn(entity);
+        }
+    }
+
+    if finished {
+        chain.finish();
+        return;
+    }
+
+    let mut mouse_pos_ws = snap_position(mouse_pos_ws(&state, &view), &actions, &build_constraints);
+
+    for (_, mut road_system) in &mut graph_query.iter() {
+        if let Some(start) = chain.start_position(&road_system) {
+            mouse_pos_ws = constrain_direction(start, mouse_pos_ws, &actions, &build_constraints, &road_system);
+            update_temp_street(start, mouse_pos_ws, &mut temp_query);
+        }
+
+        if !actions.just_pressed(Action::Primary) {
+            continue;
+        }
+
+        match chain.click(mouse_pos_ws, &mut road_system) {
+            ChainClick::Started => spawn_temp_street(&mut commands, &theme_materials),
+            ChainClick::TooShort => notifications.warning(&street_too_short(&units)),
+            ChainClick::Extended => ()
+        }
+    }
+}
+
+/// Shows the length of the temporary street next to the cursor
+fn street_length_label_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    state: Res<input::MouseState>,
+    units: Res<measure::Units>,
+    theme: Res<theme::Theme>,
+    mut temp_query: Query<With<TempStraightStreet, &city::StraightStreet>>,
+    mut label_query: Query<With<StreetLengthLabel, (Entity, &mut Text, &mut Style)>>
+) {
+    let mut length = None;
+    for temp_street in &mut temp_query.iter() {
+        length = Some(temp_street.length());
+    }
+
+    let position = Rect {
+        left: Val::Px(state.mouse_position.x() + 15.0),
+        bottom: Val::Px(state.mouse_position.y() + 15.0),
+        ..Default::default()
+    };
+
+    let mut labeled = false;
+    for (entity, mut text, mut style) in &mut label_query.iter() {
+        match length {
+            Some(length) => {
+                text.value = units.format_length(length);
+                text.style.color = theme.label;
+                style.position = position;
+                labeled = true;
+            }
+            None => {
                 commands.despawn(entity);
             }
-            
-            let node1_index = road_system.insert_intersection(roadsystem::RoadIntersection::new(state.last_mouse_left_pressed_position));
-            let node2_index = road_system.insert_intersection(roadsystem::RoadIntersection::new(mouse_pos_ws));
-
-            road_system.connect_intersections(node1_index, node2_index);
         }
-    }       
+    }
+
+    if let (Some(length), false) = (length, labeled) {
+        commands
+        .spawn(TextComponents {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: position,
+                ..Default::default()
+            },
+            text: Text {
+                value: units.format_length(length),
+                font: asset_server.load("fonts/FiraSans-Bold.ttf").unwrap(),
+                style: TextStyle {
+                    font_size: 16.0,
+                    color: theme.label,
+                },
+            },
+            ..Default::default()
+        })
+        .with(StreetLengthLabel);
+    }
 }
 
 struct StreetBuildingPlugin {
@@ -152,17 +486,32 @@ impl Default for StreetBuildingPlugin {
 impl Plugin for StreetBuildingPlugin {
     fn build(&self, app: &mut AppBuilder) {
         app.add_stage_after("ui_handling", "do_things");
+        app.init_resource::<constraints::BuildConstraints>();
+        app.init_resource::<StreetChain>();
         app.add_system_to_stage("do_things", build_street.system());
+        app.add_system_to_stage("do_things", build_street_chain.system());
+        app.add_system_to_stage("do_things", street_length_label_system.system());
         app.add_system_to_stage("do_things", destroy_street.system());
+        app.add_system_to_stage("do_things", upgrade_street.system());
         //app.add_system(build_street.system()); 
         //app.add_system(destroy_street.system());
     }    
 }
 
-/// Closes the application if Escape is pressed
-fn keyboard_input_system(keyboard_input: Res<Input<KeyCode>>, mut exit_event: ResMut<Events<bevy::app::AppExit>>) {
-    if keyboard_input.just_released(KeyCode::Escape) {
-        exit_event.send(bevy::app::AppExit);
+/// Finishes the street chain if Escape is pressed. Pauses the editor if there is no chain
+/// to finish and leaves the menus.
+fn keyboard_input_system(
+    actions: Res<keybindings::Actions>,
+    mut chain: ResMut<StreetChain>,
+    mut app_state: ResMut<AppState>
+) {
+    if !actions.just_released(Action::Cancel) {
+        return;
+    }
+
+    // the chain is kept while the editor is paused
+    if *app_state != AppState::Editing || !chain.finish() {
+        *app_state = app_state.cancelled();
     }
 }
 
@@ -197,6 +546,57 @@ fn cursor_system (
     }    
 }
 
+/// Pans and zooms the view with the keyboard
+fn camera_control_system(
+    time: Res<Time>,
+    app_state: Res<AppState>,
+    actions: Res<keybindings::Actions>,
+    mut view: ResMut<CameraView>
+) {
+    if *app_state != AppState::Editing {
+        return;
+    }
+
+    let mut direction = Vec2::zero();
+    for (action, step) in &[
+        (Action::PanUp, Vec2::new(0.0, 1.0)),
+        (Action::PanDown, Vec2::new(0.0, -1.0)),
+        (Action::PanLeft, Vec2::new(-1.0, 0.0)),
+        (Action::PanRight, Vec2::new(1.0, 0.0))
+    ] {
+        if actions.pressed(*action) {
+            direction += *step;
+        }
+    }
+
+    if direction != Vec2::zero() {
+        // the view moves by the same distance on the screen at every zoom level
+        let distance = PAN_SPEED * view.zoom * time.delta_seconds;
+        view.position += direction * distance;
+    }
+
+    if actions.just_pressed(Action::ZoomIn) {
+        view.zoom = (view.zoom / ZOOM_STEP).max(MIN_ZOOM);
+    } else if actions.just_pressed(Action::ZoomOut) {
+        view.zoom = (view.zoom * ZOOM_STEP).min(MAX_ZOOM);
+    }
+}
+
+/// Moves and scales the main camera to the view
+fn camera_system(
+    view: Res<CameraView>,
+    cursor_state: Res<CursorState>,
+    mut camera_query: Query<&mut Transform>
+) {
+    if let Ok(mut transform) = camera_query.get_mut::<Transform>(cursor_state.camera_entity) {
+        let translation = transform.translation();
+
+        if Vec2::new(translation.x(), translation.y()) != view.position || transform.scale().x() != view.zoom {
+            *transform = Transform::from_translation_rotation_scale(view.position.extend(translation.z()), Quat::identity(), view.zoom);
+        }
+    }
+}
+
 fn setup(
     mut commands: Commands,
 ) {
@@ -210,61 +610,6 @@ fn setup(
     .spawn((Graph, roadsystem::RoadSystem::new()));
 }
 
-pub fn toggle_button_sytem(
-    current_action: ChangedRes<ui::RoadActions>,
-    button_materials: Res<ui::ButtonMaterials>,
-    mut interaction_query: Query<(
-        &ui::RoadActions,
-        &mut ui::ToggleButton,
-        &mut Handle<ColorMaterial>,
-    )>,
-    
-) {
-    for (action , mut toggle_state, mut material) in &mut interaction_query.iter() {        
-        if *action != *current_action {            
-            toggle_state.state = ui::ToggleState::Normal;
-            *material = button_materials.normal.clone();
-        }                   
-    } 
-}
-
-pub fn button_system(
-    mut current_action: ResMut<ui::RoadActions>,
-    button_materials: Res<ui::ButtonMaterials>,
-    mut interaction_query: Query<(
-        &Button,
-        &ui::RoadActions,
-        &mut ui::ToggleButton,
-        Mutated<Interaction>,
-        &mut Handle<ColorMaterial>,
-    )>,
-    
-) {
-    for (_button, action , mut toggle_state, interaction, mut material) in &mut interaction_query.iter() {
-        match *interaction {
-            Interaction::Clicked => {
-                match toggle_state.state {
-                    ui::ToggleState::Normal => toggle_state.state = ui::ToggleState::Toggled,
-                    ui::ToggleState::Toggled => {
-                        toggle_state.state = ui::ToggleState::Normal;
-
-                        *current_action = ui::RoadActions::Nothing;
-                    }
-                }                      
-            }
-            _ => ()
-        }
-
-        match toggle_state.state {
-            ui::ToggleState::Normal =>  *material = button_materials.normal.clone(),
-            ui::ToggleState::Toggled => {                      
-                *material = button_materials.pressed.clone();
-                *current_action = *action;
-            }
-        }        
-    }   
-}
-
 fn main() {
 
     App::build()
@@ -276,17 +621,30 @@ fn main() {
         resizable: false,
         ..Default::default()
     })
-    .add_resource(ui::RoadActions::Nothing)
-    .add_resource(ClearColor(Color::rgb(0.9, 0.9, 0.9)))
     .init_resource::<input::MouseState>()
+    .init_resource::<CameraView>()
     .add_default_plugins()    
+    .add_plugin(keybindings::KeybindingPlugin)
+    .add_plugin(theme::ThemePlugin)
     .init_resource::<ui::ButtonMaterials>()
 
     .add_stage_after(stage::PRE_UPDATE, "ui_handling")
-    .add_system_to_stage_front("ui_handling", toggle_button_sytem.system())
-    .add_system_to_stage_front("ui_handling", button_system.system())
 
+    .add_plugin(ui::widgets::WidgetPlugin)
+    .add_plugin(ui::ActionSelectionPlugin)
+    .add_plugin(ui::notifications::NotificationPlugin)
     .add_plugin(StreetBuildingPlugin { ..Default::default() })
+    .add_plugin(selection::SelectionPlugin)
+    .add_plugin(templates::TemplatePlugin)
+    .add_plugin(generators::GeneratorPlugin)
+    .add_plugin(hover::HoverPlugin)
+    .add_plugin(measure::MeasurePlugin)
+    .add_plugin(history::HistoryPlugin)
+    .add_plugin(inspector::InspectorPlugin)
+    .add_plugin(statistics::StatisticsPlugin)
+    .add_plugin(minimap::MinimapPlugin)
+    .add_plugin(context_menu::ContextMenuPlugin)
+    .add_plugin(app_state::AppStatePlugin)
     .add_event::<bevy::app::AppExit>()
 
 
@@ -295,7 +653,56 @@ fn main() {
     .add_system(input::print_mouse_events_system.system())
     .add_system(road_network_change_tracking_system.system())
     .add_system(cursor_system.system())
+    .add_system(camera_control_system.system())
+    .add_system(camera_system.system())
     .add_startup_system(setup.system())
-    .add_startup_system(ui::ui_setup.system())
     .run();
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roadsystem::RoadSystem;
+
+    #[test]
+    fn street_chain_continues_at_the_last_intersection() {
+        let mut road_system = RoadSystem::new();
+        let mut chain = StreetChain::default();
+
+        assert_eq!(chain.click(Vec2::new(0.0, 0.0), &mut road_system), ChainClick::Started);
+        assert!(chain.is_active());
+        assert_eq!(chain.click(Vec2::new(50.0, 0.0), &mut road_system), ChainClick::TooShort);
+        assert!(road_system.streets().is_empty());
+
+        assert_eq!(chain.click(Vec2::new(200.0, 0.0), &mut road_system), ChainClick::Extended);
+        let corner = chain.last.unwrap();
+        assert_eq!(chain.start_position(&road_system), Some(Vec2::new(200.0, 0.0)));
+
+        assert_eq!(chain.click(Vec2::new(200.0, 200.0), &mut road_system), ChainClick::Extended);
+        assert_ne!(chain.last, Some(corner));
+
+        // the second street starts at the intersection the first one ended at
+        assert_eq!(road_system.streets().len(), 2);
+        assert_eq!(road_system.intersections().len(), 3);
+        assert_eq!(road_system.degree(corner), 2);
+
+        assert!(chain.finish());
+        assert!(!chain.is_active());
+        assert!(!chain.finish());
+    }
+
+    #[test]
+    fn street_chain_restarts_when_its_end_was_removed() {
+        let mut road_system = RoadSystem::new();
+        let mut chain = StreetChain::default();
+
+        chain.click(Vec2::new(0.0, 0.0), &mut road_system);
+        chain.click(Vec2::new(200.0, 0.0), &mut road_system);
+        road_system.remove_intersection(chain.last.unwrap());
+
+        assert_eq!(chain.start_position(&road_system), None);
+        assert_eq!(chain.click(Vec2::new(0.0, 300.0), &mut road_system), ChainClick::Started);
+        assert_eq!(chain.last, None);
+        assert_eq!(chain.start_position(&road_system), Some(Vec2::new(0.0, 300.0)));
+    }
+}
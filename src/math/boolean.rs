@@ -0,0 +1,551 @@
+use bevy::prelude::*;
+
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+use crate::math::polygon::Polygon;
+
+/// Points closer than this are treated as the same vertex
+const EPSILON: f32 = 1e-3;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum BooleanOperation {
+    Union,
+    Intersection,
+    Difference,
+    Xor
+}
+
+/// Decides which winding numbers count as filled.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum FillRule {
+    /// Filled if the point is surrounded an odd number of times. Holes can be given
+    /// in any orientation.
+    EvenOdd,
+    NonZero,
+    Positive
+}
+
+impl FillRule {
+    fn filled(&self, winding: i32) -> bool {
+        match self {
+            FillRule::EvenOdd => winding % 2 != 0,
+            FillRule::NonZero => winding != 0,
+            FillRule::Positive => winding > 0
+        }
+    }
+}
+
+impl BooleanOperation {
+    fn apply(&self, subject: bool, clip: bool) -> bool {
+        match self {
+            BooleanOperation::Union => subject || clip,
+            BooleanOperation::Intersection => subject && clip,
+            BooleanOperation::Difference => subject && !clip,
+            BooleanOperation::Xor => subject != clip
+        }
+    }
+}
+
+pub fn union(subject: &[Polygon], clip: &[Polygon]) -> Vec<Polygon> {
+    boolean(subject, clip, BooleanOperation::Union, FillRule::EvenOdd)
+}
+
+pub fn intersection(subject: &[Polygon], clip: &[Polygon]) -> Vec<Polygon> {
+    boolean(subject, clip, BooleanOperation::Intersection, FillRule::EvenOdd)
+}
+
+pub fn difference(subject: &[Polygon], clip: &[Polygon]) -> Vec<Polygon> {
+    boolean(subject, clip, BooleanOperation::Difference, FillRule::EvenOdd)
+}
+
+pub fn xor(subject: &[Polygon], clip: &[Polygon]) -> Vec<Polygon> {
+    boolean(subject, clip, BooleanOperation::Xor, FillRule::EvenOdd)
+}
+
+struct Edge {
+    start: Vec2,
+    end: Vec2,
+    source: usize,
+    splits: Vec<(f32, Vec2)>
+}
+
+struct Fragment {
+    from: usize,
+    to: usize,
+    /// 0 for fragments of the subject, 1 for the ones of the clip polygons
+    source: usize
+}
+
+/// Collects vertices and merges the ones which are closer than EPSILON
+#[derive(Default)]
+struct VertexPool {
+    vertices: Vec<Vec2>
+}
+
+impl VertexPool {
+    fn insert(&mut self, point: Vec2) -> usize {
+        if let Some(index) = self.vertices.iter().position(|v| (*v - point).length() < EPSILON) {
+            return index;
+        }
+
+        self.vertices.push(point);
+        self.vertices.len() - 1
+    }
+}
+
+fn collect_edges(polygons: &[Polygon], source: usize, edges: &mut Vec<Edge>) {
+    for polygon in polygons {
+        let points = polygon.points();
+        for (i, start) in points.iter().enumerate() {
+            let end = points[(i + 1) % points.len()];
+
+            if (end - *start).length() >= EPSILON {
+                edges.push(Edge { start: *start, end, source, splits: Vec::new() });
+            }
+        }
+    }
+}
+
+/// Returns the parameter of the point projected onto the edge if the point lies on it
+fn parameter_on_edge(point: Vec2, start: Vec2, end: Vec2) -> Option<f32> {
+    let direction = end - start;
+    let length = direction.length();
+    let distance = direction.perp_dot(point - start).abs() / length;
+    let t = direction.dot(point - start) / (length * length);
+    let tolerance = EPSILON / length;
+
+    if distance < EPSILON && t > -tolerance && t < 1.0 + tolerance {
+        return Some(t);
+    }
+
+    None
+}
+
+/// Adds the points where the two edges touch, cross or overlap to both of them
+fn split_edges(edges: &mut Vec<Edge>, i: usize, j: usize) {
+    let (a_start, a_end) = (edges[i].start, edges[i].end);
+    let (b_start, b_end) = (edges[j].start, edges[j].end);
+
+    let r = a_end - a_start;
+    let s = b_end - b_start;
+    let denominator = r.perp_dot(s);
+
+    if denominator.abs() > EPSILON * r.length() * s.length() {
+        let t = (b_start - a_start).perp_dot(s) / denominator;
+        let u = (b_start - a_start).perp_dot(r) / denominator;
+        let t_tolerance = EPSILON / r.length();
+        let u_tolerance = EPSILON / s.length();
+
+        if t > -t_tolerance && t < 1.0 + t_tolerance && u > -u_tolerance && u < 1.0 + u_tolerance {
+            let point = a_start + r * t;
+            edges[i].splits.push((t, point));
+            edges[j].splits.push((u, point));
+        }
+
+        return;
+    }
+
+    // parallel edges only need to be split if they are collinear and overlap
+    for point in &[b_start, b_end] {
+        if let Some(t) = parameter_on_edge(*point, a_start, a_end) {
+            edges[i].splits.push((t, *point));
+        }
+    }
+
+    for point in &[a_start, a_end] {
+        if let Some(u) = parameter_on_edge(*point, b_start, b_end) {
+            edges[j].splits.push((u, *point));
+        }
+    }
+}
+
+/// Calculates the winding numbers of subject and clip directly left of the fragment.
+///
+/// The ray is cast along the left normal of the fragment so the crossings only need to be
+/// counted for the fragments which do not lie on top of it.
+fn left_winding_numbers(vertices: &[Vec2], fragments: &[Fragment], fragment: &Fragment) -> [i32; 2] {
+    let from = vertices[fragment.from];
+    let to = vertices[fragment.to];
+
+    let direction = (to - from).normalize();
+    let normal = Vec2::new(-direction.y(), direction.x());
+    let origin = from + (to - from) * 0.5;
+
+    // local coordinate system with the ray along the x axis
+    let local = |point: Vec2| {
+        let point = point - origin;
+        Vec2::new(point.dot(normal), -point.dot(direction))
+    };
+
+    let mut winding = [0, 0];
+    for other in fragments {
+        let coincident = (other.from == fragment.from && other.to == fragment.to)
+            || (other.from == fragment.to && other.to == fragment.from);
+
+        if coincident {
+            continue;
+        }
+
+        let start = local(vertices[other.from]);
+        let end = local(vertices[other.to]);
+        let side = (end - start).perp_dot(-start);
+
+        if start.y() <= 0.0 {
+            if end.y() > 0.0 && side > 0.0 {
+                winding[other.source] += 1;
+            }
+        } else if end.y() <= 0.0 && side < 0.0 {
+            winding[other.source] -= 1;
+        }
+    }
+
+    winding
+}
+
+/// Angle of the clockwise rotation from `reference` to `direction` in the range (0, 2π]
+fn clockwise_angle(reference: Vec2, direction: Vec2) -> f32 {
+    let angle = -reference.perp_dot(direction).atan2(reference.dot(direction));
+
+    if angle <= 0.0 {
+        return angle + 2.0 * PI;
+    }
+
+    angle
+}
+
+/// Removes duplicated and collinear points. Returns None if nothing with an area is left.
+fn simplify_ring(mut points: Vec<Vec2>) -> Option<Polygon> {
+    let mut changed = true;
+    while changed && points.len() >= 3 {
+        changed = false;
+
+        for i in 0..points.len() {
+            let previous = points[(i + points.len() - 1) % points.len()];
+            let current = points[i];
+            let next = points[(i + 1) % points.len()];
+
+            let base = next - previous;
+            let collinear = if base.length() < EPSILON {
+                true
+            } else {
+                base.perp_dot(current - previous).abs() / base.length() < EPSILON
+            };
+
+            if collinear {
+                points.remove(i);
+                changed = true;
+                break;
+            }
+        }
+    }
+
+    if points.len() < 3 {
+        return None;
+    }
+
+    let polygon = Polygon::new(points);
+    if polygon.area() < EPSILON {
+        return None;
+    }
+
+    Some(polygon)
+}
+
+/// Chains the fragments to closed rings. At vertices where several fragments continue the
+/// one with the sharpest clockwise turn is taken which keeps polygons touching in a single
+/// point separated.
+fn link_fragments(vertices: &[Vec2], fragments: &[Fragment]) -> Vec<Polygon> {
+    let mut outgoing: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (index, fragment) in fragments.iter().enumerate() {
+        outgoing.entry(fragment.from).or_insert_with(Vec::new).push(index);
+    }
+
+    let mut used = vec![false; fragments.len()];
+    let mut polygons = Vec::new();
+
+    for first in 0..fragments.len() {
+        if used[first] {
+            continue;
+        }
+
+        let start = fragments[first].from;
+        let mut ring = vec![vertices[start]];
+        let mut current = first;
+        let mut closed = false;
+
+        loop {
+            used[current] = true;
+
+            let vertex = fragments[current].to;
+            if vertex == start {
+                closed = true;
+                break;
+            }
+
+            ring.push(vertices[vertex]);
+
+            let reference = vertices[fragments[current].from] - vertices[vertex];
+            let next = outgoing
+                .get(&vertex)
+                .into_iter()
+                .flatten()
+                .filter(|index| !used[**index])
+                .map(|index| {
+                    let direction = vertices[fragments[*index].to] - vertices[vertex];
+                    (*index, clockwise_angle(reference, direction))
+                })
+                .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+            match next {
+                Some((index, _)) => current = index,
+                None => break
+            }
+        }
+
+        if closed {
+            if let Some(polygon) = simplify_ring(ring) {
+                polygons.push(polygon);
+            }
+        }
+    }
+
+    polygons
+}
+
+/// Applies the boolean operation on the areas described by `subject` and `clip`.
+///
+/// Each input is a collection of rings which is interpreted using the fill rule. The
+/// result contains outer boundaries in counter-clockwise and holes in clockwise orientation.
+pub fn boolean(subject: &[Polygon], clip: &[Polygon], operation: BooleanOperation, fill_rule: FillRule) -> Vec<Polygon> {
+    let mut edges = Vec::new();
+    collect_edges(subject, 0, &mut edges);
+    collect_edges(clip, 1, &mut edges);
+
+    for i in 0..edges.len() {
+        for j in (i + 1)..edges.len() {
+            split_edges(&mut edges, i, j);
+        }
+    }
+
+    // split every edge into fragments which do not cross any other edge
+    let mut pool = VertexPool::default();
+    let mut fragments: Vec<Fragment> = Vec::new();
+    for edge in &mut edges {
+        edge.splits.push((0.0, edge.start));
+        edge.splits.push((1.0, edge.end));
+        edge.splits.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+        let indices: Vec<usize> = edge.splits.iter().map(|(_, point)| pool.insert(*point)).collect();
+        for pair in indices.windows(2) {
+            if pair[0] != pair[1] {
+                fragments.push(Fragment { from: pair[0], to: pair[1], source: edge.source });
+            }
+        }
+    }
+
+    // keep the fragments which separate the result from its surrounding
+    let mut kept: Vec<Fragment> = Vec::new();
+    for fragment in &fragments {
+        let left = left_winding_numbers(&pool.vertices, &fragments, fragment);
+
+        // fragments on top of this one are the only difference between both sides
+        let mut right = left;
+        for other in &fragments {
+            if other.from == fragment.from && other.to == fragment.to {
+                right[other.source] -= 1;
+            } else if other.from == fragment.to && other.to == fragment.from {
+                right[other.source] += 1;
+            }
+        }
+
+        let inside = |winding: [i32; 2]| {
+            operation.apply(fill_rule.filled(winding[0]), fill_rule.filled(winding[1]))
+        };
+
+        let (from, to) = match (inside(left), inside(right)) {
+            (true, false) => (fragment.from, fragment.to),
+            (false, true) => (fragment.to, fragment.from),
+            _ => continue
+        };
+
+        // edges shared by both inputs show up twice
+        if !kept.iter().any(|other| other.from == from && other.to == to) {
+            kept.push(Fragment { from, to, source: fragment.source });
+        }
+    }
+
+    link_fragments(&pool.vertices, &kept)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use super::*;
+
+    fn square(x: f32, y: f32, size: f32) -> Polygon {
+        Polygon::new(vec![
+            Vec2::new(x, y),
+            Vec2::new(x + size, y),
+            Vec2::new(x + size, y + size),
+            Vec2::new(x, y + size)
+        ])
+    }
+
+    fn total_area(polygons: &[Polygon]) -> f32 {
+        polygons.iter().map(|polygon| polygon.signed_area()).sum()
+    }
+
+    fn assert_area(polygons: &[Polygon], expected: f32) {
+        assert!((total_area(polygons) - expected).abs() < 0.1, "area {} != {}", total_area(polygons), expected);
+    }
+
+    #[test]
+    fn union_overlapping_squares() {
+        let result = union(&[square(0.0, 0.0, 100.0)], &[square(50.0, 50.0, 100.0)]);
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].points().len(), 8);
+        assert_area(&result, 17500.0);
+    }
+
+    #[test]
+    fn intersection_overlapping_squares() {
+        let result = intersection(&[square(0.0, 0.0, 100.0)], &[square(50.0, 50.0, 100.0)]);
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].points().len(), 4);
+        assert_area(&result, 2500.0);
+    }
+
+    #[test]
+    fn difference_overlapping_squares() {
+        let result = difference(&[square(0.0, 0.0, 100.0)], &[square(50.0, 50.0, 100.0)]);
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].points().len(), 6);
+        assert_area(&result, 7500.0);
+    }
+
+    #[test]
+    fn xor_overlapping_squares() {
+        let result = xor(&[square(0.0, 0.0, 100.0)], &[square(50.0, 50.0, 100.0)]);
+
+        assert_eq!(result.len(), 2);
+        assert_area(&result, 15000.0);
+    }
+
+    #[test]
+    fn results_are_counter_clockwise_regardless_of_input() {
+        let result = union(&[square(0.0, 0.0, 100.0).reversed()], &[square(50.0, 50.0, 100.0)]);
+
+        assert_eq!(result.len(), 1);
+        assert!(result[0].is_counter_clockwise());
+    }
+
+    #[test]
+    fn difference_creates_hole() {
+        let result = difference(&[square(0.0, 0.0, 100.0)], &[square(25.0, 25.0, 50.0)]);
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result.iter().filter(|polygon| polygon.is_counter_clockwise()).count(), 1);
+        assert_area(&result, 7500.0);
+    }
+
+    #[test]
+    fn union_fills_hole() {
+        let frame = vec![square(0.0, 0.0, 100.0), square(25.0, 25.0, 50.0)];
+        let result = union(&frame, &[square(20.0, 20.0, 60.0)]);
+
+        assert_eq!(result.len(), 1);
+        assert_area(&result, 10000.0);
+    }
+
+    #[test]
+    fn intersection_with_hole() {
+        let frame = vec![square(0.0, 0.0, 100.0), square(25.0, 25.0, 50.0)];
+        let result = intersection(&frame, &[square(0.0, 0.0, 50.0)]);
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].points().len(), 6);
+        assert_area(&result, 2500.0 - 625.0);
+    }
+
+    #[test]
+    fn union_squares_sharing_edge() {
+        let result = union(&[square(0.0, 0.0, 100.0)], &[square(100.0, 0.0, 100.0)]);
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].points().len(), 4);
+        assert_area(&result, 20000.0);
+    }
+
+    #[test]
+    fn union_squares_sharing_part_of_edge() {
+        let result = union(&[square(0.0, 0.0, 100.0)], &[square(100.0, 50.0, 100.0)]);
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].points().len(), 8);
+        assert_area(&result, 20000.0);
+    }
+
+    #[test]
+    fn intersection_squares_sharing_edge_is_empty() {
+        let result = intersection(&[square(0.0, 0.0, 100.0)], &[square(100.0, 0.0, 100.0)]);
+
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn difference_squares_sharing_edge_is_unchanged() {
+        let result = difference(&[square(0.0, 0.0, 100.0)], &[square(100.0, 0.0, 100.0)]);
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].points().len(), 4);
+        assert_area(&result, 10000.0);
+    }
+
+    #[test]
+    fn union_squares_touching_in_corner_stay_separated() {
+        let result = union(&[square(0.0, 0.0, 100.0)], &[square(100.0, 100.0, 100.0)]);
+
+        assert_eq!(result.len(), 2);
+        assert!(result.iter().all(|polygon| polygon.points().len() == 4));
+        assert_area(&result, 20000.0);
+    }
+
+    #[test]
+    fn identical_polygons() {
+        let a = [square(0.0, 0.0, 100.0)];
+        let b = [square(0.0, 0.0, 100.0)];
+
+        assert_area(&union(&a, &b), 10000.0);
+        assert_area(&intersection(&a, &b), 10000.0);
+        assert!(difference(&a, &b).is_empty());
+        assert!(xor(&a, &b).is_empty());
+    }
+
+    #[test]
+    fn difference_with_disjoint_polygon() {
+        let result = difference(&[square(0.0, 0.0, 100.0)], &[square(200.0, 0.0, 100.0)]);
+
+        assert_eq!(result.len(), 1);
+        assert_area(&result, 10000.0);
+    }
+
+    #[test]
+    fn positive_fill_rule_removes_inverted_loops() {
+        // a bow tie whose right loop runs clockwise
+        let bow_tie = Polygon::new(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 100.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(0.0, 100.0)
+        ]);
+
+        let result = boolean(&[bow_tie], &[], BooleanOperation::Union, FillRule::Positive);
+
+        assert_eq!(result.len(), 1);
+        assert_area(&result, 2500.0);
+    }
+}
@@ -0,0 +1,239 @@
+use bevy::prelude::*;
+
+use crate::math::operations::{ Center, Distance, Inside, Intersects, Overlaps };
+use crate::math::line::Line;
+use crate::math::polygon::Polygon;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Circle {
+    pub center: Vec2,
+    pub radius: f32
+}
+
+impl Circle {
+    pub fn new(center: Vec2, radius: f32) -> Circle {
+        Circle { center, radius }
+    }
+}
+
+impl Center for Circle {
+    fn center(&self) -> Vec2 {
+        self.center
+    }
+}
+
+impl Inside<Circle> for Vec2 {
+    fn inside(&self, other_object: &Circle) -> bool {
+        (*self - other_object.center).length() <= other_object.radius
+    }
+}
+
+impl Inside<Circle> for Line {
+    fn inside(&self, other_object: &Circle) -> bool {
+        self.point1.inside(other_object) && self.point2.inside(other_object)
+    }
+}
+
+impl Inside<Circle> for Polygon {
+    fn inside(&self, other_object: &Circle) -> bool {
+        self.points().iter().all(|point| point.inside(other_object))
+    }
+}
+
+impl Inside<Circle> for Circle {
+    fn inside(&self, other_object: &Circle) -> bool {
+        (self.center - other_object.center).length() + self.radius <= other_object.radius
+    }
+}
+
+impl Inside<Polygon> for Circle {
+    fn inside(&self, other_object: &Polygon) -> bool {
+        other_object.distance(&self.center) == 0.0 && other_object.distance_to_boundary(self.center) >= self.radius
+    }
+}
+
+impl Intersects<Circle> for Line {
+    fn intersects(&self, other: &Circle) -> bool {
+        let farthest = (self.point1 - other.center).length().max((self.point2 - other.center).length());
+
+        self.distance(&other.center) <= other.radius && farthest >= other.radius
+    }
+}
+
+impl Intersects<Line> for Circle {
+    fn intersects(&self, other: &Line) -> bool {
+        other.intersects(self)
+    }
+}
+
+impl Intersects<Circle> for Polygon {
+    fn intersects(&self, other: &Circle) -> bool {
+        self.lines().iter().any(|line| line.intersects(other))
+    }
+}
+
+impl Intersects<Polygon> for Circle {
+    fn intersects(&self, other: &Polygon) -> bool {
+        other.intersects(self)
+    }
+}
+
+impl Intersects<Circle> for Circle {
+    fn intersects(&self, other: &Circle) -> bool {
+        let distance = (self.center - other.center).length();
+
+        distance <= self.radius + other.radius && distance >= (self.radius - other.radius).abs()
+    }
+}
+
+impl Overlaps<Circle> for Vec2 {
+    fn overlaps(&self, other_object: &Circle) -> bool {
+        self.inside(other_object)
+    }
+}
+
+impl Overlaps<Vec2> for Circle {
+    fn overlaps(&self, other_object: &Vec2) -> bool {
+        other_object.inside(self)
+    }
+}
+
+impl Overlaps<Circle> for Line {
+    fn overlaps(&self, other_object: &Circle) -> bool {
+        self.distance(&other_object.center) <= other_object.radius
+    }
+}
+
+impl Overlaps<Line> for Circle {
+    fn overlaps(&self, other_object: &Line) -> bool {
+        other_object.overlaps(self)
+    }
+}
+
+impl Overlaps<Circle> for Polygon {
+    fn overlaps(&self, other_object: &Circle) -> bool {
+        self.distance(&other_object.center) <= other_object.radius
+    }
+}
+
+impl Overlaps<Polygon> for Circle {
+    fn overlaps(&self, other_object: &Polygon) -> bool {
+        other_object.overlaps(self)
+    }
+}
+
+impl Overlaps<Circle> for Circle {
+    fn overlaps(&self, other_object: &Circle) -> bool {
+        (self.center - other_object.center).length() <= self.radius + other_object.radius
+    }
+}
+
+impl Distance<Vec2> for Circle {
+    fn distance(&self, other_object: &Vec2) -> f32 {
+        ((*other_object - self.center).length() - self.radius).max(0.0)
+    }
+}
+
+impl Distance<Circle> for Vec2 {
+    fn distance(&self, other_object: &Circle) -> f32 {
+        other_object.distance(self)
+    }
+}
+
+impl Distance<Line> for Circle {
+    fn distance(&self, other_object: &Line) -> f32 {
+        (other_object.distance(&self.center) - self.radius).max(0.0)
+    }
+}
+
+impl Distance<Circle> for Line {
+    fn distance(&self, other_object: &Circle) -> f32 {
+        other_object.distance(self)
+    }
+}
+
+impl Distance<Polygon> for Circle {
+    fn distance(&self, other_object: &Polygon) -> f32 {
+        (other_object.distance(&self.center) - self.radius).max(0.0)
+    }
+}
+
+impl Distance<Circle> for Polygon {
+    fn distance(&self, other_object: &Circle) -> f32 {
+        other_object.distance(self)
+    }
+}
+
+impl Distance<Circle> for Circle {
+    fn distance(&self, other_object: &Circle) -> f32 {
+        ((self.center - other_object.center).length() - self.radius - other_object.radius).max(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use super::*;
+
+    fn square() -> Polygon {
+        Polygon::new(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(100.0, 100.0),
+            Vec2::new(0.0, 100.0)
+        ])
+    }
+
+    #[test]
+    fn point_inside_circle() {
+        let circle = Circle::new(Vec2::new(0.0, 0.0), 10.0);
+
+        assert_eq!(Vec2::new(6.0, 8.0).inside(&circle), true);
+        assert_eq!(Vec2::new(6.0, 9.0).inside(&circle), false);
+    }
+
+    #[test]
+    fn circles() {
+        let circle = Circle::new(Vec2::new(0.0, 0.0), 10.0);
+        let small = Circle::new(Vec2::new(2.0, 0.0), 5.0);
+        let touching = Circle::new(Vec2::new(20.0, 0.0), 10.0);
+        let far = Circle::new(Vec2::new(50.0, 0.0), 10.0);
+
+        assert_eq!(small.inside(&circle), true);
+        assert_eq!(small.intersects(&circle), false);
+        assert_eq!(small.overlaps(&circle), true);
+        assert_eq!(touching.intersects(&circle), true);
+        assert_eq!(far.overlaps(&circle), false);
+        assert_eq!(far.distance(&circle), 30.0);
+    }
+
+    #[test]
+    fn line_and_circle() {
+        let circle = Circle::new(Vec2::new(0.0, 0.0), 10.0);
+        let crossing = Line::new(Vec2::new(-20.0, 5.0), Vec2::new(20.0, 5.0));
+        let contained = Line::new(Vec2::new(-5.0, 0.0), Vec2::new(5.0, 0.0));
+        let outside = Line::new(Vec2::new(-20.0, 15.0), Vec2::new(20.0, 15.0));
+
+        assert_eq!(crossing.intersects(&circle), true);
+        assert_eq!(contained.intersects(&circle), false);
+        assert_eq!(contained.inside(&circle), true);
+        assert_eq!(contained.overlaps(&circle), true);
+        assert_eq!(outside.overlaps(&circle), false);
+        assert_eq!(outside.distance(&circle), 5.0);
+    }
+
+    #[test]
+    fn polygon_and_circle() {
+        let inside = Circle::new(Vec2::new(50.0, 50.0), 20.0);
+        let crossing = Circle::new(Vec2::new(0.0, 50.0), 20.0);
+        let outside = Circle::new(Vec2::new(-50.0, 50.0), 20.0);
+
+        assert_eq!(inside.inside(&square()), true);
+        assert_eq!(inside.intersects(&square()), false);
+        assert_eq!(crossing.inside(&square()), false);
+        assert_eq!(crossing.intersects(&square()), true);
+        assert_eq!(square().overlaps(&outside), false);
+        assert_eq!(square().distance(&outside), 30.0);
+        assert_eq!(square().inside(&Circle::new(Vec2::new(50.0, 50.0), 80.0)), true);
+    }
+}
@@ -1,13 +1,38 @@
 use bevy::prelude::*;
 
-use crate::math::operations::{ Center, Intersects };
+use crate::math::operations::{ Center, Distance, Inside, Intersects, Overlaps };
 
+/// Points closer than this to a line count as lying on it
+const ON_LINE_EPSILON: f32 = 1e-3;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Line {
     pub point1: Vec2,
     pub point2: Vec2
 }
 
 impl Line {
+    pub fn new(point1: Vec2, point2: Vec2) -> Line {
+        Line { point1, point2 }
+    }
+
+    pub fn length(&self) -> f32 {
+        (self.point2 - self.point1).length()
+    }
+
+    /// Returns the point on the line which is closest to the given one
+    pub fn closest_point(&self, point: Vec2) -> Vec2 {
+        let direction = self.point2 - self.point1;
+        let length_squared = direction.length_squared();
+        if length_squared == 0.0 {
+            return self.point1;
+        }
+
+        let t = (direction.dot(point - self.point1) / length_squared).max(0.0).min(1.0);
+
+        self.point1 + direction * t
+    }
+
     pub fn intersects_position(&self, other: &Line) -> Option<Vec2> {
         // using the formula as described here https://stackoverflow.com/questions/563198/how-do-you-detect-where-two-line-segments-intersect
         let s1 = self.point2 - self.point1;
@@ -31,8 +56,73 @@ impl Intersects<Line> for Line {
     fn intersects(&self, other: &Line) -> bool {
         match self.intersects_position(other) {
             Some(_) => return true,
-            None => return false
+            // parallel lines can still touch each other
+            None => {
+                self.point1.inside(other) || self.point2.inside(other) ||
+                other.point1.inside(self) || other.point2.inside(self)
+            }
+        }
+    }
+}
+
+impl Center for Line {
+    fn center(&self) -> Vec2 {
+        self.point1 + (self.point2 - self.point1) * 0.5
+    }
+}
+
+impl Distance<Vec2> for Line {
+    fn distance(&self, other_object: &Vec2) -> f32 {
+        (self.closest_point(*other_object) - *other_object).length()
+    }
+}
+
+impl Distance<Line> for Vec2 {
+    fn distance(&self, other_object: &Line) -> f32 {
+        other_object.distance(self)
+    }
+}
+
+impl Distance<Line> for Line {
+    fn distance(&self, other_object: &Line) -> f32 {
+        if self.intersects(other_object) {
+            return 0.0;
         }
+
+        self.distance(&other_object.point1)
+            .min(self.distance(&other_object.point2))
+            .min(other_object.distance(&self.point1))
+            .min(other_object.distance(&self.point2))
+    }
+}
+
+impl Inside<Line> for Vec2 {
+    fn inside(&self, other_object: &Line) -> bool {
+        other_object.distance(self) <= ON_LINE_EPSILON
+    }
+}
+
+impl Overlaps<Line> for Vec2 {
+    fn overlaps(&self, other_object: &Line) -> bool {
+        self.inside(other_object)
+    }
+}
+
+impl Overlaps<Vec2> for Line {
+    fn overlaps(&self, other_object: &Vec2) -> bool {
+        other_object.inside(self)
+    }
+}
+
+impl Overlaps<Line> for Line {
+    fn overlaps(&self, other_object: &Line) -> bool {
+        self.intersects(other_object)
+    }
+}
+
+impl Inside<Line> for Line {
+    fn inside(&self, other_object: &Line) -> bool {
+        self.point1.inside(other_object) && self.point2.inside(other_object)
     }
 }
 
@@ -86,4 +176,39 @@ mod tests {
         assert_eq!(line1.intersects_position(&line2), None);
     }
 
+    #[test]
+    fn collinear_lines_touching() {
+        let line1 = Line::new(Vec2::new(0.0, 0.0), Vec2::new(100.0, 0.0));
+        let line2 = Line::new(Vec2::new(100.0, 0.0), Vec2::new(200.0, 0.0));
+        let line3 = Line::new(Vec2::new(101.0, 0.0), Vec2::new(200.0, 0.0));
+
+        assert_eq!(line1.intersects(&line2), true);
+        assert_eq!(line1.intersects(&line3), false);
+        assert_eq!(line1.distance(&line3), 1.0);
+    }
+
+    #[test]
+    fn line_center() {
+        let line = Line::new(Vec2::new(0.0, 0.0), Vec2::new(100.0, 50.0));
+
+        assert_eq!(line.center(), Vec2::new(50.0, 25.0));
+    }
+
+    #[test]
+    fn point_on_line() {
+        let line = Line::new(Vec2::new(0.0, 0.0), Vec2::new(100.0, 100.0));
+
+        assert_eq!(Vec2::new(50.0, 50.0).inside(&line), true);
+        assert_eq!(Vec2::new(50.0, 60.0).inside(&line), false);
+        assert_eq!(Vec2::new(150.0, 150.0).inside(&line), false);
+    }
+
+    #[test]
+    fn point_line_distance() {
+        let line = Line::new(Vec2::new(0.0, 0.0), Vec2::new(100.0, 0.0));
+
+        assert_eq!(line.distance(&Vec2::new(50.0, 20.0)), 20.0);
+        assert_eq!(line.distance(&Vec2::new(-30.0, 40.0)), 50.0);
+    }
+
 }
\ No newline at end of file
@@ -1,3 +1,8 @@
 pub mod line;
 pub mod polygon;
-pub mod operations;
\ No newline at end of file
+pub mod operations;
+pub mod boolean;
+pub mod offset;
+pub mod triangulation;
+pub mod circle;
+pub mod rectangle;
\ No newline at end of file
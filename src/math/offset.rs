@@ -0,0 +1,334 @@
+use bevy::prelude::*;
+
+use std::f32::consts::PI;
+
+use crate::math::boolean::{ boolean, BooleanOperation, FillRule };
+use crate::math::polygon::{ normalize_orientation, Polygon };
+
+/// Maximal angle between two points of a round join or end
+const ROUND_STEP: f32 = PI / 16.0;
+
+/// Corners with a smaller sine are treated as straight
+const COLLINEAR_SINE: f32 = 1e-4;
+
+/// How the offset edges are connected at convex corners
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum JoinType {
+    /// Extends the edges until they meet. Corners with a miter longer than the limit
+    /// (as a multiple of the offset distance) are beveled instead.
+    Miter(f32),
+    Round,
+    Bevel
+}
+
+/// How the outline of a polyline is closed at its first and last point
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum EndType {
+    Butt,
+    Square,
+    Round
+}
+
+fn rotate(vec: Vec2, angle: f32) -> Vec2 {
+    let (sin, cos) = angle.sin_cos();
+
+    Vec2::new(vec.x() * cos - vec.y() * sin, vec.x() * sin + vec.y() * cos)
+}
+
+fn arc(points: &mut Vec<Vec2>, center: Vec2, from: Vec2, angle: f32, radius: f32) {
+    let steps = (angle.abs() / ROUND_STEP).ceil().max(1.0) as usize;
+
+    for step in 0..=steps {
+        points.push(center + rotate(from, angle * step as f32 / steps as f32) * radius);
+    }
+}
+
+fn join(points: &mut Vec<Vec2>, point: Vec2, normal1: Vec2, normal2: Vec2, delta: f32, join_type: JoinType) {
+    let cos = normal1.dot(normal2);
+
+    match join_type {
+        JoinType::Miter(limit) if 2.0 / (1.0 + cos) <= limit * limit => {
+            points.push(point + (normal1 + normal2) * delta / (1.0 + cos));
+        }
+        JoinType::Round => {
+            let angle = normal1.perp_dot(normal2).atan2(cos);
+            arc(points, point, normal1, angle, delta);
+        }
+        _ => {
+            points.push(point + normal1 * delta);
+            points.push(point + normal2 * delta);
+        }
+    }
+}
+
+fn end(points: &mut Vec<Vec2>, point: Vec2, normal: Vec2, delta: f32, end_type: EndType) {
+    let forward = rotate(normal, PI / 2.0);
+
+    match end_type {
+        EndType::Butt => {
+            points.push(point + normal * delta);
+            points.push(point - normal * delta);
+        }
+        EndType::Square => {
+            points.push(point + (normal + forward) * delta);
+            points.push(point + (forward - normal) * delta);
+        }
+        EndType::Round => arc(points, point, normal, PI, delta)
+    }
+}
+
+/// Moves every edge of the closed ring by delta to its right side. The result may intersect
+/// itself and needs to be cleaned up afterwards.
+fn offset_ring(ring: &[Vec2], delta: f32, join_type: JoinType, end_type: EndType) -> Vec<Vec2> {
+    let mut points: Vec<Vec2> = Vec::new();
+    for point in ring {
+        if points.last().map_or(true, |last| (*last - *point).length() > 1e-6) {
+            points.push(*point);
+        }
+    }
+
+    while points.len() > 1 && (points[0] - points[points.len() - 1]).length() <= 1e-6 {
+        points.pop();
+    }
+
+    if points.len() < 2 {
+        return Vec::new();
+    }
+
+    let normals: Vec<Vec2> = (0..points.len())
+        .map(|i| {
+            let direction = (points[(i + 1) % points.len()] - points[i]).normalize();
+            Vec2::new(direction.y(), -direction.x())
+        })
+        .collect();
+
+    let mut result = Vec::new();
+    for (i, point) in points.iter().enumerate() {
+        let normal1 = normals[(i + normals.len() - 1) % normals.len()];
+        let normal2 = normals[i];
+
+        let sin = normal1.perp_dot(normal2);
+        let cos = normal1.dot(normal2);
+
+        if sin.abs() < COLLINEAR_SINE {
+            if cos > 0.0 {
+                result.push(*point + normal1 * delta);
+            } else {
+                end(&mut result, *point, normal1, delta, end_type);
+            }
+        } else if sin * delta > 0.0 {
+            join(&mut result, *point, normal1, normal2, delta, join_type);
+        } else {
+            // concave corners get a small loop which is removed during clean up
+            result.push(*point + normal1 * delta);
+            result.push(*point);
+            result.push(*point + normal2 * delta);
+        }
+    }
+
+    result
+}
+
+fn clean_up(rings: Vec<Vec<Vec2>>) -> Vec<Polygon> {
+    let rings: Vec<Polygon> = rings
+        .into_iter()
+        .filter(|ring| ring.len() >= 3)
+        .map(Polygon::new)
+        .collect();
+
+    boolean(&rings, &[], BooleanOperation::Union, FillRule::Positive)
+}
+
+/// Grows (positive delta) or shrinks (negative delta) the area described by the rings.
+/// Holes are detected by their nesting depth. Parts which vanish because of a large inset
+/// are removed.
+pub fn offset_polygons(polygons: &[Polygon], delta: f32, join_type: JoinType) -> Vec<Polygon> {
+    let rings = normalize_orientation(polygons)
+        .iter()
+        .map(|polygon| offset_ring(polygon.points(), delta, join_type, EndType::Butt))
+        .collect();
+
+    clean_up(rings)
+}
+
+pub fn offset_polygon(polygon: &Polygon, delta: f32, join_type: JoinType) -> Vec<Polygon> {
+    offset_polygons(&[polygon.clone()], delta, join_type)
+}
+
+/// Creates the outline around an open polyline with `distance` to both sides
+pub fn offset_polyline(points: &[Vec2], distance: f32, join_type: JoinType, end_type: EndType) -> Vec<Polygon> {
+    if points.len() < 2 || distance <= 0.0 {
+        return Vec::new();
+    }
+
+    // walking forth and back turns the polyline into a closed ring of zero width
+    let mut ring = points.to_vec();
+    ring.extend(points[1..points.len() - 1].iter().rev());
+
+    clean_up(vec![offset_ring(&ring, distance, join_type, end_type)])
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use super::*;
+
+    fn square(size: f32) -> Polygon {
+        Polygon::new(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(size, 0.0),
+            Vec2::new(size, size),
+            Vec2::new(0.0, size)
+        ])
+    }
+
+    fn total_area(polygons: &[Polygon]) -> f32 {
+        polygons.iter().map(|polygon| polygon.signed_area()).sum()
+    }
+
+    fn assert_near(value: f32, expected: f32, tolerance: f32) {
+        assert!((value - expected).abs() < tolerance, "{} != {}", value, expected);
+    }
+
+    #[test]
+    fn outset_square_with_miter() {
+        let result = offset_polygon(&square(100.0), 10.0, JoinType::Miter(2.0));
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].points().len(), 4);
+        assert_near(total_area(&result), 120.0 * 120.0, 0.5);
+    }
+
+    #[test]
+    fn outset_square_with_bevel() {
+        let result = offset_polygon(&square(100.0), 10.0, JoinType::Bevel);
+
+        assert_eq!(result[0].points().len(), 8);
+        assert_near(total_area(&result), 120.0 * 120.0 - 4.0 * 50.0, 0.5);
+    }
+
+    #[test]
+    fn outset_square_with_round_join() {
+        let result = offset_polygon(&square(100.0), 10.0, JoinType::Round);
+
+        assert_near(total_area(&result), 100.0 * 100.0 + 4.0 * 100.0 * 10.0 + PI * 100.0, 5.0);
+    }
+
+    #[test]
+    fn miter_limit_falls_back_to_bevel() {
+        let triangle = Polygon::new(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(0.0, 10.0)
+        ]);
+
+        let mitered = offset_polygon(&triangle, 1.0, JoinType::Miter(100.0));
+        let limited = offset_polygon(&triangle, 1.0, JoinType::Miter(2.0));
+
+        assert!(limited[0].points().len() > mitered[0].points().len());
+    }
+
+    #[test]
+    fn inset_square() {
+        let result = offset_polygon(&square(100.0), -10.0, JoinType::Miter(2.0));
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].points().len(), 4);
+        assert_near(total_area(&result), 80.0 * 80.0, 0.5);
+    }
+
+    #[test]
+    fn inset_concave_polygon() {
+        // L shape with arms of width 40
+        let l_shape = Polygon::new(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(100.0, 40.0),
+            Vec2::new(40.0, 40.0),
+            Vec2::new(40.0, 100.0),
+            Vec2::new(0.0, 100.0)
+        ]);
+
+        let result = offset_polygon(&l_shape, -10.0, JoinType::Miter(2.0));
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].points().len(), 6);
+        assert_near(total_area(&result), 80.0 * 20.0 + 60.0 * 20.0, 0.5);
+    }
+
+    #[test]
+    fn large_inset_splits_polygon() {
+        // two squares connected by a thin corridor
+        let dumbbell = Polygon::new(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(100.0, 45.0),
+            Vec2::new(200.0, 45.0),
+            Vec2::new(200.0, 0.0),
+            Vec2::new(300.0, 0.0),
+            Vec2::new(300.0, 100.0),
+            Vec2::new(200.0, 100.0),
+            Vec2::new(200.0, 55.0),
+            Vec2::new(100.0, 55.0),
+            Vec2::new(100.0, 100.0),
+            Vec2::new(0.0, 100.0)
+        ]);
+
+        let result = offset_polygon(&dumbbell, -10.0, JoinType::Miter(2.0));
+
+        assert_eq!(result.len(), 2);
+        assert_near(total_area(&result), 2.0 * 80.0 * 80.0, 0.5);
+    }
+
+    #[test]
+    fn inset_larger_than_polygon_vanishes() {
+        let result = offset_polygon(&square(100.0), -60.0, JoinType::Round);
+
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn outset_shrinks_holes() {
+        let hole = Polygon::new(vec![
+            Vec2::new(25.0, 25.0),
+            Vec2::new(75.0, 25.0),
+            Vec2::new(75.0, 75.0),
+            Vec2::new(25.0, 75.0)
+        ]);
+
+        let result = offset_polygons(&[square(100.0), hole], 10.0, JoinType::Miter(2.0));
+
+        assert_eq!(result.len(), 2);
+        assert_near(total_area(&result), 120.0 * 120.0 - 30.0 * 30.0, 0.5);
+    }
+
+    #[test]
+    fn outline_straight_polyline() {
+        let result = offset_polyline(&[Vec2::new(0.0, 0.0), Vec2::new(100.0, 0.0)], 5.0, JoinType::Miter(2.0), EndType::Butt);
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].points().len(), 4);
+        assert_near(total_area(&result), 1000.0, 0.5);
+    }
+
+    #[test]
+    fn outline_polyline_has_no_gap_at_bend() {
+        let points = [Vec2::new(0.0, 0.0), Vec2::new(100.0, 0.0), Vec2::new(100.0, 100.0)];
+        let result = offset_polyline(&points, 5.0, JoinType::Miter(2.0), EndType::Butt);
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].points().len(), 6);
+        assert_near(total_area(&result), 2000.0, 0.5);
+    }
+
+    #[test]
+    fn outline_polyline_ends() {
+        let points = [Vec2::new(0.0, 0.0), Vec2::new(100.0, 0.0)];
+
+        let square = offset_polyline(&points, 5.0, JoinType::Miter(2.0), EndType::Square);
+        let round = offset_polyline(&points, 5.0, JoinType::Miter(2.0), EndType::Round);
+
+        assert_near(total_area(&square), 1100.0, 0.5);
+        assert_near(total_area(&round), 1000.0 + PI * 25.0, 1.0);
+    }
+}
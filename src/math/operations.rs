@@ -4,14 +4,28 @@ pub trait Center {
     fn center(&self) -> Vec2;
 }
 
+/// True if the outlines of both objects cross or touch each other
 pub trait Intersects<T> {
     fn intersects(&self, other: &T) -> bool;
 }
 
+/// True if the object lies completely within the other one. Touching the boundary counts as inside.
 pub trait Inside<T> {
     fn inside(&self, other_object: &T) -> bool;
 }
 
+/// True if both objects share at least one point
 pub trait Overlaps<T> {
     fn overlaps(&self, other_object: &T) -> bool;
-}
\ No newline at end of file
+}
+
+/// Smallest distance between both objects which is zero if they overlap
+pub trait Distance<T> {
+    fn distance(&self, other_object: &T) -> f32;
+}
+
+impl Distance<Vec2> for Vec2 {
+    fn distance(&self, other_object: &Vec2) -> f32 {
+        (*self - *other_object).length()
+    }
+}
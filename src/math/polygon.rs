@@ -1,12 +1,143 @@
 use bevy::prelude::*;
 
-use crate::math::operations::{ Center, Intersects, Inside };
+use crate::math::operations::{ Center, Distance, Intersects, Inside, Overlaps };
 use crate::math::line::Line;
 
+/// Points closer than this to an edge are on the boundary
+const BOUNDARY_EPSILON: f32 = 1e-3;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Containment {
+    Inside,
+    OnBoundary,
+    Outside
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct Polygon {
     points: Vec<Vec2>
 }
 
+impl Polygon {
+    pub fn new(points: Vec<Vec2>) -> Polygon {
+        Polygon { points }
+    }
+
+    pub fn points(&self) -> &[Vec2] {
+        &self.points
+    }
+
+    /// Returns the area using the shoelace formula. Positive for counter-clockwise and
+    /// negative for clockwise polygons.
+    pub fn signed_area(&self) -> f32 {
+        let mut area = 0.0;
+        for line in convert_points_to_lines(self) {
+            area = area + line.point1.perp_dot(line.point2);
+        }
+
+        area * 0.5
+    }
+
+    pub fn area(&self) -> f32 {
+        self.signed_area().abs()
+    }
+
+    pub fn is_counter_clockwise(&self) -> bool {
+        self.signed_area() > 0.0
+    }
+
+    /// Returns the same polygon with the opposite orientation
+    pub fn reversed(&self) -> Polygon {
+        let mut points = self.points.clone();
+        points.reverse();
+
+        Polygon { points }
+    }
+
+    /// Returns how many times the polygon winds around the point. Counter-clockwise
+    /// loops count positive and clockwise ones negative.
+    pub fn winding_number(&self, point: Vec2) -> i32 {
+        let mut winding = 0;
+        for line in convert_points_to_lines(self) {
+            let side = (line.point2 - line.point1).perp_dot(point - line.point1);
+
+            if line.point1.y() <= point.y() {
+                if line.point2.y() > point.y() && side > 0.0 {
+                    winding = winding + 1;
+                }
+            } else if line.point2.y() <= point.y() && side < 0.0 {
+                winding = winding - 1;
+            }
+        }
+
+        winding
+    }
+
+    pub fn lines(&self) -> Vec<Line> {
+        convert_points_to_lines(self)
+    }
+
+    pub fn distance_to_boundary(&self, point: Vec2) -> f32 {
+        convert_points_to_lines(self)
+            .iter()
+            .map(|line| line.distance(&point))
+            .fold(f32::MAX, f32::min)
+    }
+
+    /// Tells whether the point lies inside, outside or on the boundary of the polygon. Points
+    /// which are surrounded by the polygon at least once count as inside.
+    pub fn classify(&self, point: Vec2) -> Containment {
+        if self.points.is_empty() {
+            return Containment::Outside;
+        }
+
+        if self.distance_to_boundary(point) <= BOUNDARY_EPSILON {
+            return Containment::OnBoundary;
+        }
+
+        if self.winding_number(point) != 0 {
+            return Containment::Inside;
+        }
+
+        Containment::Outside
+    }
+}
+
+/// Orients every ring by its nesting depth: rings inside an even number of other rings are
+/// turned counter-clockwise (outer boundaries), the others clockwise (holes).
+pub fn normalize_orientation(rings: &[Polygon]) -> Vec<Polygon> {
+    rings
+        .iter()
+        .enumerate()
+        .map(|(index, ring)| {
+            // use the first edge center which does not touch another ring for the test
+            let sample = convert_points_to_lines(ring)
+                .iter()
+                .map(|line| line.point1 + (line.point2 - line.point1) * 0.5)
+                .find(|point| {
+                    rings
+                        .iter()
+                        .enumerate()
+                        .all(|(other_index, other)| other_index == index || other.distance_to_boundary(*point) > 1e-3)
+                });
+
+            let depth = match sample {
+                Some(sample) => rings
+                    .iter()
+                    .enumerate()
+                    .filter(|(other_index, other)| *other_index != index && other.winding_number(sample) != 0)
+                    .count(),
+                None => 0
+            };
+
+            if (depth % 2 == 0) == ring.is_counter_clockwise() {
+                ring.clone()
+            } else {
+                ring.reversed()
+            }
+        })
+        .collect()
+}
 
 impl Center for Polygon {
     fn center(&self) -> Vec2 {
@@ -76,7 +207,7 @@ impl Intersects<Polygon> for Polygon {
 impl Intersects<Line> for Polygon {
     fn intersects(&self, other: &Line) -> bool {
         for line in convert_points_to_lines(self) {
-            if line.intersects(&other) {
+            if line.intersects(other) {
                 return true;
             }
         }   
@@ -87,41 +218,119 @@ impl Intersects<Line> for Polygon {
 
 impl Inside<Polygon> for Vec2 {
     fn inside(&self, other_object: &Polygon) -> bool {
-        let line = Line {
-            point1: *self,
-            point2: Vec2::new(f32::MAX / 1000.0, self.y())
-        };
+        other_object.classify(*self) != Containment::Outside
+    }
+}
+
+impl Intersects<Polygon> for Line {
+    fn intersects(&self, other: &Polygon) -> bool {
+        other.intersects(self)
+    }
+}
+
+impl Inside<Polygon> for Line {
+    fn inside(&self, other_object: &Polygon) -> bool {
+        // split the line where it crosses the boundary and check the pieces in between
+        let direction = self.point2 - self.point1;
+        let mut parameters = vec![0.0, 1.0];
+        for edge in convert_points_to_lines(other_object) {
+            if let Some(position) = self.intersects_position(&edge) {
+                parameters.push(direction.dot(position - self.point1) / direction.length_squared());
+            }
+        }
+        parameters.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        self.point1.inside(other_object) &&
+        self.point2.inside(other_object) &&
+        parameters.windows(2).all(|pair| (self.point1 + direction * ((pair[0] + pair[1]) * 0.5)).inside(other_object))
+    }
+}
+
+impl Inside<Polygon> for Polygon {
+    fn inside(&self, other_object: &Polygon) -> bool {
+        convert_points_to_lines(self).iter().all(|line| line.inside(other_object))
+    }
+}
 
-        // Return false if polygon is a point, line or empty
-        if other_object.points.len() < 3 {
+impl Overlaps<Polygon> for Vec2 {
+    fn overlaps(&self, other_object: &Polygon) -> bool {
+        self.inside(other_object)
+    }
+}
+
+impl Overlaps<Vec2> for Polygon {
+    fn overlaps(&self, other_object: &Vec2) -> bool {
+        other_object.overlaps(self)
+    }
+}
+
+impl Overlaps<Polygon> for Line {
+    fn overlaps(&self, other_object: &Polygon) -> bool {
+        self.intersects(other_object) || self.point1.inside(other_object)
+    }
+}
+
+impl Overlaps<Line> for Polygon {
+    fn overlaps(&self, other_object: &Line) -> bool {
+        other_object.overlaps(self)
+    }
+}
+
+impl Overlaps<Polygon> for Polygon {
+    fn overlaps(&self, other_object: &Polygon) -> bool {
+        if self.points.is_empty() || other_object.points.is_empty() {
             return false;
         }
 
-        let mut iter = other_object.points.iter().peekable();
-        let mut count = 0;
-        while let Some(point) = iter.next() {
-            let other = match iter.peek() {
-                Some(next) => Line {
-                    point1: *point,
-                    point2: **next,
-                },
-                None => Line {
-                    point1: *point,
-                    point2: *other_object.points.first().unwrap()
-                }
+        self.intersects(other_object) || self.points[0].inside(other_object) || other_object.points[0].inside(self)
+    }
+}
 
-            };
+impl Distance<Vec2> for Polygon {
+    fn distance(&self, other_object: &Vec2) -> f32 {
+        if other_object.inside(self) {
+            return 0.0;
+        }
+
+        self.distance_to_boundary(*other_object)
+    }
+}
+
+impl Distance<Polygon> for Vec2 {
+    fn distance(&self, other_object: &Polygon) -> f32 {
+        other_object.distance(self)
+    }
+}
 
-            if line.intersects(&other) {
-                count = count + 1;
-            } 
+impl Distance<Line> for Polygon {
+    fn distance(&self, other_object: &Line) -> f32 {
+        if self.overlaps(other_object) {
+            return 0.0;
         }
 
-        if count == 0 {
-            return false;
+        convert_points_to_lines(self)
+            .iter()
+            .map(|line| line.distance(other_object))
+            .fold(f32::MAX, f32::min)
+    }
+}
+
+impl Distance<Polygon> for Line {
+    fn distance(&self, other_object: &Polygon) -> f32 {
+        other_object.distance(self)
+    }
+}
+
+impl Distance<Polygon> for Polygon {
+    fn distance(&self, other_object: &Polygon) -> f32 {
+        if self.overlaps(other_object) {
+            return 0.0;
         }
 
-        return count % 2 != 0
+        convert_points_to_lines(self)
+            .iter()
+            .map(|line| other_object.distance(line))
+            .fold(f32::MAX, f32::min)
     }
 }
 
@@ -230,4 +439,191 @@ mod tests {
 
         assert_eq!(Vec2::new(150.0, 50.0).inside(&polygon), false);
     }
+
+    #[test]
+    fn ray_through_vertex() {
+        let diamond = Polygon::new(vec![
+            Vec2::new(50.0, 0.0),
+            Vec2::new(100.0, 50.0),
+            Vec2::new(50.0, 100.0),
+            Vec2::new(0.0, 50.0)
+        ]);
+
+        assert_eq!(diamond.classify(Vec2::new(50.0, 50.0)), Containment::Inside);
+        assert_eq!(diamond.classify(Vec2::new(-50.0, 50.0)), Containment::Outside);
+        assert_eq!(diamond.classify(Vec2::new(-50.0, 0.0)), Containment::Outside);
+        assert_eq!(diamond.classify(Vec2::new(-50.0, 100.0)), Containment::Outside);
+    }
+
+    #[test]
+    fn ray_along_horizontal_edge() {
+        let polygon = Polygon::new(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(100.0, 50.0),
+            Vec2::new(150.0, 50.0),
+            Vec2::new(150.0, 100.0),
+            Vec2::new(0.0, 100.0)
+        ]);
+
+        assert_eq!(polygon.classify(Vec2::new(50.0, 50.0)), Containment::Inside);
+        assert_eq!(polygon.classify(Vec2::new(-50.0, 50.0)), Containment::Outside);
+        assert_eq!(polygon.classify(Vec2::new(200.0, 50.0)), Containment::Outside);
+    }
+
+    #[test]
+    fn classify_concave_polygon() {
+        // comb with three teeth pointing up. Rays to the right at y = 75 cross the teeth,
+        // rays at y = 50 and y = 100 pass through vertices and along horizontal edges.
+        let comb = Polygon::new(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(100.0, 100.0),
+            Vec2::new(80.0, 100.0),
+            Vec2::new(80.0, 50.0),
+            Vec2::new(60.0, 50.0),
+            Vec2::new(60.0, 100.0),
+            Vec2::new(40.0, 100.0),
+            Vec2::new(40.0, 50.0),
+            Vec2::new(20.0, 50.0),
+            Vec2::new(20.0, 100.0),
+            Vec2::new(0.0, 100.0)
+        ]);
+
+        assert_eq!(comb.classify(Vec2::new(10.0, 75.0)), Containment::Inside);
+        assert_eq!(comb.classify(Vec2::new(30.0, 75.0)), Containment::Outside);
+        assert_eq!(comb.classify(Vec2::new(50.0, 75.0)), Containment::Inside);
+        assert_eq!(comb.classify(Vec2::new(70.0, 75.0)), Containment::Outside);
+        assert_eq!(comb.classify(Vec2::new(10.0, 100.0)), Containment::OnBoundary);
+        assert_eq!(comb.classify(Vec2::new(30.0, 50.0)), Containment::OnBoundary);
+        assert_eq!(comb.classify(Vec2::new(-10.0, 50.0)), Containment::Outside);
+        assert_eq!(comb.classify(Vec2::new(10.0, 50.0)), Containment::Inside);
+        assert_eq!(comb.classify(Vec2::new(-10.0, 100.0)), Containment::Outside);
+        assert_eq!(comb.classify(Vec2::new(90.0, 25.0)), Containment::Inside);
+    }
+
+    #[test]
+    fn classify_boundary() {
+        let polygon = Polygon::new(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(100.0, 100.0),
+            Vec2::new(0.0, 100.0)
+        ]);
+
+        assert_eq!(polygon.classify(Vec2::new(100.0, 50.0)), Containment::OnBoundary);
+        assert_eq!(polygon.classify(Vec2::new(0.0, 0.0)), Containment::OnBoundary);
+        assert_eq!(polygon.classify(Vec2::new(50.0, 0.0)), Containment::OnBoundary);
+        assert_eq!(polygon.reversed().classify(Vec2::new(50.0, 50.0)), Containment::Inside);
+        assert_eq!(Vec2::new(100.0, 50.0).inside(&polygon), true);
+    }
+
+    #[test]
+    fn area_depends_on_orientation() {
+        let polygon = Polygon::new(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(100.0, 50.0),
+            Vec2::new(0.0, 50.0)
+        ]);
+
+        assert_eq!(polygon.signed_area(), 5000.0);
+        assert_eq!(polygon.reversed().signed_area(), -5000.0);
+        assert_eq!(polygon.reversed().area(), 5000.0);
+    }
+
+    #[test]
+    fn winding_number_of_nested_loops() {
+        let polygon = Polygon::new(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(100.0, 100.0),
+            Vec2::new(0.0, 100.0)
+        ]);
+
+        assert_eq!(polygon.winding_number(Vec2::new(50.0, 50.0)), 1);
+        assert_eq!(polygon.reversed().winding_number(Vec2::new(50.0, 50.0)), -1);
+        assert_eq!(polygon.winding_number(Vec2::new(150.0, 50.0)), 0);
+    }
+
+    #[test]
+    fn line_inside_concave_polygon() {
+        let l_shape = Polygon::new(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(100.0, 40.0),
+            Vec2::new(40.0, 40.0),
+            Vec2::new(40.0, 100.0),
+            Vec2::new(0.0, 100.0)
+        ]);
+
+        assert_eq!(Line::new(Vec2::new(10.0, 10.0), Vec2::new(90.0, 10.0)).inside(&l_shape), true);
+        assert_eq!(Line::new(Vec2::new(0.0, 0.0), Vec2::new(0.0, 100.0)).inside(&l_shape), true);
+        assert_eq!(Line::new(Vec2::new(90.0, 30.0), Vec2::new(30.0, 90.0)).inside(&l_shape), false);
+    }
+
+    #[test]
+    fn polygon_inside_polygon() {
+        let outer = Polygon::new(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(100.0, 100.0),
+            Vec2::new(0.0, 100.0)
+        ]);
+
+        let inner = Polygon::new(vec![
+            Vec2::new(10.0, 10.0),
+            Vec2::new(50.0, 10.0),
+            Vec2::new(10.0, 50.0)
+        ]);
+
+        assert_eq!(inner.inside(&outer), true);
+        assert_eq!(outer.inside(&inner), false);
+        assert_eq!(outer.overlaps(&inner), true);
+        assert_eq!(inner.overlaps(&outer), true);
+        assert_eq!(outer.intersects(&inner), false);
+    }
+
+    #[test]
+    fn polygon_distances() {
+        let polygon = Polygon::new(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(100.0, 100.0),
+            Vec2::new(0.0, 100.0)
+        ]);
+
+        let other = Polygon::new(vec![
+            Vec2::new(130.0, 0.0),
+            Vec2::new(200.0, 0.0),
+            Vec2::new(200.0, 100.0)
+        ]);
+
+        assert_eq!(polygon.distance(&Vec2::new(50.0, 50.0)), 0.0);
+        assert_eq!(polygon.distance(&Vec2::new(50.0, 120.0)), 20.0);
+        assert_eq!(polygon.distance(&Line::new(Vec2::new(110.0, 0.0), Vec2::new(110.0, 100.0))), 10.0);
+        assert_eq!(polygon.distance(&other), 30.0);
+    }
+
+    #[test]
+    fn normalize_orientation_of_holes() {
+        let outer = Polygon::new(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(0.0, 100.0),
+            Vec2::new(100.0, 100.0),
+            Vec2::new(100.0, 0.0)
+        ]);
+
+        let hole = Polygon::new(vec![
+            Vec2::new(25.0, 25.0),
+            Vec2::new(75.0, 25.0),
+            Vec2::new(75.0, 75.0),
+            Vec2::new(25.0, 75.0)
+        ]);
+
+        let rings = normalize_orientation(&[outer, hole]);
+
+        assert_eq!(rings[0].is_counter_clockwise(), true);
+        assert_eq!(rings[1].is_counter_clockwise(), false);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,319 @@
+use bevy::prelude::*;
+
+use crate::math::operations::{ Center, Distance, Inside, Intersects, Overlaps };
+use crate::math::circle::Circle;
+use crate::math::line::Line;
+use crate::math::polygon::Polygon;
+
+/// Axis aligned rectangle
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rectangle {
+    pub min: Vec2,
+    pub max: Vec2
+}
+
+impl Rectangle {
+    /// Creates the rectangle spanned by two opposite corners given in any order
+    pub fn from_corners(corner1: Vec2, corner2: Vec2) -> Rectangle {
+        Rectangle {
+            min: Vec2::new(corner1.x().min(corner2.x()), corner1.y().min(corner2.y())),
+            max: Vec2::new(corner1.x().max(corner2.x()), corner1.y().max(corner2.y()))
+        }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.max.x() - self.min.x()
+    }
+
+    pub fn height(&self) -> f32 {
+        self.max.y() - self.min.y()
+    }
+
+    pub fn to_polygon(&self) -> Polygon {
+        Polygon::new(vec![
+            self.min,
+            Vec2::new(self.max.x(), self.min.y()),
+            self.max,
+            Vec2::new(self.min.x(), self.max.y())
+        ])
+    }
+
+    /// Returns the point of the rectangle which is closest to the given one
+    pub fn closest_point(&self, point: Vec2) -> Vec2 {
+        Vec2::new(
+            point.x().max(self.min.x()).min(self.max.x()),
+            point.y().max(self.min.y()).min(self.max.y())
+        )
+    }
+}
+
+impl Center for Rectangle {
+    fn center(&self) -> Vec2 {
+        (self.min + self.max) * 0.5
+    }
+}
+
+impl Inside<Rectangle> for Vec2 {
+    fn inside(&self, other_object: &Rectangle) -> bool {
+        self.x() >= other_object.min.x() && self.x() <= other_object.max.x() &&
+        self.y() >= other_object.min.y() && self.y() <= other_object.max.y()
+    }
+}
+
+impl Inside<Rectangle> for Line {
+    fn inside(&self, other_object: &Rectangle) -> bool {
+        self.point1.inside(other_object) && self.point2.inside(other_object)
+    }
+}
+
+impl Inside<Rectangle> for Polygon {
+    fn inside(&self, other_object: &Rectangle) -> bool {
+        self.points().iter().all(|point| point.inside(other_object))
+    }
+}
+
+impl Inside<Rectangle> for Circle {
+    fn inside(&self, other_object: &Rectangle) -> bool {
+        let radius = Vec2::new(self.radius, self.radius);
+
+        (self.center - radius).inside(other_object) && (self.center + radius).inside(other_object)
+    }
+}
+
+impl Inside<Rectangle> for Rectangle {
+    fn inside(&self, other_object: &Rectangle) -> bool {
+        self.min.inside(other_object) && self.max.inside(other_object)
+    }
+}
+
+impl Inside<Circle> for Rectangle {
+    fn inside(&self, other_object: &Circle) -> bool {
+        self.to_polygon().inside(other_object)
+    }
+}
+
+impl Inside<Polygon> for Rectangle {
+    fn inside(&self, other_object: &Polygon) -> bool {
+        self.to_polygon().inside(other_object)
+    }
+}
+
+impl Intersects<Rectangle> for Line {
+    fn intersects(&self, other: &Rectangle) -> bool {
+        other.to_polygon().intersects(self)
+    }
+}
+
+impl Intersects<Line> for Rectangle {
+    fn intersects(&self, other: &Line) -> bool {
+        self.to_polygon().intersects(other)
+    }
+}
+
+impl Intersects<Rectangle> for Polygon {
+    fn intersects(&self, other: &Rectangle) -> bool {
+        self.intersects(&other.to_polygon())
+    }
+}
+
+impl Intersects<Polygon> for Rectangle {
+    fn intersects(&self, other: &Polygon) -> bool {
+        self.to_polygon().intersects(other)
+    }
+}
+
+impl Intersects<Rectangle> for Circle {
+    fn intersects(&self, other: &Rectangle) -> bool {
+        self.intersects(&other.to_polygon())
+    }
+}
+
+impl Intersects<Circle> for Rectangle {
+    fn intersects(&self, other: &Circle) -> bool {
+        other.intersects(self)
+    }
+}
+
+impl Intersects<Rectangle> for Rectangle {
+    fn intersects(&self, other: &Rectangle) -> bool {
+        self.to_polygon().intersects(&other.to_polygon())
+    }
+}
+
+impl Overlaps<Rectangle> for Vec2 {
+    fn overlaps(&self, other_object: &Rectangle) -> bool {
+        self.inside(other_object)
+    }
+}
+
+impl Overlaps<Vec2> for Rectangle {
+    fn overlaps(&self, other_object: &Vec2) -> bool {
+        other_object.inside(self)
+    }
+}
+
+impl Overlaps<Rectangle> for Line {
+    fn overlaps(&self, other_object: &Rectangle) -> bool {
+        self.overlaps(&other_object.to_polygon())
+    }
+}
+
+impl Overlaps<Line> for Rectangle {
+    fn overlaps(&self, other_object: &Line) -> bool {
+        other_object.overlaps(self)
+    }
+}
+
+impl Overlaps<Rectangle> for Polygon {
+    fn overlaps(&self, other_object: &Rectangle) -> bool {
+        self.overlaps(&other_object.to_polygon())
+    }
+}
+
+impl Overlaps<Polygon> for Rectangle {
+    fn overlaps(&self, other_object: &Polygon) -> bool {
+        other_object.overlaps(self)
+    }
+}
+
+impl Overlaps<Rectangle> for Circle {
+    fn overlaps(&self, other_object: &Rectangle) -> bool {
+        (other_object.closest_point(self.center) - self.center).length() <= self.radius
+    }
+}
+
+impl Overlaps<Circle> for Rectangle {
+    fn overlaps(&self, other_object: &Circle) -> bool {
+        other_object.overlaps(self)
+    }
+}
+
+impl Overlaps<Rectangle> for Rectangle {
+    fn overlaps(&self, other_object: &Rectangle) -> bool {
+        self.min.x() <= other_object.max.x() && other_object.min.x() <= self.max.x() &&
+        self.min.y() <= other_object.max.y() && other_object.min.y() <= self.max.y()
+    }
+}
+
+impl Distance<Vec2> for Rectangle {
+    fn distance(&self, other_object: &Vec2) -> f32 {
+        (self.closest_point(*other_object) - *other_object).length()
+    }
+}
+
+impl Distance<Rectangle> for Vec2 {
+    fn distance(&self, other_object: &Rectangle) -> f32 {
+        other_object.distance(self)
+    }
+}
+
+impl Distance<Line> for Rectangle {
+    fn distance(&self, other_object: &Line) -> f32 {
+        self.to_polygon().distance(other_object)
+    }
+}
+
+impl Distance<Rectangle> for Line {
+    fn distance(&self, other_object: &Rectangle) -> f32 {
+        other_object.distance(self)
+    }
+}
+
+impl Distance<Polygon> for Rectangle {
+    fn distance(&self, other_object: &Polygon) -> f32 {
+        self.to_polygon().distance(other_object)
+    }
+}
+
+impl Distance<Rectangle> for Polygon {
+    fn distance(&self, other_object: &Rectangle) -> f32 {
+        other_object.distance(self)
+    }
+}
+
+impl Distance<Circle> for Rectangle {
+    fn distance(&self, other_object: &Circle) -> f32 {
+        (self.distance(&other_object.center) - other_object.radius).max(0.0)
+    }
+}
+
+impl Distance<Rectangle> for Circle {
+    fn distance(&self, other_object: &Rectangle) -> f32 {
+        other_object.distance(self)
+    }
+}
+
+impl Distance<Rectangle> for Rectangle {
+    fn distance(&self, other_object: &Rectangle) -> f32 {
+        let dx = (other_object.min.x() - self.max.x()).max(self.min.x() - other_object.max.x()).max(0.0);
+        let dy = (other_object.min.y() - self.max.y()).max(self.min.y() - other_object.max.y()).max(0.0);
+
+        Vec2::new(dx, dy).length()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use super::*;
+
+    #[test]
+    fn corners_in_any_order() {
+        let rectangle = Rectangle::from_corners(Vec2::new(100.0, 0.0), Vec2::new(0.0, 50.0));
+
+        assert_eq!(rectangle.min, Vec2::new(0.0, 0.0));
+        assert_eq!(rectangle.max, Vec2::new(100.0, 50.0));
+        assert_eq!(rectangle.center(), Vec2::new(50.0, 25.0));
+    }
+
+    #[test]
+    fn point_and_line_inside_rectangle() {
+        let rectangle = Rectangle::from_corners(Vec2::new(0.0, 0.0), Vec2::new(100.0, 100.0));
+
+        assert_eq!(Vec2::new(100.0, 50.0).inside(&rectangle), true);
+        assert_eq!(Vec2::new(101.0, 50.0).inside(&rectangle), false);
+        assert_eq!(Line::new(Vec2::new(10.0, 10.0), Vec2::new(90.0, 90.0)).inside(&rectangle), true);
+        assert_eq!(Line::new(Vec2::new(10.0, 10.0), Vec2::new(190.0, 90.0)).inside(&rectangle), false);
+        assert_eq!(Line::new(Vec2::new(10.0, 10.0), Vec2::new(190.0, 90.0)).intersects(&rectangle), true);
+    }
+
+    #[test]
+    fn rectangles() {
+        let rectangle = Rectangle::from_corners(Vec2::new(0.0, 0.0), Vec2::new(100.0, 100.0));
+        let inner = Rectangle::from_corners(Vec2::new(10.0, 10.0), Vec2::new(20.0, 20.0));
+        let far = Rectangle::from_corners(Vec2::new(130.0, 140.0), Vec2::new(200.0, 200.0));
+
+        assert_eq!(inner.inside(&rectangle), true);
+        assert_eq!(inner.intersects(&rectangle), false);
+        assert_eq!(inner.overlaps(&rectangle), true);
+        assert_eq!(far.overlaps(&rectangle), false);
+        assert_eq!(far.distance(&rectangle), 50.0);
+    }
+
+    #[test]
+    fn rectangle_and_circle() {
+        let rectangle = Rectangle::from_corners(Vec2::new(0.0, 0.0), Vec2::new(100.0, 100.0));
+
+        assert_eq!(Circle::new(Vec2::new(50.0, 50.0), 50.0).inside(&rectangle), true);
+        assert_eq!(Circle::new(Vec2::new(50.0, 50.0), 51.0).inside(&rectangle), false);
+        assert_eq!(Circle::new(Vec2::new(120.0, 50.0), 30.0).overlaps(&rectangle), true);
+        assert_eq!(Circle::new(Vec2::new(103.0, 104.0), 4.0).overlaps(&rectangle), false);
+        assert_eq!(rectangle.distance(&Circle::new(Vec2::new(106.0, 108.0), 5.0)), 5.0);
+        assert_eq!(rectangle.inside(&Circle::new(Vec2::new(50.0, 50.0), 71.0)), true);
+    }
+
+    #[test]
+    fn rectangle_and_polygon() {
+        let rectangle = Rectangle::from_corners(Vec2::new(0.0, 0.0), Vec2::new(100.0, 100.0));
+        let triangle = Polygon::new(vec![
+            Vec2::new(50.0, 50.0),
+            Vec2::new(150.0, 50.0),
+            Vec2::new(150.0, 150.0)
+        ]);
+
+        assert_eq!(triangle.overlaps(&rectangle), true);
+        assert_eq!(triangle.intersects(&rectangle), true);
+        assert_eq!(triangle.inside(&rectangle), false);
+        assert_eq!(rectangle.inside(&triangle), false);
+    }
+}
@@ -0,0 +1,324 @@
+use bevy::prelude::*;
+
+use rand::Rng;
+
+use crate::math::operations::Center;
+use crate::math::polygon::{ normalize_orientation, Polygon };
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Triangle {
+    pub a: Vec2,
+    pub b: Vec2,
+    pub c: Vec2
+}
+
+impl Triangle {
+    pub fn new(a: Vec2, b: Vec2, c: Vec2) -> Triangle {
+        Triangle { a, b, c }
+    }
+
+    pub fn area(&self) -> f32 {
+        ((self.b - self.a).perp_dot(self.c - self.a) * 0.5).abs()
+    }
+
+    /// Returns true if the point lies inside or on the boundary of the triangle
+    pub fn contains(&self, point: Vec2) -> bool {
+        let d1 = (self.b - self.a).perp_dot(point - self.a);
+        let d2 = (self.c - self.b).perp_dot(point - self.b);
+        let d3 = (self.a - self.c).perp_dot(point - self.c);
+
+        let has_negative = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+        let has_positive = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+
+        !(has_negative && has_positive)
+    }
+
+    /// Picks a random point which is evenly distributed over the triangle
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> Vec2 {
+        let mut u = rng.gen::<f32>();
+        let mut v = rng.gen::<f32>();
+
+        if u + v > 1.0 {
+            u = 1.0 - u;
+            v = 1.0 - v;
+        }
+
+        self.a + (self.b - self.a) * u + (self.c - self.a) * v
+    }
+}
+
+impl Center for Triangle {
+    fn center(&self) -> Vec2 {
+        (self.a + self.b + self.c) / 3.0
+    }
+}
+
+fn is_convex(previous: Vec2, current: Vec2, next: Vec2) -> bool {
+    (current - previous).perp_dot(next - current) > 0.0
+}
+
+/// Returns the point where a ray from origin to the right hits the segment
+fn ray_hit(origin: Vec2, start: Vec2, end: Vec2) -> Option<Vec2> {
+    if (start.y() > origin.y()) == (end.y() > origin.y()) {
+        return None;
+    }
+
+    let t = (origin.y() - start.y()) / (end.y() - start.y());
+    let x = start.x() + t * (end.x() - start.x());
+    if x < origin.x() {
+        return None;
+    }
+
+    Some(Vec2::new(x, origin.y()))
+}
+
+/// Connects the hole with the counter-clockwise outer ring by a bridge to a visible vertex
+/// as described by David Eberly in "Triangulation by Ear Clipping".
+fn merge_hole(outer: &mut Vec<Vec2>, hole: &[Vec2]) {
+    let (hole_index, origin) = hole
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.x().partial_cmp(&b.1.x()).unwrap())
+        .map(|(index, point)| (index, *point))
+        .unwrap();
+
+    // find the closest edge hit by a ray from the rightmost hole vertex
+    let mut closest: Option<(usize, Vec2)> = None;
+    for i in 0..outer.len() {
+        let start = outer[i];
+        let end = outer[(i + 1) % outer.len()];
+
+        if let Some(hit) = ray_hit(origin, start, end) {
+            if closest.map_or(true, |(_, other)| hit.x() < other.x()) {
+                closest = Some((i, hit));
+            }
+        }
+    }
+
+    let (edge, hit) = match closest {
+        Some(closest) => closest,
+        None => return
+    };
+
+    let next = (edge + 1) % outer.len();
+    let mut bridge = if outer[edge].x() > outer[next].x() { edge } else { next };
+
+    // reflex vertices inside the triangle origin, hit, candidate would block the view
+    let candidate = outer[bridge];
+    let view = Triangle::new(origin, hit, candidate);
+    let mut best_angle = f32::MAX;
+    for i in 0..outer.len() {
+        let point = outer[i];
+        let previous = outer[(i + outer.len() - 1) % outer.len()];
+        let following = outer[(i + 1) % outer.len()];
+
+        if point == candidate || is_convex(previous, point, following) || !view.contains(point) {
+            continue;
+        }
+
+        let direction = point - origin;
+        let angle = direction.y().abs().atan2(direction.x());
+        if angle < best_angle {
+            best_angle = angle;
+            bridge = i;
+        }
+    }
+
+    let mut merged = outer[..=bridge].to_vec();
+    for i in 0..=hole.len() {
+        merged.push(hole[(hole_index + i) % hole.len()]);
+    }
+    merged.extend_from_slice(&outer[bridge..]);
+
+    *outer = merged;
+}
+
+fn clip_ears(mut points: Vec<Vec2>, triangles: &mut Vec<Triangle>) {
+    while points.len() > 3 {
+        let count = points.len();
+        let mut ear = None;
+
+        for i in 0..count {
+            let previous = points[(i + count - 1) % count];
+            let current = points[i];
+            let next = points[(i + 1) % count];
+
+            if !is_convex(previous, current, next) {
+                continue;
+            }
+
+            let triangle = Triangle::new(previous, current, next);
+            let blocked = points.iter().any(|point| {
+                *point != previous && *point != current && *point != next && triangle.contains(*point)
+            });
+
+            if !blocked {
+                ear = Some(i);
+                break;
+            }
+        }
+
+        // degenerated rings have no ear, the first vertex is as good as any other then
+        let i = ear.unwrap_or(0);
+        let triangle = Triangle::new(points[(i + count - 1) % count], points[i], points[(i + 1) % count]);
+        if triangle.area() > 0.0 {
+            triangles.push(triangle);
+        }
+
+        points.remove(i);
+    }
+
+    if points.len() == 3 {
+        let triangle = Triangle::new(points[0], points[1], points[2]);
+        if triangle.area() > 0.0 {
+            triangles.push(triangle);
+        }
+    }
+}
+
+/// Splits the area described by the rings into triangles. Rings inside an odd number of
+/// other rings are treated as holes.
+pub fn triangulate(polygons: &[Polygon]) -> Vec<Triangle> {
+    let rings = normalize_orientation(polygons);
+
+    let (outers, holes): (Vec<&Polygon>, Vec<&Polygon>) = rings.iter().partition(|ring| ring.is_counter_clockwise());
+
+    let mut triangles = Vec::new();
+    for outer in &outers {
+        // a hole belongs to the smallest outer ring surrounding it
+        let mut own_holes: Vec<&Polygon> = holes
+            .iter()
+            .filter(|hole| {
+                let point = hole.points()[0];
+                let parent = outers
+                    .iter()
+                    .filter(|other| other.winding_number(point) != 0)
+                    .min_by(|a, b| a.area().partial_cmp(&b.area()).unwrap());
+
+                parent.map_or(false, |parent| std::ptr::eq(*parent, *outer))
+            })
+            .cloned()
+            .collect();
+
+        own_holes.sort_by(|a, b| {
+            let a = a.points().iter().map(|p| p.x()).fold(f32::MIN, f32::max);
+            let b = b.points().iter().map(|p| p.x()).fold(f32::MIN, f32::max);
+            b.partial_cmp(&a).unwrap()
+        });
+
+        let mut points = outer.points().to_vec();
+        for hole in own_holes {
+            merge_hole(&mut points, hole.points());
+        }
+
+        clip_ears(points, &mut triangles);
+    }
+
+    triangles
+}
+
+/// Scatters points evenly over the triangles
+pub fn sample_uniform<R: Rng>(triangles: &[Triangle], count: usize, rng: &mut R) -> Vec<Vec2> {
+    let total: f32 = triangles.iter().map(|triangle| triangle.area()).sum();
+    if triangles.is_empty() || total <= 0.0 {
+        return Vec::new();
+    }
+
+    (0..count)
+        .map(|_| {
+            let mut remaining = rng.gen::<f32>() * total;
+            let triangle = triangles
+                .iter()
+                .find(|triangle| {
+                    remaining -= triangle.area();
+                    remaining <= 0.0
+                })
+                .unwrap_or(&triangles[triangles.len() - 1]);
+
+            triangle.sample(rng)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use super::*;
+
+    fn square(x: f32, y: f32, size: f32) -> Polygon {
+        Polygon::new(vec![
+            Vec2::new(x, y),
+            Vec2::new(x + size, y),
+            Vec2::new(x + size, y + size),
+            Vec2::new(x, y + size)
+        ])
+    }
+
+    fn total_area(triangles: &[Triangle]) -> f32 {
+        triangles.iter().map(|triangle| triangle.area()).sum()
+    }
+
+    #[test]
+    fn triangulate_square() {
+        let triangles = triangulate(&[square(0.0, 0.0, 100.0)]);
+
+        assert_eq!(triangles.len(), 2);
+        assert_eq!(total_area(&triangles), 10000.0);
+    }
+
+    #[test]
+    fn triangulate_clockwise_concave_polygon() {
+        let l_shape = Polygon::new(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(0.0, 100.0),
+            Vec2::new(40.0, 100.0),
+            Vec2::new(40.0, 40.0),
+            Vec2::new(100.0, 40.0),
+            Vec2::new(100.0, 0.0)
+        ]);
+
+        let triangles = triangulate(&[l_shape.clone()]);
+
+        assert_eq!(triangles.len(), 4);
+        assert!((total_area(&triangles) - l_shape.area()).abs() < 0.01);
+    }
+
+    #[test]
+    fn triangulate_polygon_with_holes() {
+        let rings = [square(0.0, 0.0, 100.0), square(10.0, 10.0, 30.0), square(60.0, 60.0, 30.0)];
+        let triangles = triangulate(&rings);
+
+        assert_eq!(triangles.len(), 4 + 2 * 4 + 2 * 2 - 2);
+        assert!((total_area(&triangles) - (10000.0 - 2.0 * 900.0)).abs() < 0.01);
+
+        for triangle in &triangles {
+            let center = triangle.center();
+            assert!(rings[1].winding_number(center) == 0 && rings[2].winding_number(center) == 0);
+        }
+    }
+
+    #[test]
+    fn triangulate_separate_polygons() {
+        let triangles = triangulate(&[square(0.0, 0.0, 100.0), square(200.0, 0.0, 100.0)]);
+
+        assert_eq!(triangles.len(), 4);
+        assert_eq!(total_area(&triangles), 20000.0);
+    }
+
+    #[test]
+    fn samples_are_inside_and_evenly_distributed() {
+        let rings = [square(0.0, 0.0, 100.0), square(25.0, 25.0, 50.0)];
+        let triangles = triangulate(&rings);
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let samples = sample_uniform(&triangles, 2000, &mut rng);
+
+        assert_eq!(samples.len(), 2000);
+        assert!(samples.iter().all(|p| rings[0].winding_number(*p) != 0 && rings[1].winding_number(*p) == 0));
+
+        let left = samples.iter().filter(|p| p.x() < 50.0).count() as f32 / samples.len() as f32;
+        assert!((left - 0.5).abs() < 0.05);
+    }
+}
@@ -0,0 +1,298 @@
+use bevy::prelude::*;
+
+use crate::app_state::AppState;
+use crate::input;
+use crate::keybindings::{ Action, Actions };
+use crate::math::offset::{ offset_polyline, EndType, JoinType };
+use crate::math::polygon::Polygon;
+use crate::roadsystem::RoadSystem;
+use crate::routing::{ route_between_points, Route };
+use crate::shapes::spawn_polygons;
+use crate::theme::ThemeMaterials;
+use crate::ui;
+use crate::view::CameraView;
+
+/// Change of the scale per key press
+const SCALE_STEP: f32 = 0.1;
+
+/// Maximal distance of a measured point to the street it is snapped to
+const SNAP_DISTANCE: f32 = 50.0;
+
+/// Conversion from world units to metres
+pub struct Units {
+    pub metres_per_unit: f32
+}
+
+impl Default for Units {
+    fn default() -> Units {
+        Units {
+            metres_per_unit: 1.0
+        }
+    }
+}
+
+impl Units {
+    pub fn format_length(&self, length: f32) -> String {
+        let metres = length * self.metres_per_unit;
+
+        if metres >= 1000.0 {
+            format!("{:.2} km", metres / 1000.0)
+        } else {
+            format!("{:.0} m", metres)
+        }
+    }
+
+    pub fn format_area(&self, area: f32) -> String {
+        let square_metres = area * self.metres_per_unit * self.metres_per_unit;
+
+        if square_metres >= 10000.0 {
+            format!("{:.2} ha", square_metres / 10000.0)
+        } else {
+            format!("{:.0} m²", square_metres)
+        }
+    }
+}
+
+/// Points and block measured with the measure tool
+#[derive(Default)]
+pub struct Measurement {
+    points: Vec<Vec2>,
+
+    /// Shortest route between the points on the streets closest to them
+    route: Option<Route>,
+    block: Option<Polygon>
+}
+
+impl Measurement {
+    fn is_empty(&self) -> bool {
+        self.points.is_empty() && self.block.is_none()
+    }
+
+    /// Returns the measured values formatted in the units
+    fn readout(&self, units: &Units) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        if let [start, end] = self.points.as_slice() {
+            lines.push(format!("Distance {}", units.format_length((*end - *start).length())));
+
+            lines.push(match &self.route {
+                Some(route) => format!("Along streets {}", units.format_length(route.length)),
+                None => "No route along streets".to_string()
+            });
+        }
+
+        if let Some(block) = &self.block {
+            lines.push(format!("Block area {}", units.format_area(block.area())));
+        }
+
+        lines.push(format!("Scale {:.1} m per unit", units.metres_per_unit));
+        lines
+    }
+}
+
+/// Marks the entities showing the measurement in the world
+pub struct MeasureOverlay;
+
+/// Marks the panel showing the measured values
+pub struct MeasurePanel;
+
+pub struct MeasureText;
+
+/// Left click measures between two points, right click measures the area of a block
+fn measure_system(
+    app_state: Res<AppState>,
+    current_action: Res<ui::RoadActions>,
+    state: Res<input::MouseState>,
+    view: Res<CameraView>,
+    actions: Res<Actions>,
+    mut units: ResMut<Units>,
+    mut measurement: ResMut<Measurement>,
+    mut graph_query: Query<&RoadSystem>
+) {
+    if *app_state != AppState::Editing {
+        return;
+    }
+
+    if *current_action != ui::RoadActions::Measure {
+        if !measurement.is_empty() {
+            *measurement = Measurement::default();
+        }
+
+        return;
+    }
+
+    if actions.just_pressed(Action::Increase) {
+        units.metres_per_unit += SCALE_STEP;
+    } else if actions.just_pressed(Action::Decrease) {
+        units.metres_per_unit = (units.metres_per_unit - SCALE_STEP).max(SCALE_STEP);
+    }
+
+    let mouse_pos_ws = crate::mouse_pos_ws(&state, &view);
+
+    for road_system in &mut graph_query.iter() {
+        if actions.just_pressed(Action::Secondary) {
+            measurement.block = road_system.block_at(mouse_pos_ws);
+        }
+
+        if !actions.just_pressed(Action::Primary) {
+            continue;
+        }
+
+        if measurement.points.len() == 2 {
+            measurement.points.clear();
+            measurement.route = None;
+        }
+
+        measurement.points.push(mouse_pos_ws);
+
+        if let [start, end] = measurement.points.as_slice() {
+            measurement.route = route_between_points(&road_system, *start, *end, SNAP_DISTANCE);
+        }
+    }
+}
+
+fn measure_overlay_system(
+    mut commands: Commands,
+    measurement: ChangedRes<Measurement>,
+    theme_materials: Res<ThemeMaterials>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut overlay_query: Query<With<MeasureOverlay, Entity>>,
+    mut graph_query: Query<&RoadSystem>
+) {
+    for entity in &mut overlay_query.iter() {
+        commands.despawn(entity);
+    }
+
+    let route_material = theme_materials.route.clone();
+    let area_material = theme_materials.area.clone();
+
+    if measurement.points.len() == 2 {
+        let outline = offset_polyline(&measurement.points, 1.5, JoinType::Miter(2.0), EndType::Butt);
+
+        spawn_polygons(&mut commands, &outline, route_material.clone(), &mut meshes, 3.0)
+        .with(MeasureOverlay);
+    }
+
+    if let Some(route) = &measurement.route {
+        for road_system in &mut graph_query.iter() {
+            for street in &route.streets {
+                if let (Some(line), Some(road_type)) = (road_system.street_line(*street), road_system.road_type(*street)) {
+                    let outline = offset_polyline(&[line.point1, line.point2], road_type.width() / 2.0 + 2.0, JoinType::Miter(2.0), EndType::Butt);
+
+                    spawn_polygons(&mut commands, &outline, route_material.clone(), &mut meshes, 0.5)
+                    .with(MeasureOverlay);
+                }
+            }
+        }
+    }
+
+    if let Some(block) = &measurement.block {
+        spawn_polygons(&mut commands, &[block.clone()], area_material, &mut meshes, -0.5)
+        .with(MeasureOverlay);
+    }
+}
+
+fn measure_readout_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    button_materials: Res<ui::ButtonMaterials>,
+    units: Res<Units>,
+    measurement: Res<Measurement>,
+    mut panel_query: Query<With<MeasurePanel, Entity>>,
+    mut text_query: Query<With<MeasureText, (Entity, &mut Text)>>
+) {
+    if measurement.is_empty() {
+        for entity in &mut panel_query.iter() {
+            commands.despawn(entity);
+        }
+
+        for (entity, _) in &mut text_query.iter() {
+            commands.despawn(entity);
+        }
+
+        return;
+    }
+
+    let readout = measurement.readout(&units).join(" | ");
+
+    let mut shown = false;
+    for (_, mut text) in &mut text_query.iter() {
+        if text.value != readout {
+            text.value = readout.clone();
+        }
+
+        shown = true;
+    }
+
+    if shown {
+        return;
+    }
+
+    let position = Rect {
+        left: Val::Px(10.0),
+        bottom: Val::Px(70.0),
+        ..Default::default()
+    };
+
+    commands
+    .spawn(NodeComponents {
+        style: ui::panel_style(position),
+        material: button_materials.normal.clone(),
+        ..Default::default()
+    })
+    .with(MeasurePanel)
+    .with_children(|parent| {
+        parent
+        .spawn(TextComponents {
+            text: ui::ui_text(&readout, &asset_server),
+            ..Default::default()
+        })
+        .with(MeasureText)
+        .with(ui::PanelText);
+    });
+}
+
+pub struct MeasurePlugin;
+
+impl Plugin for MeasurePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<Units>();
+        app.init_resource::<Measurement>();
+        app.add_system_to_stage("do_things", measure_system.system());
+        app.add_system_to_stage("do_things", measure_overlay_system.system());
+        app.add_system_to_stage("do_things", measure_readout_system.system());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use super::*;
+
+    #[test]
+    fn format_in_metres() {
+        let units = Units { metres_per_unit: 2.0 };
+
+        assert_eq!(units.format_length(100.0), "200 m");
+        assert_eq!(units.format_length(1000.0), "2.00 km");
+        assert_eq!(units.format_area(100.0), "400 m²");
+        assert_eq!(units.format_area(5000.0), "2.00 ha");
+    }
+
+    #[test]
+    fn readout_of_points_and_block() {
+        let units = Units::default();
+        let measurement = Measurement {
+            points: vec![Vec2::new(0.0, 0.0), Vec2::new(30.0, 40.0)],
+            route: None,
+            block: Some(Polygon::new(vec![Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0), Vec2::new(10.0, 10.0)]))
+        };
+
+        assert_eq!(measurement.readout(&units), vec![
+            "Distance 50 m".to_string(),
+            "No route along streets".to_string(),
+            "Block area 50 m²".to_string(),
+            "Scale 1.0 m per unit".to_string()
+        ]);
+    }
+}
@@ -0,0 +1,321 @@
+use bevy::{
+    prelude::*,
+    render::texture::TextureFormat,
+};
+
+use crate::app_state::AppState;
+use crate::input;
+use crate::math::line::Line;
+use crate::math::operations::Center;
+use crate::math::rectangle::Rectangle;
+use crate::roadsystem::RoadSystem;
+use crate::theme::Theme;
+use crate::view::CameraView;
+
+/// Width and height of the minimap on the screen
+const MINIMAP_SIZE: f32 = 240.0;
+
+/// Distance of the minimap to the right border of the window
+const MINIMAP_MARGIN: f32 = 10.0;
+
+/// Streets are drawn at least this wide to stay visible
+const MIN_STREET_WIDTH: f32 = 1.0;
+
+/// Width of the outline of the area visible in the window
+const VIEWPORT_WIDTH: f32 = 2.0;
+
+/// Overview of the whole road network in a corner of the window
+pub struct Minimap {
+    /// Part of the world which is shown, always a square
+    pub bounds: Rectangle,
+
+    /// Image the minimap is drawn into
+    texture: Option<Handle<Texture>>,
+
+    /// Camera position and zoom the image was drawn for
+    shown_camera: Option<(Vec2, f32)>
+}
+
+impl Default for Minimap {
+    fn default() -> Minimap {
+        Minimap {
+            bounds: Minimap::bounds_of(&RoadSystem::new()),
+            texture: None,
+            shown_camera: None
+        }
+    }
+}
+
+impl Minimap {
+    /// Returns the square containing the network and the view at the origin
+    pub fn bounds_of(road_system: &RoadSystem) -> Rectangle {
+        let half_window = Vec2::new(crate::WINDOW_WIDTH as f32, crate::WINDOW_HEIGHT as f32) / 2.0;
+        let mut min = -half_window;
+        let mut max = half_window;
+
+        for intersection in road_system.intersections() {
+            let position = road_system.intersection_position(intersection).unwrap();
+
+            min = min.min(position);
+            max = max.max(position);
+        }
+
+        let center = (min + max) / 2.0;
+        let half_size = (max.x() - min.x()).max(max.y() - min.y()) / 2.0;
+
+        Rectangle {
+            min: center - Vec2::new(half_size, half_size),
+            max: center + Vec2::new(half_size, half_size)
+        }
+    }
+
+    fn scale(&self) -> f32 {
+        MINIMAP_SIZE / self.bounds.width()
+    }
+
+    /// Converts a position in the world into the minimap, relative to its center
+    pub fn to_minimap(&self, position: Vec2) -> Vec2 {
+        (position - self.bounds.center()) * self.scale()
+    }
+
+    /// Converts a position in the minimap, relative to its center, into the world
+    pub fn to_world(&self, position: Vec2) -> Vec2 {
+        position / self.scale() + self.bounds.center()
+    }
+
+    /// Returns the pixels of the minimap with the streets and the outline of the area visible
+    /// in the window. The rows go from top to bottom, each pixel has four bytes.
+    pub fn draw(&self, road_system: &RoadSystem, camera_position: Vec2, camera_zoom: f32, theme: &Theme) -> Vec<u8> {
+        let size = MINIMAP_SIZE as usize;
+        let mut pixels: Vec<u8> = (0..size * size).flat_map(|_| rgba(theme.minimap_background).to_vec()).collect();
+
+        let scale = self.scale();
+        for (road_type, polyline) in road_system.street_polylines() {
+            let width = (road_type.width() * scale).max(MIN_STREET_WIDTH);
+
+            for points in polyline.windows(2) {
+                let line = Line::new(to_pixel(self.to_minimap(points[0])), to_pixel(self.to_minimap(points[1])));
+                draw_line(&mut pixels, line, width, theme.minimap_street);
+            }
+        }
+
+        let half_window = Vec2::new(crate::WINDOW_WIDTH as f32, crate::WINDOW_HEIGHT as f32) / 2.0 * camera_zoom;
+        let half_size = Vec2::new(MINIMAP_SIZE, MINIMAP_SIZE) / 2.0;
+        let area = Rectangle { min: -half_size, max: half_size };
+
+        // parts of the viewport outside of the minimap are cut off
+        let viewport = Rectangle::from_corners(
+            area.closest_point(self.to_minimap(camera_position - half_window)),
+            area.closest_point(self.to_minimap(camera_position + half_window))
+        );
+
+        let corners = viewport.to_polygon().points().to_vec();
+        for index in 0..corners.len() {
+            let line = Line::new(to_pixel(corners[index]), to_pixel(corners[(index + 1) % corners.len()]));
+            draw_line(&mut pixels, line, VIEWPORT_WIDTH, theme.selection);
+        }
+
+        pixels
+    }
+}
+
+fn rgba(color: Color) -> [u8; 4] {
+    [
+        (color.r() * 255.0).round() as u8,
+        (color.g() * 255.0).round() as u8,
+        (color.b() * 255.0).round() as u8,
+        (color.a() * 255.0).round() as u8
+    ]
+}
+
+/// Converts a position in the minimap, relative to its center, into pixel coordinates
+/// starting at the top left corner
+fn to_pixel(position: Vec2) -> Vec2 {
+    Vec2::new(position.x() + MINIMAP_SIZE / 2.0, MINIMAP_SIZE / 2.0 - position.y())
+}
+
+/// Colors the pixels whose centers are closer to the line than half of the width
+fn draw_line(pixels: &mut [u8], line: Line, width: f32, color: Color) {
+    let size = MINIMAP_SIZE as usize;
+    let half_width = width / 2.0;
+    let min = line.point1.min(line.point2) - Vec2::new(half_width, half_width);
+    let max = line.point1.max(line.point2) + Vec2::new(half_width, half_width);
+
+    let columns = (min.x().floor().max(0.0) as usize)..(max.x().ceil().max(0.0) as usize).min(size);
+    let rows = (min.y().floor().max(0.0) as usize)..(max.y().ceil().max(0.0) as usize).min(size);
+
+    let color = rgba(color);
+    for row in rows {
+        for column in columns.clone() {
+            let center = Vec2::new(column as f32 + 0.5, row as f32 + 0.5);
+
+            if (line.closest_point(center) - center).length() <= half_width {
+                let index = (row * size + column) * 4;
+                pixels[index..index + 4].copy_from_slice(&color);
+            }
+        }
+    }
+}
+/// Node showing the minimap, it catches the clicks so they do not reach the world
+pub struct MinimapRoot;
+
+fn minimap_setup(
+    mut commands: Commands,
+    mut minimap: ResMut<Minimap>,
+    mut textures: ResMut<Assets<Texture>>,
+    mut materials: ResMut<Assets<ColorMaterial>>
+) {
+    let size = MINIMAP_SIZE as usize;
+    let texture = textures.add(Texture::new(
+        Vec2::new(MINIMAP_SIZE, MINIMAP_SIZE),
+        vec![0; size * size * 4],
+        TextureFormat::Rgba8UnormSrgb
+    ));
+    minimap.texture = Some(texture.clone());
+
+    commands
+    .spawn(ButtonComponents {
+        style: Style {
+            size: Size::new(Val::Px(MINIMAP_SIZE), Val::Px(MINIMAP_SIZE)),
+            position_type: PositionType::Absolute,
+            position: Rect {
+                right: Val::Px(MINIMAP_MARGIN),
+                top: Val::Px((crate::WINDOW_HEIGHT as f32 - MINIMAP_SIZE) / 2.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        material: materials.add(ColorMaterial::texture(texture)),
+        ..Default::default()
+    })
+    .with(MinimapRoot);
+}
+
+/// The minimap is drawn again with the colors of the new theme
+fn minimap_theme_system(
+    _theme: ChangedRes<Theme>,
+    mut minimap: ResMut<Minimap>
+) {
+    minimap.shown_camera = None;
+}
+
+/// Draws the minimap whenever the network or the camera changes
+fn minimap_draw_system(
+    view: Res<CameraView>,
+    theme: Res<Theme>,
+    mut minimap: ResMut<Minimap>,
+    mut textures: ResMut<Assets<Texture>>,
+    mut changed_query: Query<Changed<RoadSystem>>,
+    mut graph_query: Query<&RoadSystem>
+) {
+    for road_system in &mut changed_query.iter() {
+        minimap.bounds = Minimap::bounds_of(&road_system);
+        minimap.shown_camera = None;
+    }
+
+    let camera = (view.position, view.zoom);
+    if minimap.shown_camera == Some(camera) {
+        return;
+    }
+
+    minimap.shown_camera = Some(camera);
+
+    for road_system in &mut graph_query.iter() {
+        let pixels = minimap.draw(&road_system, view.position, view.zoom, &theme);
+
+        if let Some(texture) = minimap.texture.as_ref().and_then(|texture| textures.get_mut(texture)) {
+            texture.data = pixels;
+        }
+    }
+}
+
+/// Pressing the minimap moves the camera to the pressed position
+fn minimap_navigation_system(
+    app_state: Res<AppState>,
+    state: Res<input::MouseState>,
+    mut view: ResMut<CameraView>,
+    minimap: Res<Minimap>,
+    mut root_query: Query<With<MinimapRoot, (&Interaction, &Transform)>>
+) {
+    if *app_state != AppState::Editing {
+        return;
+    }
+
+    // the minimap stays clicked while the cursor is dragged out of it
+    for (interaction, transform) in &mut root_query.iter() {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+
+        let center = transform.translation();
+        let camera_position = minimap.to_world(state.mouse_position - Vec2::new(center.x(), center.y()));
+
+        if camera_position != view.position {
+            view.position = camera_position;
+        }
+    }
+}
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<Minimap>();
+        app.add_startup_system(minimap_setup.system());
+        app.add_system(minimap_navigation_system.system());
+        app.add_system(minimap_theme_system.system());
+        app.add_system(minimap_draw_system.system());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use super::*;
+    use crate::roadsystem::Street;
+
+    #[test]
+    fn bounds_contain_network_and_origin_view() {
+        let mut road_system = RoadSystem::new();
+        road_system.insert_street(Vec2::new(0.0, 0.0), Vec2::new(3000.0, 0.0), Street::default());
+
+        let minimap = Minimap { bounds: Minimap::bounds_of(&road_system), ..Default::default() };
+
+        assert_eq!(minimap.bounds.min.x(), -(crate::WINDOW_WIDTH as f32) / 2.0);
+        assert_eq!(minimap.bounds.max.x(), 3000.0);
+        assert_eq!(minimap.bounds.width(), minimap.bounds.height());
+
+        let position = Vec2::new(1200.0, -300.0);
+        let on_minimap = minimap.to_minimap(position);
+
+        assert!(on_minimap.x().abs() <= MINIMAP_SIZE / 2.0 && on_minimap.y().abs() <= MINIMAP_SIZE / 2.0);
+        assert!((minimap.to_world(on_minimap) - position).length() < 1e-2);
+        assert_eq!(minimap.to_minimap(minimap.bounds.max), Vec2::new(MINIMAP_SIZE, MINIMAP_SIZE) / 2.0);
+    }
+
+    #[test]
+    fn draw_streets_and_viewport() {
+        let mut road_system = RoadSystem::new();
+        road_system.insert_street(Vec2::new(0.0, 0.0), Vec2::new(3000.0, 0.0), Street::default());
+
+        let theme = Theme::default();
+        let minimap = Minimap { bounds: Minimap::bounds_of(&road_system), ..Default::default() };
+        let pixels = minimap.draw(&road_system, Vec2::zero(), 1.0, &theme);
+
+        let size = MINIMAP_SIZE as usize;
+        let pixel = |position: Vec2| {
+            let pixel = to_pixel(minimap.to_minimap(position));
+            let index = (pixel.y() as usize * size + pixel.x() as usize) * 4;
+            [pixels[index], pixels[index + 1], pixels[index + 2], pixels[index + 3]]
+        };
+
+        assert_eq!(pixels.len(), size * size * 4);
+        assert_eq!(pixel(Vec2::new(2000.0, 0.0)), rgba(theme.minimap_street));
+        assert_eq!(pixel(Vec2::new(2000.0, 1000.0)), rgba(theme.minimap_background));
+
+        // the window shows the area around the origin
+        let corner = Vec2::new(crate::WINDOW_WIDTH as f32, crate::WINDOW_HEIGHT as f32) / 2.0;
+        assert_eq!(pixel(corner - Vec2::new(1.0, 1.0)), rgba(theme.selection));
+        assert_eq!(pixel(corner / 2.0), rgba(theme.minimap_background));
+    }
+}
@@ -7,64 +7,429 @@ use petgraph::prelude::*;
 use petgraph::csr::DefaultIx;
 use petgraph::visit::IntoNodeReferences;
 use petgraph::visit::IntoEdgeReferences;
+use petgraph::visit::NodeIndexable;
+use petgraph::unionfind::UnionFind;
 
 use bevy::{
     prelude::*,
 };
 
-use bevy_prototype_lyon::prelude::*;
 use std::f32::consts::{FRAC_PI_6, PI};
+use std::collections::{ HashMap, HashSet };
 
 
 use std::fmt;
 
-use crate::math::line::{ Line, Parallel };
-use crate::math::polygon::Polygon;
+use crate::math::line::Line;
+use crate::math::operations::{ Distance, Inside, Intersects };
+use crate::math::offset::{ offset_polygon, offset_polyline, EndType, JoinType };
+use crate::math::polygon::{ Containment, Polygon };
+use crate::shapes::spawn_polygons;
+use crate::theme::ThemeMaterials;
 
-use rand::Rng;
+pub const INTERSECTION_SIZE: f32 = 40.0;
 
+/// Intersections closer to each other are merged
+const MERGE_DISTANCE: f32 = INTERSECTION_SIZE / 2.0;
 
-fn generate_random_color() -> Color {
-    let mut rng = rand::thread_rng();
+/// Maximal angle between two intersections of a roundabout
+const ROUNDABOUT_STEP: f32 = FRAC_PI_6;
 
-    Color::rgb(rng.gen::<f32>(), rng.gen::<f32>(), rng.gen::<f32>())
-}
+/// Maximal length of a street of a roundabout, so large rings stay round
+const ROUNDABOUT_SEGMENT: f32 = INTERSECTION_SIZE;
 
 struct GraphEntityIndex {
     entity: Entity
 }
 
+/// Marks the rendered outline of a street
+pub struct StreetOutline;
+
 pub struct RoadSystem {
-    graph: StableGraph::<RoadIntersection, ()>
+    graph: StableGraph::<RoadIntersection, Street>,
+
+    /// Changes whenever streets or intersections are removed, their indices may be reused
+    revision: usize
+}
+
+/// Class of a street which defines its width
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub enum RoadType {
+    Residential,
+    Avenue,
+    Highway
+}
+
+impl RoadType {
+    pub const ALL: [RoadType; 3] = [RoadType::Residential, RoadType::Avenue, RoadType::Highway];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RoadType::Residential => "Residential",
+            RoadType::Avenue => "Avenue",
+            RoadType::Highway => "Highway"
+        }
+    }
+
+    pub fn width(&self) -> f32 {
+        match self {
+            RoadType::Residential => 10.0,
+            RoadType::Avenue => 16.0,
+            RoadType::Highway => 24.0
+        }
+    }
+
+    /// Speed limit in km/h of new streets
+    pub fn speed_limit(&self) -> f32 {
+        match self {
+            RoadType::Residential => 30.0,
+            RoadType::Avenue => 50.0,
+            RoadType::Highway => 100.0
+        }
+    }
+
+    /// Number of lanes per direction of new streets
+    pub fn lanes(&self) -> usize {
+        match self {
+            RoadType::Residential => 1,
+            RoadType::Avenue => 2,
+            RoadType::Highway => 3
+        }
+    }
+
+    /// Returns the following road type, the last one is followed by the first one
+    pub fn next(&self) -> RoadType {
+        match self {
+            RoadType::Residential => RoadType::Avenue,
+            RoadType::Avenue => RoadType::Highway,
+            RoadType::Highway => RoadType::Residential
+        }
+    }
+
+    /// Returns the next larger road type, highways stay highways
+    pub fn upgraded(&self) -> RoadType {
+        match self {
+            RoadType::Residential => RoadType::Avenue,
+            _ => RoadType::Highway
+        }
+    }
+
+    /// Returns the next smaller road type, residential streets stay residential
+    pub fn downgraded(&self) -> RoadType {
+        match self {
+            RoadType::Highway => RoadType::Avenue,
+            _ => RoadType::Residential
+        }
+    }
+}
+
+impl Default for RoadType {
+    fn default() -> RoadType {
+        RoadType::Residential
+    }
+}
+
+/// Attributes of a connection between two intersections
+#[derive(Clone, Debug, PartialEq)]
+pub struct Street {
+    pub road_type: RoadType,
+
+    /// The street may only be used from its first to its second intersection
+    pub one_way: bool,
+
+    /// Empty for unnamed streets
+    pub name: String,
+
+    /// Number of lanes per direction, the road type gives the initial number
+    pub lanes: usize,
+
+    /// Speed limit in km/h, the road type gives the initial limit
+    pub speed_limit: f32
 }
 
+impl Default for Street {
+    fn default() -> Street {
+        Street::new(RoadType::default())
+    }
+}
+
+impl Street {
+    pub fn new(road_type: RoadType) -> Street {
+        Street {
+            road_type,
+            one_way: false,
+            name: String::new(),
+            lanes: road_type.lanes(),
+            speed_limit: road_type.speed_limit()
+        }
+    }
+
+    pub fn new_one_way(road_type: RoadType) -> Street {
+        Street { one_way: true, ..Street::new(road_type) }
+    }
+
+    /// Changes the class, the lanes and the speed limit are reset to the ones of the class
+    pub fn set_road_type(&mut self, road_type: RoadType) {
+        self.road_type = road_type;
+        self.lanes = road_type.lanes();
+        self.speed_limit = road_type.speed_limit();
+    }
+}
+
+/// Rule deciding who has the right of way at an intersection
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum TrafficControl {
+    Uncontrolled,
+
+    /// Traffic on the ring has the right of way
+    Roundabout,
+
+    /// Traffic lights alternate the right of way
+    TrafficLight
+}
+
+impl Default for TrafficControl {
+    fn default() -> TrafficControl {
+        TrafficControl::Uncontrolled
+    }
+}
+
+/// Part of the road system which can be picked
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub enum RoadElement {
+    Intersection(NodeIndex<DefaultIx>),
+    Street(EdgeIndex)
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct RoadIntersection {
     pub position: Vec2,
+    pub control: TrafficControl
 }
 
 
 impl RoadIntersection {
     pub fn new(position: Vec2) -> RoadIntersection {
-        RoadIntersection { position : position }
+        RoadIntersection { position : position, control: TrafficControl::default() }
+    }
+}
+
+/// Part of a road system which can be inserted anywhere. Positions are relative to the
+/// center of the template and streets refer to the index of their intersections.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct RoadTemplate {
+    pub intersections: Vec<Vec2>,
+    pub streets: Vec<(usize, usize, RoadType)>
+}
+
+impl RoadTemplate {
+    /// Reads a template with one element per line:
+    ///
+    /// ```text
+    /// intersection <x> <y>
+    /// street <first intersection> <second intersection> <residential|avenue|highway>
+    /// ```
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn parse(text: &str) -> Result<RoadTemplate, String> {
+        let mut template = RoadTemplate::default();
+
+        for (number, line) in text.lines().enumerate() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let error = || format!("invalid line {}: {}", number + 1, line);
+
+            match words.as_slice() {
+                [] => (),
+                [comment, ..] if comment.starts_with('#') => (),
+                ["intersection", x, y] => {
+                    let x = x.parse::<f32>().map_err(|_| error())?;
+                    let y = y.parse::<f32>().map_err(|_| error())?;
+
+                    template.intersections.push(Vec2::new(x, y));
+                }
+                ["street", first, second, road_type] => {
+                    let first = first.parse::<usize>().map_err(|_| error())?;
+                    let second = second.parse::<usize>().map_err(|_| error())?;
+                    let road_type = parse_road_type(road_type).ok_or_else(error)?;
+
+                    if first >= template.intersections.len() || second >= template.intersections.len() {
+                        return Err(format!("unknown intersection in line {}: {}", number + 1, line));
+                    }
+
+                    template.streets.push((first, second, road_type));
+                }
+                _ => return Err(error())
+            }
+        }
+
+        Ok(template)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+
+        for intersection in &self.intersections {
+            text += &format!("intersection {} {}\n", intersection.x(), intersection.y());
+        }
+
+        for (first, second, road_type) in &self.streets {
+            text += &format!("street {} {} {}\n", first, second, road_type_name(*road_type));
+        }
+
+        text
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intersections.is_empty()
+    }
+
+    /// Returns the position of the intersection after rotating the template around its center
+    /// and moving it to the given position
+    pub fn place(&self, intersection: usize, position: Vec2, rotation: f32) -> Vec2 {
+        let (sin, cos) = rotation.sin_cos();
+        let point = self.intersections[intersection];
+
+        position + Vec2::new(point.x() * cos - point.y() * sin, point.x() * sin + point.y() * cos)
+    }
+}
+
+fn parse_road_type(name: &str) -> Option<RoadType> {
+    match name {
+        "residential" => Some(RoadType::Residential),
+        "avenue" => Some(RoadType::Avenue),
+        "highway" => Some(RoadType::Highway),
+        _ => None
+    }
+}
+
+fn road_type_name(road_type: RoadType) -> &'static str {
+    match road_type {
+        RoadType::Residential => "residential",
+        RoadType::Avenue => "avenue",
+        RoadType::Highway => "highway"
+    }
+}
+
+fn parse_control(name: &str) -> Option<TrafficControl> {
+    match name {
+        "uncontrolled" => Some(TrafficControl::Uncontrolled),
+        "roundabout" => Some(TrafficControl::Roundabout),
+        "traffic_light" => Some(TrafficControl::TrafficLight),
+        _ => None
+    }
+}
+
+fn control_name(control: TrafficControl) -> &'static str {
+    match control {
+        TrafficControl::Uncontrolled => "uncontrolled",
+        TrafficControl::Roundabout => "roundabout",
+        TrafficControl::TrafficLight => "traffic_light"
     }
 }
 
 impl RoadSystem {
     pub fn new() -> RoadSystem {
         RoadSystem { 
-            graph: StableGraph::<RoadIntersection, ()>::new()
+            graph: StableGraph::<RoadIntersection, Street>::new(),
+            revision: 0
         }
     }
 
+    /// Returns a number which changes whenever indices of the road system become invalid
+    pub fn revision(&self) -> usize {
+        self.revision
+    }
+
+    fn remove_edge(&mut self, street: EdgeIndex) -> Option<Street> {
+        self.revision += 1;
+        self.graph.remove_edge(street)
+    }
+
+    fn remove_node(&mut self, intersection: NodeIndex<DefaultIx>) {
+        self.revision += 1;
+        self.graph.remove_node(intersection);
+    }
+
     pub fn insert_intersection(&mut self, intersection: RoadIntersection) -> NodeIndex<DefaultIx> {
         self.graph.add_node(intersection)
   
     }
 
+    fn intersection_near(&self, position: Vec2) -> Option<NodeIndex<DefaultIx>> {
+        self.graph
+            .node_indices()
+            .find(|node| (self.graph[*node].position - position).length() < MERGE_DISTANCE)
+    }
+
+    /// Returns the intersection at the position. Positions close to a street split it,
+    /// otherwise a new intersection is inserted if there is none.
+    pub fn find_or_insert_intersection(&mut self, position: Vec2) -> NodeIndex<DefaultIx> {
+        if let Some(existing) = self.intersection_near(position) {
+            return existing;
+        }
+
+        if let Some(street) = self.nearest_street(position, MERGE_DISTANCE) {
+            let position = self.street_line(street).unwrap().closest_point(position);
+
+            return match self.intersection_near(position) {
+                Some(existing) => existing,
+                None => self.split_street(street, position)
+            };
+        }
+
+        self.insert_intersection(RoadIntersection::new(position))
+    }
+
+    /// Inserts an intersection at the position which splits the street into two streets
+    /// with the same attributes
+    pub fn split_street(&mut self, street: EdgeIndex, position: Vec2) -> NodeIndex<DefaultIx> {
+        let intersection = self.insert_intersection(RoadIntersection::new(position));
+        self.split_street_at(street, intersection);
+
+        intersection
+    }
+
+    fn split_street_at(&mut self, street: EdgeIndex, intersection: NodeIndex<DefaultIx>) {
+        if let Some((start, end)) = self.graph.edge_endpoints(street) {
+            let street = self.remove_edge(street).unwrap();
+
+            self.graph.add_edge(start, intersection, street.clone());
+            self.graph.add_edge(intersection, end, street);
+        }
+    }
+
     /// Removes an intersection (node) of the road system. 
     /// Warning: Removes all connected roads as well.
-    pub fn remove_intersection(&self, intersection: &RoadIntersection) {
-        todo!();
+    pub fn remove_intersection(&mut self, intersection: NodeIndex<DefaultIx>) {
+        let neighbors: Vec<NodeIndex<DefaultIx>> = self.incident_edges(intersection).iter().map(|(_, other)| *other).collect();
+
+        self.remove_node(intersection);
+        self.remove_isolated(&neighbors);
+    }
+
+    /// Removes a single connection between two intersections. Intersections without any
+    /// further connection are removed as well.
+    pub fn remove_street(&mut self, street: EdgeIndex) {
+        if let Some((start, end)) = self.graph.edge_endpoints(street) {
+            self.remove_edge(street);
+            self.remove_isolated(&[start, end]);
+        }
+    }
+
+    fn remove_isolated(&mut self, intersections: &[NodeIndex<DefaultIx>]) {
+        for intersection in intersections {
+            if self.graph.contains_node(*intersection) && self.incident_edges(*intersection).is_empty() {
+                self.remove_node(*intersection);
+            }
+        }
+    }
+
+    /// Returns true if the element is still part of the road system
+    pub fn contains(&self, element: RoadElement) -> bool {
+        match element {
+            RoadElement::Intersection(intersection) => self.graph.contains_node(intersection),
+            RoadElement::Street(street) => self.graph.edge_weight(street).is_some()
+        }
     }
 
     pub fn point_intersect_connection(&self, point: Vec2) -> Option<Line> {
@@ -90,11 +455,17 @@ impl RoadSystem {
         None
     }
 
+    /// Returns all edges crossing the connection between the two intersections. Edges sharing
+    /// an intersection with the connection only touch it and are ignored.
     fn find_intersections(&self, intersection1: NodeIndex, intersection2: NodeIndex) -> Vec<(EdgeIndex, Vec2)> {
         let mut intersections = Vec::new();
 
         for edge_index in self.graph.edge_indices() {
             if let Some((start, end)) = self.graph.edge_endpoints(edge_index) {
+                if start == intersection1 || start == intersection2 || end == intersection1 || end == intersection2 {
+                    continue;
+                }
+
                 if let Some(intersection) = self.intersects(intersection1, intersection2, start, end) {                
                     intersections.push((edge_index, intersection));
                 }                
@@ -104,46 +475,682 @@ impl RoadSystem {
         intersections
     }
 
-    /// Creates a street between the two intersections
-    pub fn connect_intersections(&mut self, intersection1: NodeIndex<DefaultIx>, intersection2: NodeIndex<DefaultIx>) { 
+    /// Creates a street between the two positions, reusing intersections and streets close
+    /// to them. Returns the edges of the new street.
+    pub fn insert_street(&mut self, start: Vec2, end: Vec2, street: Street) -> Vec<EdgeIndex> {
+        let intersection1 = self.find_or_insert_intersection(start);
+        let intersection2 = self.find_or_insert_intersection(end);
+
+        if intersection1 == intersection2 {
+            return Vec::new();
+        }
+
+        self.connect_intersections(intersection1, intersection2, street)
+    }
+
+    /// Creates a street between the two intersections. Crossed streets are split at the
+    /// crossing. Crossings close to an existing intersection reuse it. Returns the edges
+    /// of the new street in order from the first to the second intersection.
+    pub fn connect_intersections(&mut self, intersection1: NodeIndex<DefaultIx>, intersection2: NodeIndex<DefaultIx>, street: Street) -> Vec<EdgeIndex> { 
         
         // Find all edges intersecting the new one
         let mut intersections = self.find_intersections(intersection1, intersection2);
         
         // Sort them by distance to start to avoid wrong edges
         {
-            let intersection1 = self.graph.node_weight(intersection1).unwrap();        
+            let start = self.graph.node_weight(intersection1).unwrap().position;        
             intersections.sort_by(|a, b| {
-                let a = (a.1 - intersection1.position).length() as i32;
-                let b = (b.1 - intersection1.position).length() as i32;
-                a.cmp(&b)
+                (a.1 - start).length().partial_cmp(&(b.1 - start).length()).unwrap()
             });
         }
         
+        let mut streets = Vec::new();
+        let mut current = intersection1;
+        for (edge_index, position) in intersections {
+            // a previous crossing could have split this edge already
+            let (old_start, old_end) = match self.graph.edge_endpoints(edge_index) {
+                Some(endpoints) => endpoints,
+                None => continue
+            };
+
+            let next = match [intersection1, intersection2, old_start, old_end]
+                .iter()
+                .find(|node| (self.graph[**node].position - position).length() < MERGE_DISTANCE)
+            {
+                Some(node) => *node,
+                None => self.insert_intersection(RoadIntersection::new(position))
+            };
+
+            // Split each road into two which are intersected by the new road
+            if next != old_start && next != old_end {
+                self.split_street_at(edge_index, next);
+            }
+
+            if next != current {
+                streets.push(self.add_street(current, next, street.clone()));
+                current = next;
+            }
+        }
+
+        if current != intersection2 {
+            streets.push(self.add_street(current, intersection2, street));
+        }
+
+        streets
+    }
+
+    /// Adds a connection unless the intersections are already connected
+    fn add_street(&mut self, intersection1: NodeIndex<DefaultIx>, intersection2: NodeIndex<DefaultIx>, street: Street) -> EdgeIndex {
+        match self.graph.find_edge_undirected(intersection1, intersection2) {
+            Some((existing, _)) => existing,
+            None => self.graph.add_edge(intersection1, intersection2, street)
+        }
+    }
+
+    pub fn intersection_position(&self, intersection: NodeIndex<DefaultIx>) -> Option<Vec2> {
+        self.graph.node_weight(intersection).map(|intersection| intersection.position)
+    }
+
+    /// Returns the straight line between the two intersections of a street
+    pub fn street_line(&self, street: EdgeIndex) -> Option<Line> {
+        let (start, end) = self.graph.edge_endpoints(street)?;
+
+        Some(Line::new(self.graph[start].position, self.graph[end].position))
+    }
+
+    /// Returns the first and the second intersection of a street
+    pub fn street_intersections(&self, street: EdgeIndex) -> Option<(NodeIndex<DefaultIx>, NodeIndex<DefaultIx>)> {
+        self.graph.edge_endpoints(street)
+    }
+
+    /// Returns the intersection or street at the given position. Intersections are preferred
+    /// over the streets connected to them.
+    pub fn pick(&self, position: Vec2) -> Option<RoadElement> {
+        let intersection = self.graph
+            .node_indices()
+            .map(|node| (node, (self.graph[node].position - position).length()))
+            .filter(|(_, distance)| *distance <= INTERSECTION_SIZE / 2.0)
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+        if let Some((node, _)) = intersection {
+            return Some(RoadElement::Intersection(node));
+        }
+
+        self.graph
+            .edge_indices()
+            .filter_map(|edge| self.street_line(edge).map(|line| (edge, line.distance(&position))))
+            .filter(|(edge, distance)| *distance <= self.graph[*edge].road_type.width() / 2.0)
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+            .map(|(edge, _)| RoadElement::Street(edge))
+    }
+
+    /// Returns the street closest to the position if it is not farther away than the distance
+    pub fn nearest_street(&self, position: Vec2, max_distance: f32) -> Option<EdgeIndex> {
+        self.graph
+            .edge_indices()
+            .filter_map(|edge| self.street_line(edge).map(|line| (edge, line.distance(&position))))
+            .filter(|(_, distance)| *distance <= max_distance)
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+            .map(|(edge, _)| edge)
+    }
+
+    pub fn streets(&self) -> Vec<EdgeIndex> {
+        self.graph.edge_indices().collect()
+    }
+
+    pub fn intersections(&self) -> Vec<NodeIndex<DefaultIx>> {
+        self.graph.node_indices().collect()
+    }
+
+    /// Returns the number of parts of the network which are not connected by any street
+    pub fn connected_components(&self) -> usize {
+        let mut components = UnionFind::new(self.graph.node_bound());
+
+        for edge in self.graph.edge_references() {
+            components.union(edge.source().index(), edge.target().index());
+        }
+
+        self.graph
+            .node_indices()
+            .map(|node| components.find(node.index()))
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// Returns all intersections and streets of the part of the network containing the element
+    pub fn connected_elements(&self, element: RoadElement) -> Vec<RoadElement> {
+        let start = match element {
+            RoadElement::Intersection(intersection) if self.graph.contains_node(intersection) => intersection,
+            RoadElement::Street(street) => match self.graph.edge_endpoints(street) {
+                Some((start, _)) => start,
+                None => return Vec::new()
+            },
+            _ => return Vec::new()
+        };
+
+        let mut intersections = HashSet::new();
+        let mut streets = HashSet::new();
+        let mut open = vec![start];
+
+        while let Some(intersection) = open.pop() {
+            if !intersections.insert(intersection) {
+                continue;
+            }
+
+            for (street, other) in self.incident_edges(intersection) {
+                streets.insert(street);
+                open.push(other);
+            }
+        }
+
+        intersections
+            .into_iter()
+            .map(RoadElement::Intersection)
+            .chain(streets.into_iter().map(RoadElement::Street))
+            .collect()
+    }
+
+    pub fn street(&self, street: EdgeIndex) -> Option<&Street> {
+        self.graph.edge_weight(street)
+    }
+
+    pub fn intersection(&self, intersection: NodeIndex<DefaultIx>) -> Option<&RoadIntersection> {
+        self.graph.node_weight(intersection)
+    }
+
+    /// Returns the number of streets meeting at the intersection
+    pub fn degree(&self, intersection: NodeIndex<DefaultIx>) -> usize {
+        self.incident_edges(intersection).len()
+    }
+
+    pub fn road_type(&self, street: EdgeIndex) -> Option<RoadType> {
+        self.graph.edge_weight(street).map(|street| street.road_type)
+    }
+
+    /// Replaces all attributes of the street
+    pub fn set_street(&mut self, street: EdgeIndex, attributes: Street) {
+        if let Some(street) = self.graph.edge_weight_mut(street) {
+            *street = attributes;
+        }
+    }
+
+    /// Changes the traffic control of the intersection. Its position is changed with
+    /// `move_intersection` which updates the streets around it.
+    pub fn set_control(&mut self, intersection: NodeIndex<DefaultIx>, control: TrafficControl) {
+        if let Some(intersection) = self.graph.node_weight_mut(intersection) {
+            intersection.control = control;
+        }
+    }
+
+    /// Swaps the intersections of the street, so a one-way street leads the other way. The
+    /// street keeps its index.
+    pub fn reverse_street(&mut self, street: EdgeIndex) {
+        if let Some((start, end)) = self.graph.edge_endpoints(street) {
+            let attributes = self.graph.remove_edge(street).unwrap();
+
+            // the stable graph reuses the index which was freed last
+            self.graph.add_edge(end, start, attributes);
+        }
+    }
+
+    /// Changes the class of a street without touching its intersections
+    pub fn set_road_type(&mut self, street: EdgeIndex, road_type: RoadType) {
+        if let Some(street) = self.graph.edge_weight_mut(street) {
+            street.set_road_type(road_type);
+        }
+    }
+
+    /// Returns the street and all streets connected to it through intersections without any
+    /// further connection. The stretch ends at junctions and dead ends.
+    pub fn street_stretch(&self, street: EdgeIndex) -> Vec<EdgeIndex> {
+        let (start, end) = match self.graph.edge_endpoints(street) {
+            Some(endpoints) => endpoints,
+            None => return Vec::new()
+        };
+
+        let mut stretch = vec![street];
+        for intersection in &[start, end] {
+            let mut previous = street;
+            let mut current = *intersection;
+
+            loop {
+                let edges = self.incident_edges(current);
+                if edges.len() != 2 {
+                    break;
+                }
+
+                let (next, other) = edges.into_iter().find(|(edge, _)| *edge != previous).unwrap();
+
+                // closed loops end where they started
+                if stretch.contains(&next) {
+                    break;
+                }
+
+                stretch.push(next);
+                previous = next;
+                current = other;
+            }
+        }
+
+        stretch
+    }
+
+    /// Returns the streets which can be used to leave the intersection together with the
+    /// intersection they lead to. One-way streets are only returned in their direction.
+    pub fn drivable_streets(&self, intersection: NodeIndex<DefaultIx>) -> Vec<(EdgeIndex, NodeIndex<DefaultIx>)> {
+        self.graph.edges_directed(intersection, Direction::Outgoing)
+            .map(|edge| (edge.id(), edge.target()))
+            .chain(self.graph.edges_directed(intersection, Direction::Incoming)
+                .filter(|edge| !edge.weight().one_way)
+                .map(|edge| (edge.id(), edge.source())))
+            .collect()
+    }
+
+    pub fn nearest_intersection(&self, position: Vec2, max_distance: f32) -> Option<NodeIndex<DefaultIx>> {
+        self.graph
+            .node_indices()
+            .map(|node| (node, (self.graph[node].position - position).length()))
+            .filter(|(_, distance)| *distance <= max_distance)
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+            .map(|(node, _)| node)
+    }
+
+    /// Returns the areas enclosed by streets as counter-clockwise polygons. Streets ending
+    /// inside a block are part of its boundary but do not split it.
+    pub fn blocks(&self) -> Vec<Polygon> {
+        let mut visited = HashSet::new();
+        let mut blocks = Vec::new();
+
+        for edge_index in self.graph.edge_indices() {
+            let (start, end) = self.graph.edge_endpoints(edge_index).unwrap();
+
+            // walk along every street once in each direction with the block on the left
+            for (from, to) in &[(start, end), (end, start)] {
+                let (mut from, mut to) = (*from, *to);
+                let mut points = Vec::new();
+
+                while visited.insert((from, to)) {
+                    points.push(self.graph[from].position);
+
+                    let next = self.next_clockwise(to, from);
+                    from = to;
+                    to = next;
+                }
+
+                let block = Polygon::new(points);
+
+                // the area around the whole network is clockwise
+                if block.signed_area() > 0.0 {
+                    blocks.push(block);
+                }
+            }
+        }
+
+        blocks
+    }
+
+    /// Returns the smallest block containing the position
+    pub fn block_at(&self, position: Vec2) -> Option<Polygon> {
+        self.blocks()
+            .into_iter()
+            .filter(|block| block.classify(position) == Containment::Inside)
+            .min_by(|a, b| a.area().partial_cmp(&b.area()).unwrap())
+    }
+
+    /// Returns the neighbor of the intersection which follows the given one in clockwise order
+    fn next_clockwise(&self, intersection: NodeIndex<DefaultIx>, from: NodeIndex<DefaultIx>) -> NodeIndex<DefaultIx> {
+        let position = self.graph[intersection].position;
+        let angle_to = |node: NodeIndex<DefaultIx>| {
+            let direction = self.graph[node].position - position;
+            direction.y().atan2(direction.x())
+        };
+
+        let back = angle_to(from);
+        let clockwise = |node: NodeIndex<DefaultIx>| (back - angle_to(node)).rem_euclid(2.0 * PI);
+
+        self.incident_edges(intersection)
+            .iter()
+            .map(|(_, other)| *other)
+            .filter(|other| *other != from)
+            .min_by(|a, b| clockwise(*a).partial_cmp(&clockwise(*b)).unwrap())
+            .unwrap_or(from)
+    }
+
+    /// Returns the intersection and streets inside the area. Streets only crossing the border
+    /// of the area are included if `crossing` is set.
+    pub fn elements_in<T>(&self, area: &T, crossing: bool) -> Vec<RoadElement> 
+    where
+        Vec2: Inside<T>,
+        Line: Inside<T> + Intersects<T>
+    {
+        let intersections = self.graph
+            .node_indices()
+            .filter(|node| self.graph[*node].position.inside(area))
+            .map(RoadElement::Intersection);
+
+        let streets = self.graph
+            .edge_indices()
+            .filter(|edge| {
+                let line = self.street_line(*edge).unwrap();
+                line.inside(area) || (crossing && line.intersects(area))
+            })
+            .map(RoadElement::Street);
+
+        intersections.chain(streets).collect()
+    }
+
+    /// Returns the intersections of the elements including the ends of streets
+    pub fn element_intersections(&self, elements: &[RoadElement]) -> Vec<NodeIndex<DefaultIx>> {
+        let mut intersections = Vec::new();
+        for element in elements {
+            match element {
+                RoadElement::Intersection(intersection) => intersections.push(*intersection),
+                RoadElement::Street(street) => {
+                    if let Some((start, end)) = self.graph.edge_endpoints(*street) {
+                        intersections.push(start);
+                        intersections.push(end);
+                    }
+                }
+            }
+        }
+
+        intersections.sort();
+        intersections.dedup();
+        intersections
+    }
+
+    /// Copies the elements into a template. Streets between two selected intersections are
+    /// copied as well.
+    pub fn copy_elements(&self, elements: &[RoadElement]) -> RoadTemplate {
+        let intersections = self.element_intersections(elements);
         if intersections.is_empty() {
-            self.graph.add_edge(intersection1, intersection2, ());
+            return RoadTemplate::default();
         }
-        
-        let mut current = intersection2;
-        while let Some(current_intersection) = intersections.pop() {
-            let next = self.insert_intersection(RoadIntersection::new(current_intersection.1));
 
-            self.graph.add_edge(current, next, ());
+        let center = intersections
+            .iter()
+            .fold(Vec2::zero(), |sum, intersection| sum + self.graph[*intersection].position) / intersections.len() as f32;
 
-            
+        let selected: HashSet<NodeIndex<DefaultIx>> = elements
+            .iter()
+            .filter_map(|element| match element {
+                RoadElement::Intersection(intersection) => Some(*intersection),
+                _ => None
+            })
+            .collect();
 
-            // Split each road into two which are intersected by the new road
-            if let Some((old_start, old_end)) = self.graph.edge_endpoints(current_intersection.0) {
-                self.graph.add_edge(old_start, next, ());
-                self.graph.add_edge(next, old_end, ());
+        let streets = self.graph
+            .edge_indices()
+            .filter(|edge| {
+                let (start, end) = self.graph.edge_endpoints(*edge).unwrap();
+                elements.contains(&RoadElement::Street(*edge)) || (selected.contains(&start) && selected.contains(&end))
+            })
+            .map(|edge| {
+                let (start, end) = self.graph.edge_endpoints(edge).unwrap();
+                let index = |node| intersections.iter().position(|other| *other == node).unwrap();
+
+                (index(start), index(end), self.graph[edge].road_type)
+            })
+            .collect();
+
+        RoadTemplate {
+            intersections: intersections.iter().map(|intersection| self.graph[*intersection].position - center).collect(),
+            streets
+        }
+    }
+
+    /// Inserts the template rotated around its center at the position. Intersections of the
+    /// template are merged with existing ones nearby and crossed streets are split. Returns
+    /// the inserted elements.
+    pub fn paste(&mut self, template: &RoadTemplate, position: Vec2, rotation: f32) -> Vec<RoadElement> {
+        let intersections: Vec<NodeIndex<DefaultIx>> = (0..template.intersections.len())
+            .map(|index| self.find_or_insert_intersection(template.place(index, position, rotation)))
+            .collect();
+
+        let mut elements: Vec<RoadElement> = intersections.iter().map(|intersection| RoadElement::Intersection(*intersection)).collect();
+        for (first, second, road_type) in &template.streets {
+            let (start, end) = (intersections[*first], intersections[*second]);
+            if start == end || self.graph.find_edge_undirected(start, end).is_some() {
+                continue;
+            }
+
+            // crossings add intersections which are part of the template as well
+            for street in self.connect_intersections(start, end, Street::new(*road_type)) {
+                let (start, end) = self.graph.edge_endpoints(street).unwrap();
+
+                elements.push(RoadElement::Intersection(start));
+                elements.push(RoadElement::Intersection(end));
+                elements.push(RoadElement::Street(street));
+            }
+        }
+
+        // later streets may have split earlier ones and intersections may have been merged
+        let mut unique = HashSet::new();
+        elements.retain(|element| self.contains(*element) && unique.insert(*element));
+        elements
+    }
+
+    /// Writes all intersections and streets with all of their attributes, one per line:
+    ///
+    /// ```text
+    /// intersection <x> <y> <uncontrolled|roundabout|traffic_light>
+    /// street <first intersection> <second intersection> <road type> <one-way> <lanes> <speed limit> <name>
+    /// ```
+    ///
+    /// Streets refer to the intersections in the order they are written.
+    pub fn to_city_text(&self) -> String {
+        let mut text = String::new();
+        let mut numbers = HashMap::new();
+
+        for (number, intersection) in self.graph.node_indices().enumerate() {
+            let attributes = &self.graph[intersection];
+            numbers.insert(intersection, number);
+
+            text += &format!(
+                "intersection {} {} {}\n",
+                attributes.position.x(), attributes.position.y(), control_name(attributes.control)
+            );
+        }
+
+        for street in self.graph.edge_indices() {
+            let (start, end) = self.graph.edge_endpoints(street).unwrap();
+            let attributes = &self.graph[street];
+
+            text += &format!(
+                "street {} {} {} {} {} {} {}\n",
+                numbers[&start], numbers[&end], road_type_name(attributes.road_type), attributes.one_way,
+                attributes.lanes, attributes.speed_limit, attributes.name
+            );
+        }
+
+        text
+    }
+
+    /// Reads a road system written with `to_city_text`. The intersections and streets are
+    /// added as they are, nothing is merged or split.
+    pub fn parse_city(text: &str) -> Result<RoadSystem, String> {
+        let mut road_system = RoadSystem::new();
+        let mut intersections = Vec::new();
+
+        for (number, line) in text.lines().enumerate() {
+            // the name is the rest of the line and may contain spaces
+            let words: Vec<&str> = line.splitn(8, ' ').collect();
+            let error = || format!("invalid line {}: {}", number + 1, line);
+
+            match words.as_slice() {
+                [""] => (),
+                ["intersection", x, y, control] => {
+                    let x = x.parse::<f32>().map_err(|_| error())?;
+                    let y = y.parse::<f32>().map_err(|_| error())?;
+                    let control = parse_control(control).ok_or_else(error)?;
+
+                    intersections.push(road_system.insert_intersection(RoadIntersection { position: Vec2::new(x, y), control }));
+                }
+                ["street", first, second, road_type, one_way, lanes, speed_limit, name] => {
+                    let intersection = |index: &str| index
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|index| intersections.get(index).cloned())
+                        .ok_or_else(|| format!("unknown intersection in line {}: {}", number + 1, line));
+
+                    let start = intersection(first)?;
+                    let end = intersection(second)?;
+                    let street = Street {
+                        road_type: parse_road_type(road_type).ok_or_else(error)?,
+                        one_way: one_way.parse::<bool>().map_err(|_| error())?,
+                        name: name.to_string(),
+                        lanes: lanes.parse::<usize>().map_err(|_| error())?,
+                        speed_limit: speed_limit.parse::<f32>().map_err(|_| error())?
+                    };
+
+                    road_system.graph.add_edge(start, end, street);
+                }
+                _ => return Err(error())
+            }
+        }
+
+        Ok(road_system)
+    }
+
+    /// Moves the intersection without looking for new crossings. Used while an intersection
+    /// is dragged around.
+    pub fn set_intersection_position(&mut self, intersection: NodeIndex<DefaultIx>, position: Vec2) {
+        if let Some(intersection) = self.graph.node_weight_mut(intersection) {
+            intersection.position = position;
+        }
+    }
+
+    /// Moves the intersection and updates the road network around it. Dropping it onto
+    /// another intersection merges both and connected streets are split where they cross
+    /// other streets. Streets without new crossings keep their index. Returns the
+    /// intersection at the new position.
+    pub fn move_intersection(&mut self, intersection: NodeIndex<DefaultIx>, position: Vec2) -> NodeIndex<DefaultIx> {
+        self.set_intersection_position(intersection, position);
+
+        let target = self.graph
+            .node_indices()
+            .filter(|node| *node != intersection)
+            .find(|node| (self.graph[*node].position - position).length() < MERGE_DISTANCE);
+
+        let intersection = match target {
+            Some(target) => {
+                self.merge_intersections(intersection, target);
+                target
+            }
+            None => intersection
+        };
+
+        for (edge_index, _) in self.incident_edges(intersection) {
+            if let Some((start, end)) = self.graph.edge_endpoints(edge_index) {
+                if self.find_intersections(start, end).is_empty() {
+                    continue;
+                }
+
+                let street = self.remove_edge(edge_index).unwrap();
+                self.connect_intersections(start, end, street);
+            }
+        }
+
+        intersection
+    }
+
+    /// Moves all streets of an intersection to another one and removes it afterwards
+    fn merge_intersections(&mut self, from: NodeIndex<DefaultIx>, into: NodeIndex<DefaultIx>) {
+        for (edge_index, other) in self.incident_edges(from) {
+            if other == into || self.graph.find_edge_undirected(into, other).is_some() {
+                continue;
+            }
 
-                self.graph.remove_edge(current_intersection.0);            
+            let (start, _) = self.graph.edge_endpoints(edge_index).unwrap();
+            let street = self.graph[edge_index].clone();
+            if start == from {
+                self.graph.add_edge(into, other, street);
+            } else {
+                self.graph.add_edge(other, into, street);
             }
+        }
+
+        self.remove_node(from);
+    }
+
+    /// Replaces the intersection at the position, if there is one, with a roundabout. The ring
+    /// approximates a circle with one-way streets in counter clockwise direction and all streets
+    /// of the replaced intersection are reconnected radially to it. Streets ending inside the
+    /// ring are removed. Returns the intersections of the ring.
+    pub fn insert_roundabout(&mut self, position: Vec2, radius: f32) -> Vec<NodeIndex<DefaultIx>> {
+        let mut center = position;
+        let mut connections = Vec::new();
+
+        if let Some(intersection) = self.intersection_near(position) {
+            center = self.graph[intersection].position;
+
+            for (edge_index, other) in self.incident_edges(intersection) {
+                let (start, _) = self.graph.edge_endpoints(edge_index).unwrap();
+                connections.push((other, self.graph[edge_index].clone(), start == intersection));
+            }
+
+            self.remove_node(intersection);
+        }
+
+        let (connections, inside): (Vec<_>, Vec<_>) = connections
+            .into_iter()
+            .partition(|(other, _, _)| (self.graph[*other].position - center).length() > radius);
+
+        let angle_to = |position: Vec2| (position - center).y().atan2((position - center).x());
+        let angles: Vec<f32> = connections.iter().map(|(other, _, _)| angle_to(self.graph[*other].position)).collect();
+        let ring_angles = roundabout_angles(&angles, radius);
+
+        let road_type = connections
+            .iter()
+            .map(|(_, street, _)| street.road_type)
+            .max_by(|a, b| a.width().partial_cmp(&b.width()).unwrap())
+            .unwrap_or_default();
+
+        let ring: Vec<NodeIndex<DefaultIx>> = ring_angles
+            .iter()
+            .map(|angle| self.insert_intersection(RoadIntersection {
+                position: center + Vec2::new(angle.cos(), angle.sin()) * radius,
+                control: TrafficControl::Roundabout
+            }))
+            .collect();
+
+        let mut ring_streets = Vec::new();
+        for index in 0..ring.len() {
+            ring_streets.extend(self.connect_intersections(ring[index], ring[(index + 1) % ring.len()], Street::new_one_way(road_type)));
+        }
+
+        // streets crossing the ring split it with further intersections. They are collected
+        // before the radial streets are connected which can remove and reuse street indices.
+        let intersections: Vec<NodeIndex<DefaultIx>> = ring_streets
+            .iter()
+            .filter_map(|street| self.graph.edge_endpoints(*street))
+            .map(|(start, _)| start)
+            .collect();
+
+        for intersection in &intersections {
+            self.graph[*intersection].control = TrafficControl::Roundabout;
+        }
 
-            current = next;
+        for ((other, street, outgoing), angle) in connections.into_iter().zip(angles) {
+            let closest = ring_angles
+                .iter()
+                .map(|ring_angle| (ring_angle - angle).rem_euclid(2.0 * PI))
+                .map(|difference| difference.min(2.0 * PI - difference))
+                .enumerate()
+                .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+                .unwrap().0;
+
+            if outgoing {
+                self.connect_intersections(ring[closest], other, street);
+            } else {
+                self.connect_intersections(other, ring[closest], street);
+            }
         }
 
-        self.graph.add_edge(current, intersection1, ());
+        let inside: Vec<NodeIndex<DefaultIx>> = inside.iter().map(|(other, _, _)| *other).collect();
+        self.remove_isolated(&inside);
+
+        intersections
     }
 
     /// Removes a street between the two intersections
@@ -154,62 +1161,112 @@ impl RoadSystem {
         todo!();
     }
 
-    pub fn update(&self, commands: &mut Commands, materials: &mut ResMut<Assets<ColorMaterial>>, mut meshes: &mut ResMut<Assets<Mesh>>) {
+    pub fn update(&self, commands: &mut Commands, materials: &ThemeMaterials, mut meshes: &mut ResMut<Assets<Mesh>>) {
         // build the intersections
         for (_, node) in self.graph.node_references() {
+            // small intersections keep the ring of a roundabout visible
+            let size = match node.control {
+                TrafficControl::Uncontrolled | TrafficControl::TrafficLight => 1.0,
+                TrafficControl::Roundabout => 0.5
+            };
+
             commands
             .spawn(SpriteComponents {
-                material: materials.add(Color::rgb(0.0, 0.0, 1.0).into()),            
+                material: materials.intersection.clone(),
                 transform: Transform::from_translation(Vec3::new(node.position.x(), node.position.y(), 2.0)),            
-                sprite: Sprite::new(Vec2::new(40.0, 40.0)),
+                sprite: Sprite::new(Vec2::new(INTERSECTION_SIZE, INTERSECTION_SIZE) * size),
                 ..Default::default()
             })
             .with(RoadIntersection{
-                position: node.position
+                position: node.position,
+                control: node.control
             });
         }
 
         // build the connections
-        for a in self.graph.edge_references() {
-            let source = self.graph.node_weight(a.source());
-            let target = self.graph.node_weight(a.target());
-
-            if let (Some(source), Some(target)) = (source, target) {
-                let street_vector = target.position - source.position;
-                let street_length = street_vector.length();
-                //let street_center = source.position + street_vector / street_length * street_length / 2.0;
-                //let rotation = -street_vector.angle_between(Vec2::new(1.0, 0.0)); 
-
-                let line = Line {
-                    point1: source.position,
-                    point2: target.position
-                };
-
-                let parallel_line = line.parralel(5.0);
-                let blue = materials.add(Color::rgb(0.1, 0.4, 0.5).into());
-
-                let mut builder = PathBuilder::new();
-                // Using that builder, you can build any shape:
-                builder.move_to(point(line.point1.x(), line.point1.y()));
-                builder.line_to(point(line.point2.x(), line.point2.y()));
-                builder.line_to(point(parallel_line.point2.x(), parallel_line.point2.y()));
-                builder.line_to(point(parallel_line.point1.x(), parallel_line.point1.y()));
-                
-                builder.close(); // This draws a line to (0.0, 0.0)                            
-
-                let path = builder.build();
-                commands
-                .spawn(path.fill(
-                    blue,
-                    &mut meshes,
-                    Vec3::new(0.0, 0.0, 0.0),
-                    &FillOptions::default(),
-                ))
-                .with(line);      
+        for outline in self.street_outlines() {
+            spawn_polygons(commands, &outline, materials.street.clone(), &mut meshes, 0.0)
+            .with(StreetOutline);
+        }
+    }
+
+    fn incident_edges(&self, node: NodeIndex<DefaultIx>) -> Vec<(EdgeIndex, NodeIndex<DefaultIx>)> {
+        self.graph.edges_directed(node, Direction::Outgoing)
+            .map(|edge| (edge.id(), edge.target()))
+            .chain(self.graph.edges_directed(node, Direction::Incoming).map(|edge| (edge.id(), edge.source())))
+            .collect()
+    }
+
+    /// Returns the streets as polylines. Connections of the same road type are chained as long
+    /// as they meet at intersections without any further connection. Closed loops end at
+    /// their first point.
+    pub fn street_polylines(&self) -> Vec<(RoadType, Vec<Vec2>)> {
+        let mut visited = HashSet::new();
+        let mut polylines = Vec::new();
+
+        for edge_index in self.graph.edge_indices() {
+            if visited.contains(&edge_index) {
+                continue;
+            }
+
+            let (start, end) = self.graph.edge_endpoints(edge_index).unwrap();
+            let road_type = self.graph[edge_index].road_type;
+            visited.insert(edge_index);
+
+            // walk into both directions until a junction or dead end is reached
+            let mut halves = Vec::new();
+            for to in &[end, start] {
+                let mut nodes = vec![*to];
+                let mut current = *to;
+
+                loop {
+                    let edges = self.incident_edges(current);
+                    if edges.len() != 2 {
+                        break;
+                    }
+
+                    match edges.iter().find(|(index, _)| !visited.contains(index) && self.graph[*index].road_type == road_type) {
+                        Some((index, next)) => {
+                            visited.insert(*index);
+                            current = *next;
+                            nodes.push(current);
+                        }
+                        None => break
+                    }
+                }
+
+                halves.push(nodes);
             }
+
+            let mut nodes: Vec<NodeIndex<DefaultIx>> = halves[1].iter().rev().cloned().collect();
+            nodes.extend(halves[0].iter());
+
+            polylines.push((road_type, nodes.iter().map(|node| self.graph.node_weight(*node).unwrap().position).collect()));
         }
+
+        polylines
     }
 
+    /// Calculates the outline of every street with the width of its road type
+    pub fn street_outlines(&self) -> Vec<Vec<Polygon>> {
+        self.street_polylines()
+            .iter()
+            .map(|(road_type, polyline)| {
+                let width = road_type.width();
+                let closed = polyline.len() > 3 && (polyline[0] - polyline[polyline.len() - 1]).length() < 1e-3;
+
+                if closed {
+                    let ring = Polygon::new(polyline[..polyline.len() - 1].to_vec());
+                    let mut outline = offset_polygon(&ring, width / 2.0, JoinType::Miter(2.0));
+                    outline.extend(offset_polygon(&ring, -width / 2.0, JoinType::Miter(2.0)).iter().map(|polygon| polygon.reversed()));
+
+                    return outline;
+                }
+
+                offset_polyline(polyline, width / 2.0, JoinType::Miter(2.0), EndType::Butt)
+            })
+            .collect()
+    }
 
     pub fn intersects(
         &self, 
@@ -279,8 +1336,414 @@ impl RoadSystem {
     */  
 }
 
+/// Returns the angles of the intersections of a roundabout. It contains the given angles and
+/// enough further ones so the ring has no gap larger than the roundabout step and no street
+/// longer than the roundabout segment.
+fn roundabout_angles(angles: &[f32], radius: f32) -> Vec<f32> {
+    let mut angles = angles.to_vec();
+    angles.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    angles.dedup_by(|a, b| (*a - *b).abs() < 1e-4);
+
+    // angles close to π and -π are the same direction
+    if angles.len() > 1 && angles[0] + 2.0 * PI - angles[angles.len() - 1] < 1e-4 {
+        angles.pop();
+    }
+
+    if angles.is_empty() {
+        angles.push(0.0);
+    }
+
+    let mut ring = Vec::new();
+    for (index, angle) in angles.iter().enumerate() {
+        let next = angles.get(index + 1).cloned().unwrap_or(angles[0] + 2.0 * PI);
+        let steps = ((next - angle) / ROUNDABOUT_STEP)
+            .max((next - angle) * radius / ROUNDABOUT_SEGMENT)
+            .ceil()
+            .max(1.0) as usize;
+
+        for step in 0..steps {
+            ring.push(angle + (next - angle) * step as f32 / steps as f32);
+        }
+    }
+
+    ring
+}
+
 impl fmt::Display for RoadSystem {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "RoadSystem Num_Nodes:{}, Num_Edges:{}", self.graph.node_count(), self.graph.edge_count())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use super::*;
+
+    fn street(road_system: &mut RoadSystem, start: Vec2, end: Vec2) -> Vec<EdgeIndex> {
+        let start = road_system.insert_intersection(RoadIntersection::new(start));
+        let end = road_system.insert_intersection(RoadIntersection::new(end));
+
+        road_system.connect_intersections(start, end, Street::default())
+    }
+
+    #[test]
+    fn separate_networks_are_components() {
+        let mut road_system = RoadSystem::new();
+        assert_eq!(road_system.connected_components(), 0);
+
+        road_system.insert_street(Vec2::new(0.0, 0.0), Vec2::new(200.0, 0.0), Street::default());
+        road_system.insert_street(Vec2::new(0.0, 100.0), Vec2::new(200.0, 100.0), Street::default());
+        assert_eq!(road_system.connected_components(), 2);
+
+        road_system.insert_street(Vec2::new(100.0, -50.0), Vec2::new(100.0, 150.0), Street::default());
+        assert_eq!(road_system.connected_components(), 1);
+    }
+
+    #[test]
+    fn connected_elements_stay_in_their_network() {
+        let mut road_system = RoadSystem::new();
+        let edges = road_system.insert_street(Vec2::new(0.0, 0.0), Vec2::new(200.0, 0.0), Street::default());
+        road_system.insert_street(Vec2::new(200.0, 0.0), Vec2::new(200.0, 200.0), Street::default());
+        road_system.insert_street(Vec2::new(500.0, 0.0), Vec2::new(700.0, 0.0), Street::default());
+
+        let elements = road_system.connected_elements(RoadElement::Street(edges[0]));
+        let streets = elements.iter().filter(|element| matches!(element, RoadElement::Street(_))).count();
+
+        assert_eq!(elements.len(), 5);
+        assert_eq!(streets, 2);
+        assert!(elements.contains(&RoadElement::Street(edges[0])));
+    }
+
+    #[test]
+    fn reversed_streets_keep_their_index() {
+        let mut road_system = RoadSystem::new();
+        let edges = road_system.insert_street(Vec2::new(0.0, 0.0), Vec2::new(200.0, 0.0), Street::new_one_way(RoadType::Avenue));
+        road_system.insert_street(Vec2::new(0.0, 100.0), Vec2::new(200.0, 100.0), Street::default());
+
+        road_system.reverse_street(edges[0]);
+
+        let line = road_system.street_line(edges[0]).unwrap();
+        assert_eq!(line.point1, Vec2::new(200.0, 0.0));
+        assert_eq!(line.point2, Vec2::new(0.0, 0.0));
+        assert_eq!(road_system.street(edges[0]), Some(&Street::new_one_way(RoadType::Avenue)));
+    }
+
+    #[test]
+    fn crossing_streets_are_split() {
+        let mut road_system = RoadSystem::new();
+
+        assert_eq!(street(&mut road_system, Vec2::new(0.0, 0.0), Vec2::new(200.0, 0.0)).len(), 1);
+        assert_eq!(street(&mut road_system, Vec2::new(100.0, -100.0), Vec2::new(100.0, 100.0)).len(), 2);
+        assert_eq!(road_system.graph.node_count(), 5);
+        assert_eq!(road_system.graph.edge_count(), 4);
+
+        // ends close to a street join it instead of creating another intersection
+        assert_eq!(street(&mut road_system, Vec2::new(50.0, 100.0), Vec2::new(50.0, -5.0)).len(), 1);
+        assert_eq!(road_system.graph.node_count(), 7);
+        assert_eq!(road_system.graph.edge_count(), 6);
+    }
+
+    #[test]
+    fn pick_prefers_intersections() {
+        let mut road_system = RoadSystem::new();
+        let street = street(&mut road_system, Vec2::new(0.0, 0.0), Vec2::new(200.0, 0.0))[0];
+        let (start, _) = road_system.graph.edge_endpoints(street).unwrap();
+
+        assert_eq!(road_system.pick(Vec2::new(1.0, 2.0)), Some(RoadElement::Intersection(start)));
+        assert_eq!(road_system.pick(Vec2::new(150.0, 3.0)), Some(RoadElement::Street(street)));
+        assert_eq!(road_system.pick(Vec2::new(150.0, 30.0)), None);
+    }
+
+    #[test]
+    fn moved_intersections_split_and_merge() {
+        let mut road_system = RoadSystem::new();
+        let horizontal = street(&mut road_system, Vec2::new(0.0, 0.0), Vec2::new(200.0, 0.0))[0];
+        let vertical = street(&mut road_system, Vec2::new(100.0, 100.0), Vec2::new(100.0, 200.0))[0];
+        let (_, end) = road_system.graph.edge_endpoints(horizontal).unwrap();
+        let (lower, upper) = road_system.graph.edge_endpoints(vertical).unwrap();
+
+        assert_eq!(road_system.move_intersection(lower, Vec2::new(100.0, -100.0)), lower);
+        assert_eq!(road_system.graph.node_count(), 5);
+        assert_eq!(road_system.graph.edge_count(), 4);
+
+        // dropped onto another intersection
+        assert_eq!(road_system.move_intersection(upper, Vec2::new(205.0, 3.0)), end);
+        assert_eq!(road_system.graph.node_count(), 4);
+        assert_eq!(road_system.graph.edge_count(), 3);
+    }
+
+    #[test]
+    fn removing_streets_removes_isolated_intersections() {
+        let mut road_system = RoadSystem::new();
+        street(&mut road_system, Vec2::new(0.0, 0.0), Vec2::new(200.0, 0.0));
+        let crossing = street(&mut road_system, Vec2::new(100.0, -100.0), Vec2::new(100.0, 100.0));
+
+        road_system.remove_street(crossing[0]);
+        assert_eq!(road_system.graph.node_count(), 4);
+        assert_eq!(road_system.graph.edge_count(), 3);
+
+        let (center, _) = road_system.graph.edge_endpoints(crossing[1]).unwrap();
+        road_system.remove_intersection(center);
+        assert_eq!(road_system.graph.node_count(), 0);
+        assert_eq!(road_system.graph.edge_count(), 0);
+    }
+
+    #[test]
+    fn inserted_streets_snap_onto_streets() {
+        let mut road_system = RoadSystem::new();
+        street(&mut road_system, Vec2::new(0.0, 0.0), Vec2::new(200.0, 0.0));
+
+        let edges = road_system.insert_street(Vec2::new(100.0, 10.0), Vec2::new(100.0, 200.0), Street::default());
+        assert_eq!(edges.len(), 1);
+        assert_eq!(road_system.graph.node_count(), 4);
+        assert_eq!(road_system.graph.edge_count(), 3);
+
+        let line = road_system.street_line(edges[0]).unwrap();
+        assert_eq!(line.point1, Vec2::new(100.0, 0.0));
+
+        assert!(road_system.insert_street(Vec2::new(0.0, 5.0), Vec2::new(5.0, 0.0), Street::default()).is_empty());
+    }
+
+    #[test]
+    fn roundabout_replaces_intersection() {
+        let mut road_system = RoadSystem::new();
+        street(&mut road_system, Vec2::new(-200.0, 0.0), Vec2::new(200.0, 0.0));
+        street(&mut road_system, Vec2::new(0.0, -200.0), Vec2::new(0.0, 200.0));
+        road_system.insert_street(Vec2::new(0.0, 0.0), Vec2::new(30.0, 30.0), Street::default());
+
+        let ring = road_system.insert_roundabout(Vec2::new(5.0, 5.0), 60.0);
+
+        // the short street ends inside the ring and is removed
+        assert_eq!(ring.len(), 12);
+        assert_eq!(road_system.graph.node_count(), 4 + 12);
+        assert_eq!(road_system.graph.edge_count(), 4 + 12);
+
+        for intersection in &ring {
+            assert_eq!(road_system.graph[*intersection].control, TrafficControl::Roundabout);
+            assert!((road_system.graph[*intersection].position.length() - 60.0).abs() < 1e-3);
+        }
+
+        let one_way = road_system.graph.edge_indices().filter(|edge| road_system.graph[*edge].one_way).count();
+        assert_eq!(one_way, 12);
+
+        let east = road_system.pick(Vec2::new(200.0, 0.0)).unwrap();
+        let entry = road_system.pick(Vec2::new(60.0, 0.0)).unwrap();
+        match (east, entry) {
+            (RoadElement::Intersection(east), RoadElement::Intersection(entry)) => {
+                // the street kept its direction away from the center
+                assert!(road_system.graph.find_edge(entry, east).is_some());
+            }
+            _ => panic!("intersections expected")
+        }
+    }
+
+    #[test]
+    fn roundabout_ring_is_round() {
+        // the same direction on both sides of the seam is one intersection
+        let ring = roundabout_angles(&[PI, -PI + 1e-5], 60.0);
+        assert!((ring[0] + PI).abs() < 1e-4);
+        assert!(ring[ring.len() - 1] < PI - 0.1);
+
+        // large rings are divided into short streets
+        let ring = roundabout_angles(&[0.0], 200.0);
+        assert_eq!(ring.len(), 32);
+        for (index, angle) in ring.iter().enumerate() {
+            let next = ring.get(index + 1).cloned().unwrap_or(ring[0] + 2.0 * PI);
+            assert!((next - angle) * 200.0 <= ROUNDABOUT_SEGMENT + 1e-3);
+        }
+    }
+
+    #[test]
+    fn stretch_ends_at_junctions() {
+        let mut road_system = RoadSystem::new();
+        let curve = [Vec2::new(0.0, 0.0), Vec2::new(100.0, 0.0), Vec2::new(200.0, 50.0), Vec2::new(300.0, 50.0)];
+        for points in curve.windows(2) {
+            road_system.insert_street(points[0], points[1], Street::default());
+        }
+        let branch = road_system.insert_street(Vec2::new(200.0, 50.0), Vec2::new(200.0, 200.0), Street::default());
+
+        let first = match road_system.pick(Vec2::new(50.0, 0.0)) {
+            Some(RoadElement::Street(street)) => street,
+            _ => panic!("street expected")
+        };
+
+        let stretch = road_system.street_stretch(first);
+        assert_eq!(stretch.len(), 2);
+        assert!(!stretch.contains(&branch[0]));
+        assert_eq!(road_system.street_stretch(branch[0]), branch);
+    }
+
+    #[test]
+    fn city_text_keeps_all_attributes() {
+        let mut road_system = RoadSystem::new();
+        let streets = road_system.insert_street(Vec2::new(0.5, -10.25), Vec2::new(200.0, 0.0), Street::new_one_way(RoadType::Avenue));
+        road_system.insert_street(Vec2::new(200.0, 0.0), Vec2::new(200.0, 150.0), Street::default());
+        road_system.insert_street(Vec2::new(500.0, 0.0), Vec2::new(600.0, 0.0), Street::default());
+        road_system.remove_street(road_system.nearest_street(Vec2::new(550.0, 0.0), 1.0).unwrap());
+
+        let mut named = road_system.street(streets[0]).unwrap().clone();
+        named.name = "Main  Street".to_string();
+        named.lanes = 3;
+        named.speed_limit = 42.5;
+        road_system.set_street(streets[0], named);
+
+        let intersection = road_system.nearest_intersection(Vec2::new(200.0, 0.0), 1.0).unwrap();
+        road_system.set_control(intersection, TrafficControl::TrafficLight);
+
+        let text = road_system.to_city_text();
+        let loaded = RoadSystem::parse_city(&text).unwrap();
+
+        assert_eq!(loaded.intersections().len(), road_system.intersections().len());
+        assert_eq!(loaded.streets().len(), road_system.streets().len());
+
+        for (intersection, loaded_intersection) in road_system.intersections().iter().zip(loaded.intersections()) {
+            assert_eq!(road_system.intersection(*intersection), loaded.intersection(loaded_intersection));
+        }
+
+        for (street, loaded_street) in road_system.streets().iter().zip(loaded.streets()) {
+            assert_eq!(road_system.street(*street), loaded.street(loaded_street));
+            assert_eq!(road_system.street_line(*street), loaded.street_line(loaded_street));
+        }
+
+        assert_eq!(loaded.to_city_text(), text);
+    }
+
+    #[test]
+    fn reject_invalid_cities() {
+        assert!(RoadSystem::parse_city("intersection 0 0 uncontrolled\n").is_ok());
+        assert!(RoadSystem::parse_city("intersection 0 0 stop_sign\n").is_err());
+        assert!(RoadSystem::parse_city("intersection 0 0 uncontrolled\nstreet 0 1 residential false 1 30 \n").is_err());
+        assert!(RoadSystem::parse_city("intersection 0 0 uncontrolled\nstreet 0 0 residential maybe 1 30 \n").is_err());
+    }
+
+    #[test]
+    fn road_type_resets_lanes_and_speed_limit() {
+        let mut street = Street::new(RoadType::Residential);
+        street.lanes = 4;
+        street.speed_limit = 20.0;
+
+        street.set_road_type(RoadType::Avenue);
+        assert_eq!(street, Street::new(RoadType::Avenue));
+        assert_eq!(street.lanes, 2);
+    }
+
+    #[test]
+    fn upgrades_saturate() {
+        assert_eq!(RoadType::Residential.upgraded(), RoadType::Avenue);
+        assert_eq!(RoadType::Highway.upgraded(), RoadType::Highway);
+        assert_eq!(RoadType::Highway.downgraded(), RoadType::Avenue);
+        assert_eq!(RoadType::Residential.downgraded(), RoadType::Residential);
+    }
+
+    #[test]
+    fn blocks_between_streets() {
+        let mut road_system = RoadSystem::new();
+        road_system.insert_street(Vec2::new(0.0, 0.0), Vec2::new(200.0, 0.0), Street::default());
+        road_system.insert_street(Vec2::new(200.0, 0.0), Vec2::new(200.0, 100.0), Street::default());
+        road_system.insert_street(Vec2::new(200.0, 100.0), Vec2::new(0.0, 100.0), Street::default());
+        road_system.insert_street(Vec2::new(0.0, 100.0), Vec2::new(0.0, 0.0), Street::default());
+        road_system.insert_street(Vec2::new(100.0, 0.0), Vec2::new(100.0, 100.0), Street::default());
+
+        // a dead end does not split the block
+        road_system.insert_street(Vec2::new(150.0, 0.0), Vec2::new(150.0, 50.0), Street::default());
+
+        let blocks = road_system.blocks();
+        assert_eq!(blocks.len(), 2);
+
+        let block = road_system.block_at(Vec2::new(175.0, 75.0)).unwrap();
+        assert!((block.area() - 10000.0).abs() < 1e-2);
+        assert!(road_system.block_at(Vec2::new(300.0, 50.0)).is_none());
+    }
+
+    #[test]
+    fn one_way_streets_are_drivable_in_their_direction() {
+        let mut road_system = RoadSystem::new();
+        let street = road_system.insert_street(Vec2::new(0.0, 0.0), Vec2::new(200.0, 0.0), Street::new_one_way(RoadType::Avenue));
+        let (start, end) = road_system.graph.edge_endpoints(street[0]).unwrap();
+
+        assert_eq!(road_system.drivable_streets(start), vec![(street[0], end)]);
+        assert!(road_system.drivable_streets(end).is_empty());
+    }
+
+    #[test]
+    fn elements_in_area() {
+        let mut road_system = RoadSystem::new();
+        street(&mut road_system, Vec2::new(0.0, 0.0), Vec2::new(100.0, 0.0));
+        street(&mut road_system, Vec2::new(0.0, 50.0), Vec2::new(300.0, 50.0));
+
+        let area = Polygon::new(vec![
+            Vec2::new(-10.0, -10.0),
+            Vec2::new(150.0, -10.0),
+            Vec2::new(150.0, 100.0),
+            Vec2::new(-10.0, 100.0)
+        ]);
+
+        assert_eq!(road_system.elements_in(&area, false).len(), 4);
+        assert_eq!(road_system.elements_in(&area, true).len(), 5);
+    }
+
+    #[test]
+    fn road_types_are_not_chained() {
+        let mut road_system = RoadSystem::new();
+        let first = street(&mut road_system, Vec2::new(0.0, 0.0), Vec2::new(100.0, 0.0))[0];
+        let (_, end) = road_system.graph.edge_endpoints(first).unwrap();
+        let last = road_system.insert_intersection(RoadIntersection::new(Vec2::new(200.0, 0.0)));
+        road_system.connect_intersections(end, last, Street::new(RoadType::Avenue));
+
+        assert_eq!(road_system.street_polylines().len(), 2);
+
+        road_system.set_road_type(first, RoadType::Avenue);
+        assert_eq!(road_system.street_polylines(), vec![(RoadType::Avenue, vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(200.0, 0.0)
+        ])]);
+    }
+
+    #[test]
+    fn copy_and_paste_rotated() {
+        let mut road_system = RoadSystem::new();
+        let streets = street(&mut road_system, Vec2::new(0.0, 0.0), Vec2::new(200.0, 0.0));
+        road_system.set_road_type(streets[0], RoadType::Highway);
+
+        let template = road_system.copy_elements(&[RoadElement::Street(streets[0])]);
+        assert_eq!(template.intersections, vec![Vec2::new(-100.0, 0.0), Vec2::new(100.0, 0.0)]);
+        assert_eq!(template.streets, vec![(0, 1, RoadType::Highway)]);
+
+        // pasted across the original street
+        let elements = road_system.paste(&template, Vec2::new(100.0, 0.0), std::f32::consts::PI / 2.0);
+        assert_eq!(elements.len(), 5);
+        assert_eq!(road_system.graph.node_count(), 5);
+        assert_eq!(road_system.graph.edge_count(), 4);
+
+        // pasting onto itself merges every intersection and adds nothing
+        road_system.paste(&template, Vec2::new(100.0, 0.0), 0.0);
+        assert_eq!(road_system.graph.node_count(), 5);
+        assert_eq!(road_system.graph.edge_count(), 4);
+    }
+
+    #[test]
+    fn parse_template() {
+        let template = RoadTemplate::parse("# a single street\nintersection -50 0\n\nintersection 50 0.5\nstreet 0 1 avenue\n").unwrap();
+
+        assert_eq!(template.intersections, vec![Vec2::new(-50.0, 0.0), Vec2::new(50.0, 0.5)]);
+        assert_eq!(template.streets, vec![(0, 1, RoadType::Avenue)]);
+        assert_eq!(RoadTemplate::parse(&template.to_text()), Ok(template));
+    }
+
+    #[test]
+    fn reject_invalid_templates() {
+        assert!(RoadTemplate::parse("intersection 0").is_err());
+        assert!(RoadTemplate::parse("intersection 0 0\nstreet 0 1 avenue").is_err());
+        assert!(RoadTemplate::parse("intersection 0 0\nintersection 1 0\nstreet 0 1 motorway").is_err());
+    }
+
+    #[test]
+    fn place_rotated_template() {
+        let template = RoadTemplate::parse("intersection 10 0").unwrap();
+        let position = template.place(0, Vec2::new(100.0, 100.0), PI / 2.0);
+
+        assert!((position - Vec2::new(100.0, 110.0)).length() < 1e-4);
+    }
+}
@@ -0,0 +1,235 @@
+use bevy::prelude::*;
+
+use petgraph::graph::{ EdgeIndex, NodeIndex };
+
+use std::cmp::Ordering;
+use std::collections::{ BinaryHeap, HashMap };
+
+use crate::roadsystem::RoadSystem;
+
+/// Path through the road system
+#[derive(Clone, Debug, PartialEq)]
+pub struct Route {
+    pub intersections: Vec<NodeIndex>,
+    pub streets: Vec<EdgeIndex>,
+    pub length: f32
+}
+
+/// Intersection which is still to be visited with the estimated length of the route through it
+struct Candidate {
+    estimate: f32,
+    intersection: NodeIndex
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Candidate) -> bool {
+        self.estimate == other.estimate
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Candidate) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    // reversed so the heap returns the shortest estimate first
+    fn cmp(&self, other: &Candidate) -> Ordering {
+        other.estimate.partial_cmp(&self.estimate).unwrap_or(Ordering::Equal)
+    }
+}
+
+/// Finds the shortest route between the intersections with A*. One-way streets are only
+/// used in their direction.
+pub fn shortest_route(road_system: &RoadSystem, from: NodeIndex, to: NodeIndex) -> Option<Route> {
+    let target = road_system.intersection_position(to)?;
+    let heuristic = |intersection: NodeIndex| {
+        (road_system.intersection_position(intersection).unwrap() - target).length()
+    };
+
+    let mut lengths: HashMap<NodeIndex, f32> = HashMap::new();
+    let mut previous: HashMap<NodeIndex, (NodeIndex, EdgeIndex)> = HashMap::new();
+    let mut candidates = BinaryHeap::new();
+
+    road_system.intersection_position(from)?;
+    lengths.insert(from, 0.0);
+    candidates.push(Candidate { estimate: heuristic(from), intersection: from });
+
+    while let Some(Candidate { estimate, intersection }) = candidates.pop() {
+        let length = lengths[&intersection];
+
+        if intersection == to {
+            return Some(collect_route(&previous, from, to, length));
+        }
+
+        // outdated entry of an intersection which was reached on a shorter route later
+        if estimate > length + heuristic(intersection) + 1e-3 {
+            continue;
+        }
+
+        for (street, next) in road_system.drivable_streets(intersection) {
+            let next_length = length + road_system.street_line(street).unwrap().length();
+
+            if lengths.get(&next).map_or(true, |known| next_length < *known) {
+                lengths.insert(next, next_length);
+                previous.insert(next, (intersection, street));
+                candidates.push(Candidate { estimate: next_length + heuristic(next), intersection: next });
+            }
+        }
+    }
+
+    None
+}
+
+/// Finds the shortest route between two points which are snapped to their nearest street
+/// within the distance. The first and the last street are part of the route, but only their
+/// parts up to the points count to its length.
+pub fn route_between_points(road_system: &RoadSystem, start: Vec2, end: Vec2, max_distance: f32) -> Option<Route> {
+    let first = road_system.nearest_street(start, max_distance)?;
+    let last = road_system.nearest_street(end, max_distance)?;
+
+    // both points on the same street which can be driven from the first to the second point
+    if first == last {
+        let line = road_system.street_line(first)?;
+        let start = line.closest_point(start);
+        let end = line.closest_point(end);
+
+        if !road_system.street(first)?.one_way || (end - line.point1).length() >= (start - line.point1).length() {
+            return Some(Route { intersections: Vec::new(), streets: vec![first], length: (end - start).length() });
+        }
+    }
+
+    let mut best: Option<Route> = None;
+    for (exit, exit_length) in street_ends(road_system, first, start, true) {
+        for (entry, entry_length) in street_ends(road_system, last, end, false) {
+            if let Some(route) = shortest_route(road_system, exit, entry) {
+                let length = exit_length + route.length + entry_length;
+
+                if best.as_ref().map_or(true, |best| length < best.length) {
+                    best = Some(Route { length, ..route });
+                }
+            }
+        }
+    }
+
+    best.map(|mut route| {
+        route.streets.insert(0, first);
+        route.streets.push(last);
+        route
+    })
+}
+
+/// Returns the intersections of the street which can be reached from the point closest to the
+/// position, or with `leaving` false which reach it, with their distance to the point. One-way
+/// streets only lead from their first to their second intersection.
+fn street_ends(road_system: &RoadSystem, street: EdgeIndex, position: Vec2, leaving: bool) -> Vec<(NodeIndex, f32)> {
+    let (start, end) = match (road_system.street_intersections(street), road_system.street_line(street), road_system.street(street)) {
+        (Some(intersections), Some(line), Some(street)) => {
+            let point = line.closest_point(position);
+            let start = (intersections.0, (point - line.point1).length());
+            let end = (intersections.1, (line.point2 - point).length());
+
+            if street.one_way {
+                return vec![if leaving { end } else { start }];
+            }
+
+            (start, end)
+        }
+        _ => return Vec::new()
+    };
+
+    vec![start, end]
+}
+
+fn collect_route(previous: &HashMap<NodeIndex, (NodeIndex, EdgeIndex)>, from: NodeIndex, to: NodeIndex, length: f32) -> Route {
+    let mut intersections = vec![to];
+    let mut streets = Vec::new();
+
+    let mut current = to;
+    while current != from {
+        let (before, street) = previous[&current];
+
+        intersections.push(before);
+        streets.push(street);
+        current = before;
+    }
+
+    intersections.reverse();
+    streets.reverse();
+
+    Route { intersections, streets, length }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use super::*;
+    use crate::roadsystem::{ RoadElement, RoadType, Street };
+
+    fn intersection_at(road_system: &RoadSystem, position: Vec2) -> NodeIndex {
+        match road_system.pick(position) {
+            Some(RoadElement::Intersection(intersection)) => intersection,
+            _ => panic!("no intersection at {:?}", position)
+        }
+    }
+
+    #[test]
+    fn shortest_route_around_block() {
+        let mut road_system = RoadSystem::new();
+        road_system.insert_street(Vec2::new(0.0, 0.0), Vec2::new(300.0, 0.0), Street::default());
+        road_system.insert_street(Vec2::new(300.0, 0.0), Vec2::new(300.0, 100.0), Street::default());
+        road_system.insert_street(Vec2::new(0.0, 0.0), Vec2::new(0.0, 100.0), Street::default());
+        road_system.insert_street(Vec2::new(0.0, 100.0), Vec2::new(300.0, 100.0), Street::default());
+
+        let from = intersection_at(&road_system, Vec2::new(0.0, 0.0));
+        let to = intersection_at(&road_system, Vec2::new(300.0, 100.0));
+
+        let route = shortest_route(&road_system, from, to).unwrap();
+        assert!((route.length - 400.0).abs() < 1e-3);
+        assert_eq!(route.intersections.len(), 3);
+        assert_eq!(route.streets.len(), 2);
+        assert_eq!(route.intersections[0], from);
+    }
+
+    #[test]
+    fn one_way_streets_force_detours() {
+        let mut road_system = RoadSystem::new();
+        road_system.insert_street(Vec2::new(300.0, 0.0), Vec2::new(0.0, 0.0), Street::new_one_way(RoadType::Avenue));
+        road_system.insert_street(Vec2::new(0.0, 0.0), Vec2::new(0.0, 100.0), Street::default());
+        road_system.insert_street(Vec2::new(0.0, 100.0), Vec2::new(300.0, 100.0), Street::default());
+        road_system.insert_street(Vec2::new(300.0, 100.0), Vec2::new(300.0, 0.0), Street::default());
+
+        let from = intersection_at(&road_system, Vec2::new(0.0, 0.0));
+        let to = intersection_at(&road_system, Vec2::new(300.0, 0.0));
+
+        assert!((shortest_route(&road_system, from, to).unwrap().length - 500.0).abs() < 1e-3);
+        assert!((shortest_route(&road_system, to, from).unwrap().length - 300.0).abs() < 1e-3);
+
+        road_system.remove_street(road_system.nearest_street(Vec2::new(150.0, 100.0), 1.0).unwrap());
+        assert!(shortest_route(&road_system, from, to).is_none());
+    }
+    #[test]
+    fn route_between_points_on_streets() {
+        let mut road_system = RoadSystem::new();
+        road_system.insert_street(Vec2::new(0.0, 0.0), Vec2::new(300.0, 0.0), Street::default());
+        road_system.insert_street(Vec2::new(300.0, 0.0), Vec2::new(300.0, 100.0), Street::default());
+        road_system.insert_street(Vec2::new(300.0, 100.0), Vec2::new(0.0, 100.0), Street::new_one_way(RoadType::Avenue));
+
+        // from the middle of the bottom street around the corner to the middle of the top street
+        let route = route_between_points(&road_system, Vec2::new(100.0, 5.0), Vec2::new(150.0, 95.0), 10.0).unwrap();
+        assert!((route.length - (200.0 + 100.0 + 150.0)).abs() < 1e-3);
+        assert_eq!(route.streets.len(), 3);
+
+        // the one-way street can't be driven back
+        assert!(route_between_points(&road_system, Vec2::new(150.0, 95.0), Vec2::new(100.0, 5.0), 10.0).is_none());
+
+        let route = route_between_points(&road_system, Vec2::new(250.0, 0.0), Vec2::new(50.0, 0.0), 10.0).unwrap();
+        assert!((route.length - 200.0).abs() < 1e-3);
+        assert!(route.intersections.is_empty());
+
+        assert!(route_between_points(&road_system, Vec2::new(150.0, 50.0), Vec2::new(50.0, 0.0), 10.0).is_none());
+    }
+}
@@ -0,0 +1,424 @@
+use bevy::prelude::*;
+
+use petgraph::graph::NodeIndex;
+
+use std::collections::HashSet;
+use std::f32::consts::PI;
+
+use crate::app_state::AppState;
+use crate::input;
+use crate::keybindings::{ Action, Actions };
+use crate::math::offset::{ offset_polyline, EndType, JoinType };
+use crate::math::polygon::Polygon;
+use crate::math::rectangle::Rectangle;
+use crate::roadsystem::{ RoadElement, RoadSystem, INTERSECTION_SIZE };
+use crate::shapes::spawn_polygons;
+use crate::theme::ThemeMaterials;
+use crate::ui;
+use crate::view::CameraView;
+
+/// Angle selections, templates and grids are rotated by per key press
+pub const ROTATION_STEP: f32 = PI / 12.0;
+
+/// Minimal distance between two points of a lasso
+const LASSO_STEP: f32 = 5.0;
+
+/// Intersections which are moved together with their position before the move
+struct Drag {
+    start: Vec2,
+    offset: Vec2,
+    positions: Vec<(NodeIndex, Vec2)>
+}
+
+/// Area dragged with the mouse to select elements or to place a grid
+#[derive(Clone, PartialEq, Debug)]
+pub enum SelectionArea {
+    /// Dragged from the first to the second corner
+    Box(Vec2, Vec2),
+    Lasso(Vec<Vec2>)
+}
+
+impl SelectionArea {
+    pub fn new(position: Vec2, lasso: bool) -> Self {
+        if lasso {
+            SelectionArea::Lasso(vec![position])
+        } else {
+            SelectionArea::Box(position, position)
+        }
+    }
+
+    /// Moves the dragged corner to the cursor or extends the lasso once the cursor moved far enough
+    pub fn update(&mut self, position: Vec2) {
+        match self {
+            SelectionArea::Box(_, corner) => *corner = position,
+            SelectionArea::Lasso(points) => {
+                if (points[points.len() - 1] - position).length() >= LASSO_STEP {
+                    points.push(position);
+                }
+            }
+        }
+    }
+
+    /// Enclosed polygon or `None` if the area is still empty
+    pub fn polygon(&self) -> Option<Polygon> {
+        match self {
+            SelectionArea::Box(corner1, corner2) => {
+                let rectangle = Rectangle::from_corners(*corner1, *corner2);
+
+                if rectangle.width() > 0.0 && rectangle.height() > 0.0 {
+                    Some(rectangle.to_polygon())
+                } else {
+                    None
+                }
+            }
+            SelectionArea::Lasso(points) if points.len() >= 3 => Some(Polygon::new(points.clone())),
+            _ => None
+        }
+    }
+}
+
+/// Currently selected parts of the road system
+#[derive(Default)]
+pub struct Selection {
+    pub elements: HashSet<RoadElement>,
+    drag: Option<Drag>,
+    area: Option<SelectionArea>
+}
+
+impl Selection {
+    pub fn clear(&mut self) {
+        self.elements.clear();
+        self.drag = None;
+        self.area = None;
+    }
+
+    /// Forgets all elements which were removed from the road system
+    pub fn retain_valid(&mut self, road_system: &RoadSystem) {
+        self.elements.retain(|element| road_system.contains(*element));
+    }
+
+    fn toggle(&mut self, element: RoadElement) {
+        if !self.elements.remove(&element) {
+            self.elements.insert(element);
+        }
+    }
+}
+
+/// Marks the entities which highlight the selection
+pub struct SelectionHighlight;
+
+fn rotate_around(position: Vec2, center: Vec2, angle: f32) -> Vec2 {
+    let (sin, cos) = angle.sin_cos();
+    let offset = position - center;
+
+    center + Vec2::new(offset.x() * cos - offset.y() * sin, offset.x() * sin + offset.y() * cos)
+}
+
+/// Moves the intersections to their new position and updates the network around them
+fn finish_move(road_system: &mut RoadSystem, selection: &mut Selection, positions: &[(NodeIndex, Vec2)]) {
+    for (intersection, position) in positions {
+        if !road_system.contains(RoadElement::Intersection(*intersection)) {
+            continue;
+        }
+
+        // the intersection may have been merged into another one
+        let moved = road_system.move_intersection(*intersection, *position);
+        if moved != *intersection && selection.elements.remove(&RoadElement::Intersection(*intersection)) {
+            selection.elements.insert(RoadElement::Intersection(moved));
+        }
+    }
+
+    selection.retain_valid(road_system);
+}
+
+fn select_system(
+    app_state: Res<AppState>,
+    current_action: Res<ui::RoadActions>,
+    mut selection: ResMut<Selection>,
+    state: Res<input::MouseState>,
+    view: Res<CameraView>,
+    actions: Res<Actions>,
+    mut graph_query: Query<&mut RoadSystem>
+) {
+    if *app_state != AppState::Editing {
+        return;
+    }
+
+    if *current_action != ui::RoadActions::Select {
+        if !selection.elements.is_empty() || selection.drag.is_some() || selection.area.is_some() {
+            selection.clear();
+        }
+
+        return;
+    }
+
+    let pressed = actions.just_pressed(Action::Primary);
+    let released = actions.just_released(Action::Primary);
+    if !pressed && !released && selection.drag.is_none() && selection.area.is_none() {
+        return;
+    }
+
+    let mouse_pos_ws = crate::mouse_pos_ws(&state, &view);
+    let extend = actions.pressed(Action::Extend);
+    let selection = &mut *selection;
+
+    for mut road_system in &mut graph_query.iter() {
+        if pressed {
+            match road_system.pick(mouse_pos_ws) {
+                Some(element) if extend => selection.toggle(element),
+                Some(element) => {
+                    if !selection.elements.contains(&element) {
+                        selection.elements.clear();
+                        selection.elements.insert(element);
+                    }
+
+                    let elements: Vec<RoadElement> = selection.elements.iter().cloned().collect();
+                    let positions = road_system
+                        .element_intersections(&elements)
+                        .iter()
+                        .map(|intersection| (*intersection, road_system.intersection_position(*intersection).unwrap()))
+                        .collect();
+
+                    selection.drag = Some(Drag { start: mouse_pos_ws, offset: Vec2::zero(), positions });
+                }
+                None => {
+                    if !extend {
+                        selection.elements.clear();
+                    }
+
+                    selection.area = Some(SelectionArea::new(mouse_pos_ws, actions.pressed(Action::Lasso)));
+                }
+            }
+        }
+
+        if let Some(drag) = &mut selection.drag {
+            let offset = mouse_pos_ws - drag.start;
+
+            if offset != drag.offset {
+                drag.offset = offset;
+
+                for (intersection, position) in &drag.positions {
+                    road_system.set_intersection_position(*intersection, *position + offset);
+                }
+            }
+        }
+
+        if let Some(area) = &mut selection.area {
+            area.update(mouse_pos_ws);
+        }
+
+        if !released {
+            continue;
+        }
+
+        if let Some(drag) = selection.drag.take() {
+            if drag.offset != Vec2::zero() {
+                let positions: Vec<(NodeIndex, Vec2)> = drag.positions
+                    .iter()
+                    .map(|(intersection, position)| (*intersection, *position + drag.offset))
+                    .collect();
+
+                finish_move(&mut road_system, selection, &positions);
+            }
+        }
+
+        let elements = match selection.area.take() {
+            // dragging to the left also selects streets crossing the box
+            Some(SelectionArea::Box(corner1, corner2)) => {
+                road_system.elements_in(&Rectangle::from_corners(corner1, corner2), corner2.x() < corner1.x())
+            }
+            Some(SelectionArea::Lasso(points)) if points.len() >= 3 => {
+                road_system.elements_in(&Polygon::new(points), false)
+            }
+            _ => Vec::new()
+        };
+
+        selection.elements.extend(elements);
+    }
+}
+
+/// Applies the editing actions to all selected elements
+fn bulk_edit_system(
+    app_state: Res<AppState>,
+    current_action: Res<ui::RoadActions>,
+    actions: Res<Actions>,
+    mut selection: ResMut<Selection>,
+    mut graph_query: Query<&mut RoadSystem>
+) {
+    if *app_state != AppState::Editing || *current_action != ui::RoadActions::Select || selection.elements.is_empty() || selection.drag.is_some() {
+        return;
+    }
+
+    if ![Action::Delete, Action::CycleRoadType, Action::RotateLeft, Action::RotateRight].iter().any(|action| actions.just_pressed(*action)) {
+        return;
+    }
+
+    let selection = &mut *selection;
+
+    for mut road_system in &mut graph_query.iter() {
+        let elements: Vec<RoadElement> = selection.elements.iter().cloned().collect();
+
+        if actions.just_pressed(Action::Delete) {
+            for element in &elements {
+                match element {
+                    RoadElement::Street(street) => road_system.remove_street(*street),
+                    RoadElement::Intersection(intersection) => {
+                        if road_system.contains(*element) {
+                            road_system.remove_intersection(*intersection);
+                        }
+                    }
+                }
+            }
+
+            selection.clear();
+            return;
+        }
+
+        if actions.just_pressed(Action::CycleRoadType) {
+            // all selected streets get the type following the one of the street with the
+            // lowest index, the order of the selection is arbitrary
+            let road_type = elements
+                .iter()
+                .filter_map(|element| match element {
+                    RoadElement::Street(street) => Some(*street),
+                    _ => None
+                })
+                .min()
+                .and_then(|street| road_system.road_type(street));
+
+            if let Some(road_type) = road_type {
+                for element in &elements {
+                    if let RoadElement::Street(street) = element {
+                        road_system.set_road_type(*street, road_type.next());
+                    }
+                }
+            }
+        }
+
+        let angle = if actions.just_pressed(Action::RotateLeft) {
+            ROTATION_STEP
+        } else if actions.just_pressed(Action::RotateRight) {
+            -ROTATION_STEP
+        } else {
+            continue;
+        };
+
+        let intersections = road_system.element_intersections(&elements);
+        let center = intersections
+            .iter()
+            .map(|intersection| road_system.intersection_position(*intersection).unwrap())
+            .fold(Vec2::zero(), |sum, position| sum + position) / intersections.len() as f32;
+
+        let positions: Vec<(NodeIndex, Vec2)> = intersections
+            .iter()
+            .map(|intersection| {
+                let position = road_system.intersection_position(*intersection).unwrap();
+                (*intersection, rotate_around(position, center, angle))
+            })
+            .collect();
+
+        // move all first so rotated streets do not cross their old positions
+        for (intersection, position) in &positions {
+            road_system.set_intersection_position(*intersection, *position);
+        }
+
+        finish_move(&mut road_system, selection, &positions);
+    }
+}
+
+fn highlight_system(
+    mut commands: Commands,
+    selection: ChangedRes<Selection>,
+    theme_materials: Res<ThemeMaterials>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut highlight_query: Query<With<SelectionHighlight, Entity>>,
+    mut graph_query: Query<&RoadSystem>
+) {
+    for entity in &mut highlight_query.iter() {
+        commands.despawn(entity);
+    }
+
+    let selection_material = theme_materials.selection.clone();
+
+    for road_system in &mut graph_query.iter() {
+        for element in &selection.elements {
+            match element {
+                RoadElement::Intersection(intersection) => {
+                    if let Some(position) = road_system.intersection_position(*intersection) {
+                        // slightly larger than the intersection and behind it
+                        commands
+                        .spawn(SpriteComponents {
+                            material: selection_material.clone(),
+                            transform: Transform::from_translation(Vec3::new(position.x(), position.y(), 1.5)),
+                            sprite: Sprite::new(Vec2::new(INTERSECTION_SIZE + 10.0, INTERSECTION_SIZE + 10.0)),
+                            ..Default::default()
+                        })
+                        .with(SelectionHighlight);
+                    }
+                }
+                RoadElement::Street(street) => {
+                    if let (Some(line), Some(road_type)) = (road_system.street_line(*street), road_system.road_type(*street)) {
+                        let outline = offset_polyline(&[line.point1, line.point2], road_type.width() / 2.0, JoinType::Miter(2.0), EndType::Butt);
+
+                        spawn_polygons(&mut commands, &outline, selection_material.clone(), &mut meshes, 1.0)
+                        .with(SelectionHighlight);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut border = match &selection.area {
+        Some(SelectionArea::Box(corner1, corner2)) => Rectangle::from_corners(*corner1, *corner2).to_polygon().points().to_vec(),
+        Some(SelectionArea::Lasso(points)) if points.len() >= 2 => points.clone(),
+        _ => return
+    };
+    border.push(border[0]);
+
+    let outline = offset_polyline(&border, 1.0, JoinType::Miter(2.0), EndType::Butt);
+    if !outline.is_empty() {
+        spawn_polygons(&mut commands, &outline, selection_material, &mut meshes, 3.0)
+        .with(SelectionHighlight);
+    }
+}
+
+pub struct SelectionPlugin;
+
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<Selection>();
+        app.add_system_to_stage("do_things", select_system.system());
+        app.add_system_to_stage("do_things", bulk_edit_system.system());
+        app.add_system_to_stage("do_things", highlight_system.system());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use super::*;
+
+    #[test]
+    fn lasso_skips_small_movements() {
+        let mut area = SelectionArea::new(Vec2::new(0.0, 0.0), true);
+        area.update(Vec2::new(LASSO_STEP / 2.0, 0.0));
+        area.update(Vec2::new(100.0, 0.0));
+        assert!(area.polygon().is_none());
+
+        area.update(Vec2::new(100.0, 100.0));
+        assert_eq!(area, SelectionArea::Lasso(vec![Vec2::new(0.0, 0.0), Vec2::new(100.0, 0.0), Vec2::new(100.0, 100.0)]));
+        assert!((area.polygon().unwrap().area() - 5000.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn box_needs_an_area() {
+        let mut area = SelectionArea::new(Vec2::new(10.0, 10.0), false);
+        assert!(area.polygon().is_none());
+
+        area.update(Vec2::new(50.0, 10.0));
+        assert!(area.polygon().is_none());
+
+        area.update(Vec2::new(-10.0, 40.0));
+        assert!((area.polygon().unwrap().area() - 600.0).abs() < 1e-3);
+    }
+}
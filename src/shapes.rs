@@ -0,0 +1,43 @@
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::VertexAttribute,
+        pipeline::PrimitiveTopology,
+    },
+};
+
+use crate::math::polygon::Polygon;
+use crate::math::triangulation::triangulate;
+
+/// Spawns the polygons filled as a single mesh. Rings inside an odd number of other rings
+/// are holes.
+pub fn spawn_polygons<'a>(
+    commands: &'a mut Commands,
+    polygons: &[Polygon],
+    material: Handle<ColorMaterial>,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    z: f32
+) -> &'a mut Commands {
+    let positions: Vec<[f32; 3]> = triangulate(polygons)
+        .iter()
+        .flat_map(|triangle| vec![triangle.a, triangle.b, triangle.c])
+        .map(|point| [point.x(), point.y(), 0.0])
+        .collect();
+
+    let count = positions.len();
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.attributes.push(VertexAttribute::position(positions));
+    mesh.attributes.push(VertexAttribute::normal(vec![[0.0, 0.0, 1.0]; count]));
+    mesh.attributes.push(VertexAttribute::uv(vec![[0.0, 0.0]; count]));
+    mesh.indices = Some((0..count as u32).collect());
+
+    // the sprite size scales the mesh
+    commands.spawn(SpriteComponents {
+        material,
+        mesh: meshes.add(mesh),
+        sprite: Sprite::new(Vec2::new(1.0, 1.0)),
+        transform: Transform::from_translation(Vec3::new(0.0, 0.0, z)),
+        ..Default::default()
+    })
+}
@@ -0,0 +1,212 @@
+use bevy::prelude::*;
+
+use std::collections::BTreeMap;
+
+use crate::buildings::{ building_envelope, BUILDING_SETBACK };
+use crate::measure::Units;
+use crate::roadsystem::{ RoadSystem, RoadType };
+use crate::ui::{ ButtonMaterials, UiContainerWidget };
+use crate::ui::widgets::{ self, Label, Panel };
+
+/// Key figures of the road network
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct Statistics {
+    /// Length of all streets of each road type
+    pub length_by_type: Vec<(RoadType, f32)>,
+
+    /// Number of intersections with the given number of streets
+    pub intersections_by_degree: BTreeMap<usize, usize>,
+    pub block_count: usize,
+    pub average_block_area: f32,
+
+    /// Area of the blocks which is left for buildings after the setback
+    pub buildable_area: f32,
+    pub components: usize
+}
+
+impl Statistics {
+    pub fn of(road_system: &RoadSystem) -> Statistics {
+        let mut length_by_type: Vec<(RoadType, f32)> = RoadType::ALL.iter().map(|road_type| (*road_type, 0.0)).collect();
+
+        for street in road_system.streets() {
+            if let (Some(road_type), Some(line)) = (road_system.road_type(street), road_system.street_line(street)) {
+                for (other, length) in &mut length_by_type {
+                    if *other == road_type {
+                        *length += line.length();
+                    }
+                }
+            }
+        }
+
+        let mut intersections_by_degree = BTreeMap::new();
+        for intersection in road_system.intersections() {
+            *intersections_by_degree.entry(road_system.degree(intersection)).or_insert(0) += 1;
+        }
+
+        let blocks = road_system.blocks();
+        let average_block_area = if blocks.is_empty() {
+            0.0
+        } else {
+            blocks.iter().map(|block| block.area()).sum::<f32>() / blocks.len() as f32
+        };
+
+        let buildable_area = blocks
+            .iter()
+            .flat_map(|block| building_envelope(block, BUILDING_SETBACK))
+            .map(|envelope| envelope.area())
+            .sum();
+
+        Statistics {
+            length_by_type,
+            intersections_by_degree,
+            block_count: blocks.len(),
+            average_block_area,
+            buildable_area,
+            components: road_system.connected_components()
+        }
+    }
+
+    pub fn total_length(&self) -> f32 {
+        self.length_by_type.iter().map(|(_, length)| length).sum()
+    }
+
+    pub fn intersection_count(&self) -> usize {
+        self.intersections_by_degree.values().sum()
+    }
+
+    /// Returns the labels and formatted values shown in the dashboard
+    fn rows(&self, units: &Units) -> Vec<(String, String)> {
+        let mut rows: Vec<(String, String)> = self.length_by_type
+            .iter()
+            .map(|(road_type, length)| (road_type.name().to_string(), units.format_length(*length)))
+            .collect();
+
+        rows.push(("Total length".to_string(), units.format_length(self.total_length())));
+
+        let degrees: Vec<String> = self.intersections_by_degree
+            .iter()
+            .map(|(degree, count)| format!("{}x{}", count, degree))
+            .collect();
+
+        rows.push(("Intersections".to_string(), format!("{} ({})", self.intersection_count(), degrees.join(", "))));
+        rows.push(("Blocks".to_string(), self.block_count.to_string()));
+        rows.push(("Average block".to_string(), units.format_area(self.average_block_area)));
+        rows.push(("Buildable area".to_string(), units.format_area(self.buildable_area)));
+        rows.push(("Networks".to_string(), self.components.to_string()));
+        rows
+    }
+}
+
+/// Marks the labels of the dashboard, which are told apart by their label
+pub struct StatisticsRow;
+
+fn statistics_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    button_materials: Res<ButtonMaterials>,
+    units: Res<Units>
+) {
+    let position = Rect {
+        left: Val::Px(10.0),
+        top: Val::Px(10.0),
+        ..Default::default()
+    };
+
+    let mut panel = Panel::new("Statistics", position).collapsible();
+    for (label, value) in Statistics::of(&RoadSystem::new()).rows(&units) {
+        panel = panel.with(Label::<StatisticsRow>::new(&label, &value));
+    }
+
+    panel.create(&mut commands, &button_materials, &asset_server);
+}
+
+/// Updates the statistics whenever the road network changes
+fn statistics_system(
+    mut statistics: ResMut<Statistics>,
+    mut changed_query: Query<Changed<RoadSystem>>
+) {
+    for road_system in &mut changed_query.iter() {
+        *statistics = Statistics::of(&road_system);
+    }
+}
+
+fn statistics_display_system(
+    statistics: Res<Statistics>,
+    units: Res<Units>,
+    mut label_query: Query<&mut Label<StatisticsRow>>
+) {
+    let rows = statistics.rows(&units);
+
+    for mut label in &mut label_query.iter() {
+        if let Some((_, value)) = rows.iter().find(|(row, _)| *row == label.label) {
+            if label.text != *value {
+                label.text = value.clone();
+            }
+        }
+    }
+}
+
+pub struct StatisticsPlugin;
+
+impl Plugin for StatisticsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<Statistics>();
+        app.add_startup_system(statistics_setup.system());
+        app.add_system(statistics_system.system());
+        app.add_system(statistics_display_system.system());
+        widgets::add_label::<StatisticsRow>(app);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use super::*;
+    use crate::roadsystem::Street;
+
+    #[test]
+    fn statistics_of_two_blocks_and_a_separate_street() {
+        let mut road_system = RoadSystem::new();
+        let streets = [
+            ((0.0, 0.0), (100.0, 0.0)),
+            ((100.0, 0.0), (200.0, 0.0)),
+            ((0.0, 100.0), (100.0, 100.0)),
+            ((100.0, 100.0), (200.0, 100.0)),
+            ((0.0, 0.0), (0.0, 100.0)),
+            ((200.0, 0.0), (200.0, 100.0))
+        ];
+
+        for ((x1, y1), (x2, y2)) in &streets {
+            road_system.insert_street(Vec2::new(*x1, *y1), Vec2::new(*x2, *y2), Street::default());
+        }
+
+        road_system.insert_street(Vec2::new(100.0, 0.0), Vec2::new(100.0, 100.0), Street::new(RoadType::Avenue));
+        road_system.insert_street(Vec2::new(500.0, 0.0), Vec2::new(600.0, 0.0), Street::new(RoadType::Highway));
+
+        let statistics = Statistics::of(&road_system);
+
+        assert_eq!(statistics.length_by_type, vec![
+            (RoadType::Residential, 600.0),
+            (RoadType::Avenue, 100.0),
+            (RoadType::Highway, 100.0)
+        ]);
+        assert_eq!(statistics.total_length(), 800.0);
+        assert_eq!(statistics.intersection_count(), 8);
+        assert_eq!(statistics.intersections_by_degree.get(&1), Some(&2));
+        assert_eq!(statistics.intersections_by_degree.get(&2), Some(&4));
+        assert_eq!(statistics.intersections_by_degree.get(&3), Some(&2));
+        assert_eq!(statistics.block_count, 2);
+        assert!((statistics.average_block_area - 10000.0).abs() < 1e-3);
+        assert!((statistics.buildable_area - 2.0 * 60.0 * 60.0).abs() < 1e-2);
+        assert_eq!(statistics.components, 2);
+    }
+
+    #[test]
+    fn rows_of_empty_network() {
+        let rows = Statistics::of(&RoadSystem::new()).rows(&Units::default());
+
+        assert_eq!(rows[3], ("Total length".to_string(), "0 m".to_string()));
+        assert_eq!(rows[4], ("Intersections".to_string(), "0 ()".to_string()));
+        assert_eq!(rows[8], ("Networks".to_string(), "0".to_string()));
+    }
+}
@@ -0,0 +1,254 @@
+use bevy::prelude::*;
+
+use std::fs;
+use std::path::Path;
+
+use crate::app_state::AppState;
+use crate::input;
+use crate::keybindings::{ Action, Actions };
+use crate::math::offset::{ offset_polyline, EndType, JoinType };
+use crate::roadsystem::{ RoadSystem, RoadTemplate };
+use crate::selection::{ Selection, ROTATION_STEP };
+use crate::shapes::spawn_polygons;
+use crate::theme::ThemeMaterials;
+use crate::ui;
+use crate::ui::notifications::Notifications;
+use crate::view::CameraView;
+
+/// Directory the template library is loaded from
+const TEMPLATE_DIRECTORY: &str = "templates";
+
+/// Reads the template file, errors name the file
+fn load_template(path: &Path) -> Result<RoadTemplate, String> {
+    let text = fs::read_to_string(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+
+    RoadTemplate::parse(&text).map_err(|error| format!("{}: {}", path.display(), error))
+}
+
+/// Returns the first name `template_<number>` which has no file in the directory yet
+fn unused_template_name(directory: &Path) -> String {
+    (1..)
+        .map(|number| format!("template_{}", number))
+        .find(|name| !directory.join(format!("{}.txt", name)).exists())
+        .unwrap()
+}
+
+/// Templates which can be stamped with the template actions
+#[derive(Default)]
+pub struct TemplateLibrary {
+    pub templates: Vec<(String, RoadTemplate)>
+}
+
+impl TemplateLibrary {
+    /// Loads all templates of the directory sorted by their file name. Files which can not
+    /// be read are skipped and returned as errors.
+    pub fn load(directory: &Path) -> (TemplateLibrary, Vec<String>) {
+        let mut paths: Vec<_> = match fs::read_dir(directory) {
+            Ok(entries) => entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect(),
+            Err(_) => Vec::new()
+        };
+        paths.sort();
+
+        let mut library = TemplateLibrary::default();
+        let mut errors = Vec::new();
+        for path in paths {
+            match load_template(&path) {
+                Ok(template) => {
+                    let name = path.file_stem().unwrap().to_string_lossy().to_string();
+                    library.templates.push((name, template));
+                }
+                Err(error) => errors.push(error)
+            }
+        }
+
+        (library, errors)
+    }
+}
+
+/// Template which is pasted by the stamp tool
+#[derive(Default)]
+pub struct Clipboard {
+    pub template: Option<RoadTemplate>,
+    pub rotation: f32,
+
+    /// Position and rotation of the visible preview
+    preview: Option<(Vec2, f32)>
+}
+
+/// Loads the template library and tells the user about the templates which were skipped
+fn template_setup(
+    mut library: ResMut<TemplateLibrary>,
+    mut notifications: ResMut<Notifications>
+) {
+    let (loaded, errors) = TemplateLibrary::load(Path::new(TEMPLATE_DIRECTORY));
+
+    *library = loaded;
+    for error in errors {
+        notifications.warning(&format!("Skipping template {}", error));
+    }
+}
+
+/// Marks the entities showing where the clipboard would be pasted
+pub struct StampPreview;
+
+fn copy_system(
+    app_state: Res<AppState>,
+    mut current_action: ResMut<ui::RoadActions>,
+    actions: Res<Actions>,
+    selection: Res<Selection>,
+    mut clipboard: ResMut<Clipboard>,
+    mut graph_query: Query<&RoadSystem>
+) {
+    if *app_state != AppState::Editing {
+        return;
+    }
+
+    if actions.just_pressed(Action::Copy) && !selection.elements.is_empty() {
+        let elements: Vec<_> = selection.elements.iter().cloned().collect();
+
+        for road_system in &mut graph_query.iter() {
+            clipboard.template = Some(road_system.copy_elements(&elements));
+            clipboard.rotation = 0.0;
+        }
+    }
+
+    if actions.just_pressed(Action::Paste) && clipboard.template.is_some() {
+        *current_action = ui::RoadActions::Stamp;
+    }
+}
+
+fn stamp_system(
+    app_state: Res<AppState>,
+    current_action: Res<ui::RoadActions>,
+    actions: Res<Actions>,
+    state: Res<input::MouseState>,
+    view: Res<CameraView>,
+    mut library: ResMut<TemplateLibrary>,
+    mut clipboard: ResMut<Clipboard>,
+    mut notifications: ResMut<Notifications>,
+    mut graph_query: Query<&mut RoadSystem>
+) {
+    if *app_state != AppState::Editing || *current_action != ui::RoadActions::Stamp {
+        return;
+    }
+
+    for (number, (_, template)) in library.templates.iter().enumerate() {
+        if actions.just_pressed(Action::Template(number + 1)) {
+            clipboard.template = Some(template.clone());
+            clipboard.rotation = 0.0;
+        }
+    }
+
+    if actions.just_pressed(Action::RotateLeft) {
+        clipboard.rotation += ROTATION_STEP;
+    } else if actions.just_pressed(Action::RotateRight) {
+        clipboard.rotation -= ROTATION_STEP;
+    }
+
+    if actions.just_pressed(Action::SaveTemplate) {
+        if let Some(template) = &clipboard.template {
+            // templates which were skipped while loading keep their files
+            let name = unused_template_name(Path::new(TEMPLATE_DIRECTORY));
+            let path = Path::new(TEMPLATE_DIRECTORY).join(format!("{}.txt", name));
+
+            match fs::create_dir_all(TEMPLATE_DIRECTORY).and_then(|_| fs::write(&path, template.to_text())) {
+                Ok(_) => {
+                    notifications.info(&format!("Saved template {}", path.display()));
+                    library.templates.push((name, template.clone()));
+                }
+                Err(error) => notifications.error(&format!("Could not save template {}: {}", path.display(), error))
+            }
+        }
+    }
+
+    if !actions.just_pressed(Action::Primary) {
+        return;
+    }
+
+    if let Some(template) = &clipboard.template {
+        let mouse_pos_ws = crate::mouse_pos_ws(&state, &view);
+
+        for mut road_system in &mut graph_query.iter() {
+            road_system.paste(template, mouse_pos_ws, clipboard.rotation);
+        }
+    }
+}
+
+fn stamp_preview_system(
+    mut commands: Commands,
+    current_action: Res<ui::RoadActions>,
+    state: Res<input::MouseState>,
+    view: Res<CameraView>,
+    mut clipboard: ResMut<Clipboard>,
+    theme_materials: Res<ThemeMaterials>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut preview_query: Query<With<StampPreview, Entity>>
+) {
+    let mouse_pos_ws = crate::mouse_pos_ws(&state, &view);
+
+    let preview = match (&clipboard.template, *current_action) {
+        (Some(_), ui::RoadActions::Stamp) => Some((mouse_pos_ws, clipboard.rotation)),
+        _ => None
+    };
+
+    if preview == clipboard.preview {
+        return;
+    }
+
+    for entity in &mut preview_query.iter() {
+        commands.despawn(entity);
+    }
+
+    clipboard.preview = preview;
+
+    let (position, rotation) = match preview {
+        Some(preview) => preview,
+        None => return
+    };
+
+    let preview_material = theme_materials.preview.clone();
+    let template = clipboard.template.as_ref().unwrap();
+
+    for (first, second, road_type) in &template.streets {
+        let points = [template.place(*first, position, rotation), template.place(*second, position, rotation)];
+
+        let outline = offset_polyline(&points, road_type.width() / 2.0, JoinType::Miter(2.0), EndType::Butt);
+
+        spawn_polygons(&mut commands, &outline, preview_material.clone(), &mut meshes, 2.5)
+        .with(StampPreview);
+    }
+}
+
+pub struct TemplatePlugin;
+
+impl Plugin for TemplatePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<TemplateLibrary>();
+        app.add_startup_system(template_setup.system());
+        app.init_resource::<Clipboard>();
+        app.add_system_to_stage("do_things", copy_system.system());
+        app.add_system_to_stage("do_things", stamp_system.system());
+        app.add_system_to_stage("do_things", stamp_preview_system.system());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn template_names_skip_existing_files() {
+        let directory = std::env::temp_dir().join("unused_template_name");
+        fs::create_dir_all(&directory).unwrap();
+        for name in &["template_1.txt", "template_3.txt"] {
+            fs::write(directory.join(name), "").unwrap();
+        }
+
+        assert_eq!(unused_template_name(&directory), "template_2");
+
+        fs::write(directory.join("template_2.txt"), "").unwrap();
+        assert_eq!(unused_template_name(&directory), "template_4");
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,448 @@
+use bevy::{
+    prelude::*,
+    render::pass::ClearColor,
+};
+
+use std::fs;
+
+use crate::keybindings::{ Action, Actions };
+use crate::ui::{ ButtonMaterials, PanelText };
+use crate::ui::notifications::{ Notifications, Severity };
+
+/// File a custom theme is loaded from
+const THEME_FILE: &str = "theme.txt";
+
+/// Colors of the world and the user interface
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub background: Color,
+    pub street: Color,
+    pub intersection: Color,
+
+    /// Street which is currently built
+    pub temp_street: Color,
+
+    /// Streets of stamps and generators before they are placed
+    pub preview: Color,
+    pub selection: Color,
+    pub hover: Color,
+
+    /// Measured distances and routes
+    pub route: Color,
+
+    /// Measured blocks
+    pub area: Color,
+
+    /// Text shown directly over the world
+    pub label: Color,
+    pub minimap_background: Color,
+    pub minimap_street: Color,
+    pub button: Color,
+    pub button_hovered: Color,
+    pub button_pressed: Color,
+    pub panel: Color,
+
+    /// Darkens the world while a menu is shown
+    pub overlay: Color,
+
+    /// Text shown on panels and buttons
+    pub text: Color,
+    pub info: Color,
+    pub warning: Color,
+    pub error: Color
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::light()
+    }
+}
+
+impl Theme {
+    pub fn light() -> Theme {
+        Theme {
+            name: "light".to_string(),
+            background: Color::rgb(0.9, 0.9, 0.9),
+            street: Color::rgb(0.1, 0.4, 0.5),
+            intersection: Color::rgb(0.0, 0.0, 1.0),
+            temp_street: Color::rgb(0.0, 0.1, 0.0),
+            preview: Color::rgb(0.6, 0.6, 0.6),
+            selection: Color::rgb(1.0, 0.6, 0.0),
+            hover: Color::rgb(1.0, 0.9, 0.3),
+            route: Color::rgb(0.8, 0.1, 0.1),
+            area: Color::rgb(0.6, 0.85, 0.6),
+            label: Color::rgb(0.1, 0.1, 0.1),
+            minimap_background: Color::rgb(0.2, 0.2, 0.2),
+            minimap_street: Color::rgb(0.8, 0.8, 0.8),
+            button: Color::rgb(0.15, 0.15, 0.15),
+            button_hovered: Color::rgb(0.25, 0.25, 0.25),
+            button_pressed: Color::rgb(0.35, 0.75, 0.35),
+            panel: Color::rgb(0.3, 0.4, 0.8),
+            overlay: Color::rgba(0.0, 0.0, 0.0, 0.5),
+            text: Color::rgb(0.9, 0.9, 0.9),
+            info: Color::rgb(0.1, 0.1, 0.1),
+            warning: Color::rgb(0.8, 0.45, 0.0),
+            error: Color::rgb(0.8, 0.1, 0.1)
+        }
+    }
+
+    pub fn dark() -> Theme {
+        Theme {
+            name: "dark".to_string(),
+            background: Color::rgb(0.12, 0.12, 0.14),
+            street: Color::rgb(0.35, 0.55, 0.65),
+            intersection: Color::rgb(0.3, 0.45, 0.9),
+            temp_street: Color::rgb(0.7, 0.8, 0.7),
+            preview: Color::rgb(0.45, 0.45, 0.45),
+            selection: Color::rgb(1.0, 0.6, 0.0),
+            hover: Color::rgb(1.0, 0.9, 0.3),
+            route: Color::rgb(1.0, 0.35, 0.35),
+            area: Color::rgb(0.25, 0.45, 0.3),
+            label: Color::rgb(0.9, 0.9, 0.9),
+            minimap_background: Color::rgb(0.05, 0.05, 0.05),
+            minimap_street: Color::rgb(0.6, 0.6, 0.6),
+            button: Color::rgb(0.22, 0.22, 0.22),
+            button_hovered: Color::rgb(0.32, 0.32, 0.32),
+            button_pressed: Color::rgb(0.3, 0.6, 0.3),
+            panel: Color::rgb(0.2, 0.25, 0.4),
+            overlay: Color::rgba(0.0, 0.0, 0.0, 0.6),
+            text: Color::rgb(0.9, 0.9, 0.9),
+            info: Color::rgb(0.9, 0.9, 0.9),
+            warning: Color::rgb(1.0, 0.7, 0.2),
+            error: Color::rgb(1.0, 0.4, 0.4)
+        }
+    }
+
+    /// Light theme with the Okabe-Ito palette, which avoids colors that can only be told
+    /// apart by red and green
+    pub fn color_blind() -> Theme {
+        Theme {
+            name: "color_blind".to_string(),
+            background: Color::rgb(0.95, 0.95, 0.95),
+            street: Color::rgb(0.0, 0.45, 0.7),
+            intersection: Color::rgb(0.1, 0.1, 0.1),
+            temp_street: Color::rgb(0.0, 0.62, 0.45),
+            preview: Color::rgb(0.6, 0.6, 0.6),
+            selection: Color::rgb(0.9, 0.62, 0.0),
+            hover: Color::rgb(0.94, 0.89, 0.26),
+            route: Color::rgb(0.84, 0.37, 0.0),
+            area: Color::rgb(0.34, 0.71, 0.91),
+            label: Color::rgb(0.1, 0.1, 0.1),
+            minimap_background: Color::rgb(0.2, 0.2, 0.2),
+            minimap_street: Color::rgb(0.8, 0.8, 0.8),
+            button: Color::rgb(0.15, 0.15, 0.15),
+            button_hovered: Color::rgb(0.3, 0.3, 0.3),
+            button_pressed: Color::rgb(0.0, 0.45, 0.7),
+            panel: Color::rgb(0.25, 0.25, 0.25),
+            overlay: Color::rgba(0.0, 0.0, 0.0, 0.5),
+            text: Color::rgb(0.95, 0.95, 0.95),
+            info: Color::rgb(0.1, 0.1, 0.1),
+            warning: Color::rgb(0.9, 0.62, 0.0),
+            error: Color::rgb(0.84, 0.37, 0.0)
+        }
+    }
+
+    /// Returns the built-in theme with the name
+    pub fn built_in(name: &str) -> Option<Theme> {
+        [Theme::light(), Theme::dark(), Theme::color_blind()]
+            .iter()
+            .find(|theme| theme.name == name)
+            .cloned()
+    }
+
+    pub fn message_color(&self, severity: Severity) -> Color {
+        match severity {
+            Severity::Info => self.info,
+            Severity::Warning => self.warning,
+            Severity::Error => self.error
+        }
+    }
+
+    fn color_mut(&mut self, name: &str) -> Option<&mut Color> {
+        match name {
+            "background" => Some(&mut self.background),
+            "street" => Some(&mut self.street),
+            "intersection" => Some(&mut self.intersection),
+            "temp_street" => Some(&mut self.temp_street),
+            "preview" => Some(&mut self.preview),
+            "selection" => Some(&mut self.selection),
+            "hover" => Some(&mut self.hover),
+            "route" => Some(&mut self.route),
+            "area" => Some(&mut self.area),
+            "label" => Some(&mut self.label),
+            "minimap_background" => Some(&mut self.minimap_background),
+            "minimap_street" => Some(&mut self.minimap_street),
+            "button" => Some(&mut self.button),
+            "button_hovered" => Some(&mut self.button_hovered),
+            "button_pressed" => Some(&mut self.button_pressed),
+            "panel" => Some(&mut self.panel),
+            "overlay" => Some(&mut self.overlay),
+            "text" => Some(&mut self.text),
+            "info" => Some(&mut self.info),
+            "warning" => Some(&mut self.warning),
+            "error" => Some(&mut self.error),
+            _ => None
+        }
+    }
+
+    /// Reads a theme which changes the colors of a built-in theme, one per line:
+    ///
+    /// ```text
+    /// palette <light|dark|color_blind>
+    /// <color> <red> <green> <blue> [alpha]
+    /// ```
+    ///
+    /// Components range from 0 to 1, colors are opaque without alpha. The colors override the
+    /// palette on any line, at most one palette can be chosen. Empty lines and lines starting
+    /// with `#` are ignored.
+    pub fn parse(text: &str) -> Result<Theme, String> {
+        let mut palette = None;
+        let mut colors = Vec::new();
+
+        for (number, line) in text.lines().enumerate() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let error = || format!("invalid line {}: {}", number + 1, line);
+
+            match words.as_slice() {
+                [] => (),
+                [comment, ..] if comment.starts_with('#') => (),
+                ["palette", name] if palette.is_none() => palette = Some(Theme::built_in(name).ok_or_else(error)?),
+                [name, components @ ..] if components.len() == 3 || components.len() == 4 => {
+                    let components = components
+                        .iter()
+                        .map(|component| component.parse::<f32>().map_err(|_| error()))
+                        .collect::<Result<Vec<f32>, String>>()?;
+                    let alpha = components.get(3).cloned().unwrap_or(1.0);
+
+                    // the colors are applied once the palette is known
+                    if Theme::light().color_mut(name).is_none() {
+                        return Err(error());
+                    }
+
+                    colors.push((*name, Color::rgba(components[0], components[1], components[2], alpha)));
+                }
+                _ => return Err(error())
+            }
+        }
+
+        let mut theme = Theme { name: "custom".to_string(), ..palette.unwrap_or_else(Theme::light) };
+
+        for (name, color) in colors {
+            if let Some(target) = theme.color_mut(name) {
+                *target = color;
+            }
+        }
+
+        Ok(theme)
+    }
+}
+
+/// Themes which can be switched between, the custom theme of the user first
+pub struct Themes {
+    pub themes: Vec<Theme>,
+    pub current: usize
+}
+
+impl Default for Themes {
+    fn default() -> Themes {
+        Themes { themes: vec![Theme::light(), Theme::dark(), Theme::color_blind()], current: 0 }
+    }
+}
+
+impl Themes {
+    /// Loads the custom theme of the user in front of the built-in themes. Nothing changes if
+    /// the file does not exist or the theme is invalid.
+    pub fn load_custom(&mut self) -> Result<(), String> {
+        let text = match fs::read_to_string(THEME_FILE) {
+            Ok(text) => text,
+            Err(_) => return Ok(())
+        };
+
+        let theme = Theme::parse(&text).map_err(|error| format!("{}: {}", THEME_FILE, error))?;
+        self.themes.insert(0, theme);
+
+        Ok(())
+    }
+
+    pub fn current(&self) -> &Theme {
+        &self.themes[self.current]
+    }
+
+    pub fn next(&mut self) {
+        self.current = (self.current + 1) % self.themes.len();
+    }
+}
+
+/// Materials of the world, which change their color with the theme
+pub struct ThemeMaterials {
+    pub street: Handle<ColorMaterial>,
+    pub intersection: Handle<ColorMaterial>,
+    pub temp_street: Handle<ColorMaterial>,
+    pub preview: Handle<ColorMaterial>,
+    pub selection: Handle<ColorMaterial>,
+    pub hover: Handle<ColorMaterial>,
+    pub route: Handle<ColorMaterial>,
+    pub area: Handle<ColorMaterial>,
+    pub overlay: Handle<ColorMaterial>
+}
+
+impl FromResources for ThemeMaterials {
+    fn from_resources(resources: &Resources) -> Self {
+        let theme = resources.get::<Theme>().unwrap();
+        let mut materials = resources.get_mut::<Assets<ColorMaterial>>().unwrap();
+
+        ThemeMaterials {
+            street: materials.add(theme.street.into()),
+            intersection: materials.add(theme.intersection.into()),
+            temp_street: materials.add(theme.temp_street.into()),
+            preview: materials.add(theme.preview.into()),
+            selection: materials.add(theme.selection.into()),
+            hover: materials.add(theme.hover.into()),
+            route: materials.add(theme.route.into()),
+            area: materials.add(theme.area.into()),
+            overlay: materials.add(theme.overlay.into())
+        }
+    }
+}
+
+/// Theme whose colors were applied to the materials and texts
+#[derive(Default)]
+struct ShownTheme(Option<Theme>);
+
+/// Switches to the next theme and tells the user its name
+pub fn show_next_theme(themes: &mut Themes, theme: &mut Theme, notifications: &mut Notifications) {
+    themes.next();
+    *theme = themes.current().clone();
+    notifications.info(&format!("Theme {}", theme.name));
+}
+
+fn next_theme_system(
+    actions: Res<Actions>,
+    mut themes: ResMut<Themes>,
+    mut theme: ResMut<Theme>,
+    mut notifications: ResMut<Notifications>
+) {
+    if actions.just_pressed(Action::NextTheme) {
+        show_next_theme(&mut themes, &mut theme, &mut notifications);
+    }
+}
+
+/// Shows the custom theme of the user and tells the user why it could not be loaded
+fn theme_setup(
+    mut themes: ResMut<Themes>,
+    mut theme: ResMut<Theme>,
+    mut notifications: ResMut<Notifications>
+) {
+    match themes.load_custom() {
+        Ok(()) => *theme = themes.current().clone(),
+        Err(error) => notifications.error(&format!("Could not load theme {}", error))
+    }
+}
+
+/// Recolors the materials and the background when the theme changes
+fn theme_system(
+    theme: Res<Theme>,
+    mut shown: ResMut<ShownTheme>,
+    mut clear_color: ResMut<ClearColor>,
+    theme_materials: Res<ThemeMaterials>,
+    button_materials: Res<ButtonMaterials>,
+    mut materials: ResMut<Assets<ColorMaterial>>
+) {
+    if shown.0.as_ref() == Some(&*theme) {
+        return;
+    }
+
+    clear_color.0 = theme.background;
+
+    let colors = [
+        (&theme_materials.street, theme.street),
+        (&theme_materials.intersection, theme.intersection),
+        (&theme_materials.temp_street, theme.temp_street),
+        (&theme_materials.preview, theme.preview),
+        (&theme_materials.selection, theme.selection),
+        (&theme_materials.hover, theme.hover),
+        (&theme_materials.route, theme.route),
+        (&theme_materials.area, theme.area),
+        (&theme_materials.overlay, theme.overlay),
+        (&button_materials.normal, theme.button),
+        (&button_materials.hovered, theme.button_hovered),
+        (&button_materials.pressed, theme.button_pressed),
+        (&button_materials.background, theme.panel)
+    ];
+
+    for (handle, color) in colors.iter() {
+        if let Some(material) = materials.get_mut(handle) {
+            material.color = *color;
+        }
+    }
+
+    shown.0 = Some(theme.clone());
+}
+
+/// Recolors the texts of the panels, which are created in the color of the default theme.
+/// Texts over the world are colored by the systems showing them.
+fn theme_text_system(
+    theme: Res<Theme>,
+    mut text_query: Query<With<PanelText, &mut Text>>
+) {
+    for mut text in &mut text_query.iter() {
+        if text.style.color != theme.text {
+            text.style.color = theme.text;
+        }
+    }
+}
+
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_resource(ClearColor(Theme::default().background));
+        app.init_resource::<Theme>();
+        app.init_resource::<Themes>();
+        app.init_resource::<ShownTheme>();
+        app.init_resource::<ThemeMaterials>();
+        app.add_startup_system(theme_setup.system());
+        app.add_system(next_theme_system.system());
+        app.add_system(theme_system.system());
+        app.add_system(theme_text_system.system());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use super::*;
+
+    #[test]
+    fn custom_theme_changes_a_palette() {
+        let theme = Theme::parse("# dark with red streets\npalette dark\n\nstreet 1 0 0.5\n").unwrap();
+
+        assert_eq!(theme.name, "custom");
+        assert_eq!(theme.street, Color::rgb(1.0, 0.0, 0.5));
+        assert_eq!(Theme::parse("overlay 0 0 0 0.3").unwrap().overlay, Color::rgba(0.0, 0.0, 0.0, 0.3));
+        assert_eq!(theme.background, Theme::dark().background);
+        assert_eq!(Theme::parse(""), Ok(Theme { name: "custom".to_string(), ..Theme::light() }));
+    }
+
+    #[test]
+    fn colors_override_the_palette_on_any_line() {
+        let before = Theme::parse("street 1 0 0\npalette dark\nbackground 0 0 1").unwrap();
+        let after = Theme::parse("palette dark\nstreet 1 0 0\nbackground 0 0 1").unwrap();
+
+        assert_eq!(before, after);
+        assert_eq!(before.street, Color::rgb(1.0, 0.0, 0.0));
+        assert_eq!(before.background, Color::rgb(0.0, 0.0, 1.0));
+        assert_eq!(before.panel, Theme::dark().panel);
+    }
+
+    #[test]
+    fn reject_invalid_themes() {
+        assert!(Theme::parse("palette sepia").is_err());
+        assert!(Theme::parse("palette dark\npalette light").is_err());
+        assert!(Theme::parse("sky 0 0 1").is_err());
+        assert!(Theme::parse("street 0 0").is_err());
+        assert!(Theme::parse("street 0 0 0 1 1").is_err());
+        assert!(Theme::parse("street red 0 0").is_err());
+    }
+}
@@ -0,0 +1,183 @@
+use bevy::prelude::*;
+
+pub mod notifications;
+pub mod widgets;
+
+use crate::app_state::AppState;
+use crate::keybindings::{ Action, Actions };
+use crate::theme::Theme;
+
+use widgets::ToggleGroup;
+
+struct SingleActionSelection;
+
+/// Widget showing or editing a value of type T, created as child of another node
+pub trait UiWidget<T> {
+    fn create(&self, child_builder: &mut ChildBuilder, materials: &Res<ButtonMaterials>, asset_server: &Res<AssetServer>);
+}
+
+/// Widget which is created as root node containing other widgets
+pub trait UiContainerWidget<T> {
+    fn create(&self, commands: &mut Commands, materials: &Res<ButtonMaterials>, asset_server: &Res<AssetServer>);
+}
+
+
+impl UiContainerWidget<RoadActions> for SingleActionSelection{
+    fn create(&self, commands: &mut Commands, materials: &Res<ButtonMaterials>, asset_server: &Res<AssetServer>) {
+        commands.spawn(NodeComponents {
+            style: Style {
+                size: Size::new(Val::Percent(60.0), Val::Px(60.0)),
+                justify_content: JustifyContent::FlexStart,
+                align_items: AlignItems::FlexEnd,                
+                ..Default::default()
+            },
+            material: materials.background.clone(),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            ToggleGroup::new(vec![
+                (RoadActions::Select, "Select"),
+                (RoadActions::Build, "Build"),
+                (RoadActions::Polyline, "Polyline"),
+                (RoadActions::Demolish, "Remove"),
+                (RoadActions::Upgrade, "Upgrade"),
+                (RoadActions::Stamp, "Stamp"),
+                (RoadActions::Parallel, "Parallel"),
+                (RoadActions::Grid, "Grid"),
+                (RoadActions::Roundabout, "Roundabout"),
+                (RoadActions::Measure, "Measure")
+            ])
+            .create(parent, materials, asset_server);
+        });
+    }
+}
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum RoadActions {
+    Nothing,
+    Select,
+    Build,
+    Polyline,
+    Demolish,
+    Upgrade,
+    Stamp,
+    Parallel,
+    Grid,
+    Roundabout,
+    Measure
+}
+
+impl Default for RoadActions {
+    fn default() -> RoadActions {
+        RoadActions::Nothing
+    }
+}
+
+pub enum ToggleState {
+    Normal,
+    Toggled
+}
+
+pub struct ToggleButton {
+    pub state: ToggleState
+}
+
+pub struct ButtonMaterials {
+    pub normal: Handle<ColorMaterial>,
+    pub hovered: Handle<ColorMaterial>,
+    pub pressed: Handle<ColorMaterial>,
+    pub background: Handle<ColorMaterial>
+}
+
+impl FromResources for ButtonMaterials {
+    fn from_resources(resources: &Resources) -> Self {
+        let theme = resources.get::<Theme>().unwrap();
+        let mut materials = resources.get_mut::<Assets<ColorMaterial>>().unwrap();
+        ButtonMaterials {
+            normal: materials.add(theme.button.into()),
+            hovered: materials.add(theme.button_hovered.into()),
+            pressed: materials.add(theme.button_pressed.into()),
+            background: materials.add(theme.panel.into()),
+        }
+    }
+}
+
+/// Marks the texts on panels and buttons, which are shown in the text color of the theme
+pub struct PanelText;
+
+/// Text on the ui materials in the color of the default theme. Texts marked as panel texts
+/// are recolored when another theme is shown.
+pub fn ui_text(value: &str, asset_server: &Res<AssetServer>) -> Text {
+    Text {
+        value: value.to_string(),
+        font: asset_server.load("fonts/FiraSans-Bold.ttf").unwrap(),
+        style: TextStyle {
+            font_size: 16.0,
+            color: Theme::default().text,
+        },
+    }
+}
+
+/// Style of panels floating over the world at the given position
+pub fn panel_style(position: Rect<Val>) -> Style {
+    Style {
+        position_type: PositionType::Absolute,
+        position,
+        padding: Rect::all(Val::Px(5.0)),
+        align_items: AlignItems::Center,
+        ..Default::default()
+    }
+}
+
+fn ui_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    button_materials: Res<ButtonMaterials>
+) {
+    let action_container = SingleActionSelection;
+    action_container.create(&mut commands, &button_materials, &asset_server);
+}
+
+const TOOL_SHORTCUTS: &[(Action, RoadActions)] = &[
+    (Action::SelectTool, RoadActions::Select),
+    (Action::BuildTool, RoadActions::Build),
+    (Action::PolylineTool, RoadActions::Polyline),
+    (Action::DemolishTool, RoadActions::Demolish),
+    (Action::UpgradeTool, RoadActions::Upgrade),
+    (Action::StampTool, RoadActions::Stamp),
+    (Action::ParallelTool, RoadActions::Parallel),
+    (Action::GridTool, RoadActions::Grid),
+    (Action::RoundaboutTool, RoadActions::Roundabout),
+    (Action::MeasureTool, RoadActions::Measure)
+];
+
+/// Toggles a road action by its shortcut like the buttons of the toolbar
+fn tool_shortcut_system(
+    app_state: Res<AppState>,
+    actions: Res<Actions>,
+    mut current_action: ResMut<RoadActions>
+) {
+    if *app_state != AppState::Editing {
+        return;
+    }
+
+    let pressed = TOOL_SHORTCUTS
+        .iter()
+        .filter(|(action, _)| actions.just_pressed(*action))
+        .map(|(_, road_action)| *road_action);
+
+    if let Some(road_action) = widgets::single_click(pressed) {
+        *current_action = widgets::toggle(*current_action, road_action);
+    }
+}
+
+/// Toolbar selecting the current road action
+pub struct ActionSelectionPlugin;
+
+impl Plugin for ActionSelectionPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<RoadActions>();
+        app.add_startup_system(ui_setup.system());
+        app.add_system_to_stage("ui_handling", tool_shortcut_system.system());
+        widgets::add_toggle_group::<RoadActions>(app);
+    }
+}
@@ -0,0 +1,338 @@
+use bevy::{
+    prelude::*,
+    input::mouse::MouseWheel,
+};
+
+use super::{ ui_text, ButtonMaterials, PanelText, UiContainerWidget, UiWidget };
+use super::widgets::Panel;
+use crate::theme::Theme;
+
+/// Time a toast is shown, in seconds
+const TOAST_DURATION: f64 = 4.0;
+
+/// Older toasts are removed early if more are shown
+const MAX_TOASTS: usize = 4;
+
+/// Distance of the lowest toast to the bottom of the window, above the toolbar
+const TOAST_BOTTOM: f32 = 80.0;
+const TOAST_HEIGHT: f32 = 24.0;
+
+/// Older messages are removed from the log
+const MAX_MESSAGES: usize = 200;
+
+/// Number of messages visible in the log at once
+const LOG_LINES: usize = 8;
+const LOG_WIDTH: f32 = 400.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error
+}
+
+impl Severity {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Info => "Info",
+            Severity::Warning => "Warning",
+            Severity::Error => "Error"
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Message {
+    pub severity: Severity,
+    pub text: String
+}
+
+impl Message {
+    fn log_text(&self) -> String {
+        format!("{}: {}", self.severity.name(), self.text)
+    }
+}
+
+/// Messages for the user. Any system can add messages, which are shown as toast for a short
+/// time and kept in the message log.
+#[derive(Default)]
+pub struct Notifications {
+    messages: Vec<Message>,
+
+    /// Messages which are not shown as toast yet
+    new: Vec<Message>,
+
+    /// Number of messages the log is scrolled back from the newest one
+    scroll: usize
+}
+
+impl Notifications {
+    pub fn push(&mut self, severity: Severity, text: &str) {
+        let message = Message { severity, text: text.to_string() };
+
+        self.messages.push(message.clone());
+        if self.messages.len() > MAX_MESSAGES {
+            self.messages.remove(0);
+        }
+
+        // a scrolled log keeps showing the same messages
+        if self.scroll > 0 {
+            self.scroll = (self.scroll + 1).min(self.max_scroll());
+        }
+
+        self.new.push(message);
+    }
+
+    pub fn info(&mut self, text: &str) {
+        self.push(Severity::Info, text);
+    }
+
+    pub fn warning(&mut self, text: &str) {
+        self.push(Severity::Warning, text);
+    }
+
+    pub fn error(&mut self, text: &str) {
+        self.push(Severity::Error, text);
+    }
+
+    /// Returns the messages shown in the log, the newest one last
+    pub fn visible(&self) -> &[Message] {
+        let end = self.messages.len() - self.scroll;
+        let start = end.saturating_sub(LOG_LINES);
+
+        &self.messages[start..end]
+    }
+
+    /// Scrolls the log back to older messages or forward to newer ones
+    pub fn scroll_by(&mut self, lines: isize) {
+        let scroll = (self.scroll as isize + lines).max(0) as usize;
+        self.scroll = scroll.min(self.max_scroll());
+    }
+
+    fn max_scroll(&self) -> usize {
+        self.messages.len().saturating_sub(LOG_LINES)
+    }
+
+    fn take_new(&mut self) -> Vec<Message> {
+        std::mem::take(&mut self.new)
+    }
+}
+
+/// Message shown over the world until it expires
+pub struct Toast {
+    severity: Severity,
+    expires: f64
+}
+
+/// Lines of the message log, which is scrolled with the mouse wheel while hovered
+pub struct MessageLog;
+
+/// Line of the message log with its index from the top
+pub struct MessageLine(usize);
+
+impl UiWidget<Notifications> for MessageLog {
+    fn create(&self, child_builder: &mut ChildBuilder, materials: &Res<ButtonMaterials>, asset_server: &Res<AssetServer>) {
+        child_builder
+        .spawn(ButtonComponents {
+            style: Style {
+                size: Size::new(Val::Px(LOG_WIDTH), Val::Px(LOG_LINES as f32 * 20.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::FlexStart,
+                ..Default::default()
+            },
+            material: materials.background.clone(),
+            ..Default::default()
+        })
+        .with(MessageLog)
+        .with_children(|parent| {
+            for line in 0..LOG_LINES {
+                parent
+                .spawn(TextComponents {
+                    text: ui_text("", asset_server),
+                    ..Default::default()
+                })
+                .with(MessageLine(line))
+                .with(PanelText);
+            }
+        });
+    }
+}
+
+fn notification_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    button_materials: Res<ButtonMaterials>
+) {
+    let position = Rect {
+        left: Val::Percent(35.0),
+        top: Val::Px(10.0),
+        ..Default::default()
+    };
+
+    Panel::new("Messages", position)
+    .collapsible()
+    .with(MessageLog)
+    .create(&mut commands, &button_materials, &asset_server);
+}
+
+/// Shows new messages as toasts and removes expired ones
+fn toast_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    mut notifications: ResMut<Notifications>,
+    theme: Res<Theme>,
+    mut toast_query: Query<(Entity, &Toast, &mut Style, &mut Text)>
+) {
+    let now = time.seconds_since_startup;
+
+    let mut toasts: Vec<(Entity, f64)> = Vec::new();
+    for (entity, toast, _, mut text) in &mut toast_query.iter() {
+        // the theme may have changed since the toast was shown
+        let color = theme.message_color(toast.severity);
+        if text.style.color != color {
+            text.style.color = color;
+        }
+
+        toasts.push((entity, toast.expires));
+    }
+    toasts.sort_by(|(_, first), (_, second)| first.partial_cmp(second).unwrap());
+
+    // taking the messages marks the notifications as changed, which redraws the log
+    let new = if notifications.new.is_empty() {
+        Vec::new()
+    } else {
+        notifications.take_new()
+    };
+
+    // the oldest toasts make room for new ones
+    let kept = MAX_TOASTS.saturating_sub(new.len());
+    let overflow = toasts.len().saturating_sub(kept);
+
+    let mut shown = Vec::new();
+    for (index, (entity, expires)) in toasts.iter().enumerate() {
+        if index < overflow || *expires <= now {
+            commands.despawn(*entity);
+        } else {
+            shown.push(*entity);
+        }
+    }
+
+    for (index, entity) in shown.iter().enumerate() {
+        if let Ok(mut style) = toast_query.get_mut::<Style>(*entity) {
+            style.position.bottom = Val::Px(TOAST_BOTTOM + index as f32 * TOAST_HEIGHT);
+        }
+    }
+
+    let skipped = new.len().saturating_sub(MAX_TOASTS);
+    for (index, message) in new.iter().skip(skipped).enumerate() {
+        let mut text = ui_text(&message.text, &asset_server);
+        text.style.color = theme.message_color(message.severity);
+
+        commands
+        .spawn(TextComponents {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    left: Val::Percent(35.0),
+                    bottom: Val::Px(TOAST_BOTTOM + (shown.len() + index) as f32 * TOAST_HEIGHT),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text,
+            ..Default::default()
+        })
+        .with(Toast { severity: message.severity, expires: now + TOAST_DURATION });
+    }
+}
+
+fn message_log_scroll_system(
+    mut wheel_reader: Local<EventReader<MouseWheel>>,
+    wheel_events: Res<Events<MouseWheel>>,
+    mut notifications: ResMut<Notifications>,
+    mut log_query: Query<With<MessageLog, &Interaction>>
+) {
+    let lines: f32 = wheel_reader.iter(&wheel_events).map(|event| event.y).sum();
+
+    let mut hovered = false;
+    for interaction in &mut log_query.iter() {
+        hovered |= *interaction != Interaction::None;
+    }
+
+    if hovered && lines != 0.0 {
+        notifications.scroll_by(lines.round() as isize);
+    }
+}
+
+fn message_log_display_system(
+    notifications: ChangedRes<Notifications>,
+    mut line_query: Query<(&MessageLine, &mut Text)>
+) {
+    let visible = notifications.visible();
+
+    for (line, mut text) in &mut line_query.iter() {
+        text.value = match visible.get(line.0) {
+            Some(message) => message.log_text(),
+            None => String::new()
+        };
+    }
+}
+
+pub struct NotificationPlugin;
+
+impl Plugin for NotificationPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<Notifications>();
+        app.add_startup_system(notification_setup.system());
+        app.add_system_to_stage("ui_handling", message_log_scroll_system.system());
+        app.add_system(toast_system.system());
+        app.add_system(message_log_display_system.system());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_with(count: usize) -> Notifications {
+        let mut notifications = Notifications::default();
+        for number in 0..count {
+            notifications.info(&number.to_string());
+        }
+
+        notifications
+    }
+
+    #[test]
+    fn log_shows_the_newest_messages() {
+        let mut notifications = log_with(3);
+        notifications.error("failed");
+
+        let visible = notifications.visible();
+        assert_eq!(visible.len(), 4);
+        assert_eq!(visible[3], Message { severity: Severity::Error, text: "failed".to_string() });
+        assert_eq!(visible[3].log_text(), "Error: failed");
+
+        assert_eq!(notifications.take_new().len(), 4);
+        assert!(notifications.take_new().is_empty());
+    }
+
+    #[test]
+    fn scrolled_log_stays_at_its_messages() {
+        let mut notifications = log_with(20);
+
+        notifications.scroll_by(-3);
+        assert_eq!(notifications.visible()[LOG_LINES - 1].text, "19");
+
+        notifications.scroll_by(5);
+        assert_eq!(notifications.visible()[LOG_LINES - 1].text, "14");
+
+        notifications.warning("new");
+        assert_eq!(notifications.visible()[LOG_LINES - 1].text, "14");
+
+        notifications.scroll_by(100);
+        assert_eq!(notifications.visible()[0].text, "0");
+        assert_eq!(notifications.visible().len(), LOG_LINES);
+    }
+}
@@ -0,0 +1,1075 @@
+use bevy::{
+    prelude::*,
+    window::ReceivedCharacter,
+};
+
+use std::marker::PhantomData;
+
+use crate::input;
+use super::{ panel_style, ui_text, ButtonMaterials, PanelText, ToggleButton, ToggleState, UiContainerWidget, UiWidget };
+
+/// Height of a single row of a panel
+const ROW_HEIGHT: f32 = 30.0;
+
+/// Width of sliders, dropdowns and numeric fields
+const FIELD_WIDTH: f32 = 150.0;
+
+fn button_style(width: f32) -> Style {
+    Style {
+        size: Size::new(Val::Px(width), Val::Px(ROW_HEIGHT)),
+        margin: Rect::all(Val::Px(2.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..Default::default()
+    }
+}
+
+/// Row of a panel with a label in front of the widget
+fn row(
+    child_builder: &mut ChildBuilder,
+    label: &str,
+    materials: &Res<ButtonMaterials>,
+    asset_server: &Res<AssetServer>,
+    spawn_widget: impl FnOnce(&mut ChildBuilder)
+) {
+    child_builder
+    .spawn(NodeComponents {
+        style: Style {
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        material: materials.background.clone(),
+        ..Default::default()
+    })
+    .with_children(|parent| {
+        parent
+        .spawn(TextComponents {
+            style: Style {
+                size: Size::new(Val::Px(110.0), Val::Px(ROW_HEIGHT)),
+                ..Default::default()
+            },
+            text: ui_text(label, asset_server),
+            ..Default::default()
+        })
+        .with(PanelText);
+
+        spawn_widget(parent);
+    });
+}
+
+/// Marks buttons which are highlighted while hovered
+pub struct HoverFeedback;
+
+/// Button with a text which sends its value as event when it is clicked
+pub struct LabelledButton<T> {
+    pub label: String,
+    pub value: T
+}
+
+impl<T> LabelledButton<T> {
+    pub fn new(label: &str, value: T) -> LabelledButton<T> {
+        LabelledButton { label: label.to_string(), value }
+    }
+}
+
+/// Value sent by a labelled button
+pub struct ButtonValue<T>(pub T);
+
+impl<T: Clone + Send + Sync + 'static> UiWidget<T> for LabelledButton<T> {
+    fn create(&self, child_builder: &mut ChildBuilder, materials: &Res<ButtonMaterials>, asset_server: &Res<AssetServer>) {
+        child_builder
+        .spawn(ButtonComponents {
+            style: button_style(FIELD_WIDTH),
+            material: materials.normal.clone(),
+            ..Default::default()
+        })
+        .with(ButtonValue(self.value.clone()))
+        .with(HoverFeedback)
+        .with_children(|parent| {
+            parent
+            .spawn(TextComponents {
+                text: ui_text(&self.label, asset_server),
+                ..Default::default()
+            })
+            .with(PanelText);
+        });
+    }
+}
+
+pub fn button_event_system<T: Clone + Send + Sync + 'static>(
+    mut events: ResMut<Events<T>>,
+    mut interaction_query: Query<(&ButtonValue<T>, Mutated<Interaction>)>
+) {
+    for (value, interaction) in &mut interaction_query.iter() {
+        if let Interaction::Clicked = *interaction {
+            events.send(value.0.clone());
+        }
+    }
+}
+
+/// Exclusive selection of one of the values of an enum. The selected value is the resource
+/// of type T.
+pub struct ToggleGroup<T> {
+    pub options: Vec<(T, String)>
+}
+
+impl<T> ToggleGroup<T> {
+    pub fn new(options: Vec<(T, &str)>) -> ToggleGroup<T> {
+        ToggleGroup {
+            options: options.into_iter().map(|(value, label)| (value, label.to_string())).collect()
+        }
+    }
+}
+
+impl<T: Copy + Send + Sync + 'static> UiWidget<T> for ToggleGroup<T> {
+    fn create(&self, child_builder: &mut ChildBuilder, materials: &Res<ButtonMaterials>, asset_server: &Res<AssetServer>) {
+        for (value, label) in &self.options {
+            child_builder
+            .spawn(ButtonComponents {
+                style: Style {
+                    size: Size::new(Val::Px(80.0), Val::Px(45.0)),
+                    // center button
+                    margin: Rect::all(Val::Auto),
+                    // horizontally center child text
+                    justify_content: JustifyContent::Center,
+                    // vertically center child text
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                material: materials.normal.clone(),
+                ..Default::default()
+            })
+            .with(ToggleButton {
+                state: ToggleState::Normal
+            })
+            .with(*value)
+            .with_children(|parent| {
+                parent
+                .spawn(TextComponents {
+                    text: ui_text(label, asset_server),
+                    ..Default::default()
+                })
+                .with(PanelText);
+            });
+        }
+    }
+}
+
+/// Returns the value to select when the value is toggled. Toggling the selected value again
+/// selects the default value instead.
+pub fn toggle<T: PartialEq + Default>(selected: T, value: T) -> T {
+    if selected == value { T::default() } else { value }
+}
+
+/// Returns the value clicked in a frame. Clicks on different values in the same frame are
+/// ambiguous and ignored, so the result doesn't depend on the order they are read in.
+pub fn single_click<T: PartialEq>(clicks: impl IntoIterator<Item = T>) -> Option<T> {
+    let mut clicked = None;
+
+    for value in clicks {
+        match &clicked {
+            None => clicked = Some(value),
+            Some(first) if *first == value => (),
+            Some(_) => return None
+        }
+    }
+
+    clicked
+}
+
+/// Toggles the value of the clicked toggle button
+pub fn toggle_group_system<T: Copy + PartialEq + Default + Send + Sync + 'static>(
+    mut selected: ResMut<T>,
+    mut interaction_query: Query<With<ToggleButton, (&T, Mutated<Interaction>)>>
+) {
+    let mut clicks = Vec::new();
+    for (value, interaction) in &mut interaction_query.iter() {
+        if let Interaction::Clicked = *interaction {
+            clicks.push(*value);
+        }
+    }
+
+    if let Some(value) = single_click(clicks) {
+        *selected = toggle(*selected, value);
+    }
+}
+
+/// Shows which toggle button is selected and which one is hovered. The selection can also
+/// change without a click, e.g. by shortcuts.
+pub fn toggle_display_system<T: Copy + PartialEq + Send + Sync + 'static>(
+    selected: Res<T>,
+    button_materials: Res<ButtonMaterials>,
+    mut button_query: Query<(&T, &Interaction, &mut ToggleButton, &mut Handle<ColorMaterial>)>
+) {
+    for (value, interaction, mut toggle_button, mut material) in &mut button_query.iter() {
+        let toggled = *value == *selected;
+
+        let expected = match (toggled, interaction) {
+            (true, _) => &button_materials.pressed,
+            (false, Interaction::Hovered) => &button_materials.hovered,
+            (false, _) => &button_materials.normal
+        };
+
+        if *material != *expected {
+            *material = expected.clone();
+        }
+
+        match (toggled, &toggle_button.state) {
+            (true, ToggleState::Normal) => toggle_button.state = ToggleState::Toggled,
+            (false, ToggleState::Toggled) => toggle_button.state = ToggleState::Normal,
+            _ => ()
+        }
+    }
+}
+
+/// Number between a minimum and a maximum which is set by clicking or dragging along a bar.
+/// T tells sliders apart, the current value is read from the `Slider<T>` component.
+pub struct Slider<T> {
+    pub label: String,
+    pub value: f32,
+    pub min: f32,
+    pub max: f32,
+
+    /// Values are rounded to multiples of the step
+    pub step: f32,
+    marker: PhantomData<T>
+}
+
+impl<T> Slider<T> {
+    pub fn new(label: &str, min: f32, max: f32, step: f32, value: f32) -> Slider<T> {
+        Slider { label: label.to_string(), value, min, max, step, marker: PhantomData }
+    }
+
+    /// Position of the value between minimum (0.0) and maximum (1.0)
+    pub fn fraction(&self) -> f32 {
+        if self.max <= self.min {
+            return 0.0;
+        }
+
+        ((self.value - self.min) / (self.max - self.min)).max(0.0).min(1.0)
+    }
+
+    /// Returns the value at the position between minimum (0.0) and maximum (1.0)
+    pub fn value_at(&self, fraction: f32) -> f32 {
+        let value = self.min + (self.max - self.min) * fraction.max(0.0).min(1.0);
+
+        if self.step > 0.0 {
+            ((value / self.step).round() * self.step).max(self.min).min(self.max)
+        } else {
+            value
+        }
+    }
+}
+
+/// Part of the bar which shows the value of a slider
+pub struct SliderFill<T>(PhantomData<T>);
+
+pub struct SliderText<T>(PhantomData<T>);
+
+impl<T: Send + Sync + 'static> UiWidget<T> for Slider<T> {
+    fn create(&self, child_builder: &mut ChildBuilder, materials: &Res<ButtonMaterials>, asset_server: &Res<AssetServer>) {
+        row(child_builder, &self.label, materials, asset_server, |parent| {
+            parent
+            .spawn(ButtonComponents {
+                style: Style {
+                    size: Size::new(Val::Px(FIELD_WIDTH), Val::Px(ROW_HEIGHT)),
+                    margin: Rect::all(Val::Px(2.0)),
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                material: materials.normal.clone(),
+                ..Default::default()
+            })
+            .with(Slider::<T>::new(&self.label, self.min, self.max, self.step, self.value))
+            .with_children(|parent| {
+                parent
+                .spawn(NodeComponents {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: Rect { left: Val::Px(0.0), bottom: Val::Px(0.0), ..Default::default() },
+                        size: Size::new(Val::Percent(self.fraction() * 100.0), Val::Percent(100.0)),
+                        ..Default::default()
+                    },
+                    material: materials.pressed.clone(),
+                    ..Default::default()
+                })
+                .with(SliderFill::<T>(PhantomData));
+
+                parent
+                .spawn(TextComponents {
+                    style: Style {
+                        margin: Rect::all(Val::Auto),
+                        ..Default::default()
+                    },
+                    text: ui_text(&format!("{}", self.value), asset_server),
+                    ..Default::default()
+                })
+                .with(SliderText::<T>(PhantomData))
+                .with(PanelText);
+            });
+        });
+    }
+}
+
+/// Sets the value of a slider while it is pressed
+pub fn slider_system<T: Send + Sync + 'static>(
+    state: Res<input::MouseState>,
+    mut slider_query: Query<(&mut Slider<T>, &Interaction, &Node, &Transform)>
+) {
+    for (mut slider, interaction, node, transform) in &mut slider_query.iter() {
+        if let Interaction::Clicked = *interaction {
+            let left = transform.translation().x() - node.size.x() / 2.0;
+            let value = slider.value_at((state.mouse_position.x() - left) / node.size.x());
+
+            if value != slider.value {
+                slider.value = value;
+            }
+        }
+    }
+}
+
+/// Shows the value of sliders which were changed
+pub fn slider_display_system<T: Send + Sync + 'static>(
+    mut slider_query: Query<Mutated<Slider<T>>>,
+    mut fill_query: Query<With<SliderFill<T>, &mut Style>>,
+    mut text_query: Query<With<SliderText<T>, &mut Text>>
+) {
+    for slider in &mut slider_query.iter() {
+        for mut style in &mut fill_query.iter() {
+            style.size.width = Val::Percent(slider.fraction() * 100.0);
+        }
+
+        for mut text in &mut text_query.iter() {
+            text.value = format!("{}", slider.value);
+        }
+    }
+}
+
+/// Option which is switched on and off by clicking it. T tells checkboxes apart.
+pub struct Checkbox<T> {
+    pub label: String,
+    pub checked: bool,
+    marker: PhantomData<T>
+}
+
+impl<T> Checkbox<T> {
+    pub fn new(label: &str, checked: bool) -> Checkbox<T> {
+        Checkbox { label: label.to_string(), checked, marker: PhantomData }
+    }
+}
+
+impl<T: Send + Sync + 'static> UiWidget<T> for Checkbox<T> {
+    fn create(&self, child_builder: &mut ChildBuilder, materials: &Res<ButtonMaterials>, asset_server: &Res<AssetServer>) {
+        row(child_builder, &self.label, materials, asset_server, |parent| {
+            parent
+            .spawn(ButtonComponents {
+                style: button_style(ROW_HEIGHT),
+                material: if self.checked { materials.pressed.clone() } else { materials.normal.clone() },
+                ..Default::default()
+            })
+            .with(Checkbox::<T>::new(&self.label, self.checked));
+        });
+    }
+}
+
+pub fn checkbox_system<T: Send + Sync + 'static>(
+    mut checkbox_query: Query<(&mut Checkbox<T>, Mutated<Interaction>)>
+) {
+    for (mut checkbox, interaction) in &mut checkbox_query.iter() {
+        if let Interaction::Clicked = *interaction {
+            checkbox.checked = !checkbox.checked;
+        }
+    }
+}
+
+/// Shows the state of checkboxes which were clicked or changed by other systems
+pub fn checkbox_display_system<T: Send + Sync + 'static>(
+    button_materials: Res<ButtonMaterials>,
+    mut checkbox_query: Query<(Mutated<Checkbox<T>>, &mut Handle<ColorMaterial>)>
+) {
+    for (checkbox, mut material) in &mut checkbox_query.iter() {
+        *material = if checkbox.checked {
+            button_materials.pressed.clone()
+        } else {
+            button_materials.normal.clone()
+        };
+    }
+}
+
+/// Number which is changed in steps with buttons next to it. T tells fields apart.
+pub struct NumericField<T> {
+    pub label: String,
+    pub value: f32,
+    pub step: f32,
+    pub min: f32,
+    pub max: f32,
+    marker: PhantomData<T>
+}
+
+impl<T> NumericField<T> {
+    pub fn new(label: &str, min: f32, max: f32, step: f32, value: f32) -> NumericField<T> {
+        NumericField { label: label.to_string(), value, step, min, max, marker: PhantomData }
+    }
+
+    /// Changes the value by the given number of steps staying within the bounds
+    pub fn change(&mut self, steps: f32) {
+        self.value = (self.value + steps * self.step).max(self.min).min(self.max);
+    }
+}
+
+/// Button changing its numeric field by the given number of steps
+pub struct NumericStep<T> {
+    field: Entity,
+    steps: f32,
+    marker: PhantomData<T>
+}
+
+impl<T: Send + Sync + 'static> UiWidget<T> for NumericField<T> {
+    fn create(&self, child_builder: &mut ChildBuilder, materials: &Res<ButtonMaterials>, asset_server: &Res<AssetServer>) {
+        row(child_builder, &self.label, materials, asset_server, |parent| {
+            // the buttons are spawned first but have to know the field
+            let field = Entity::new();
+
+            for (label, steps) in &[("-", -1.0), ("+", 1.0)] {
+                parent
+                .spawn(ButtonComponents {
+                    style: button_style(ROW_HEIGHT),
+                    material: materials.normal.clone(),
+                    ..Default::default()
+                })
+                .with(NumericStep::<T> { field, steps: *steps, marker: PhantomData })
+                .with(HoverFeedback)
+                .with_children(|parent| {
+                    parent
+                    .spawn(TextComponents {
+                        text: ui_text(label, asset_server),
+                        ..Default::default()
+                    })
+                    .with(PanelText);
+                });
+            }
+
+            parent
+            .spawn_as_entity(field, TextComponents {
+                style: Style {
+                    margin: Rect::all(Val::Px(5.0)),
+                    ..Default::default()
+                },
+                text: ui_text(&format!("{}", self.value), asset_server),
+                ..Default::default()
+            })
+            .with(NumericField::<T>::new(&self.label, self.min, self.max, self.step, self.value))
+            .with(PanelText);
+        });
+    }
+}
+
+pub fn numeric_field_system<T: Send + Sync + 'static>(
+    mut step_query: Query<(&NumericStep<T>, Mutated<Interaction>)>,
+    mut field_query: Query<&mut NumericField<T>>
+) {
+    for (step, interaction) in &mut step_query.iter() {
+        if let Interaction::Clicked = *interaction {
+            if let Ok(mut field) = field_query.get_mut::<NumericField<T>>(step.field) {
+                field.change(step.steps);
+            }
+        }
+    }
+}
+
+pub fn numeric_display_system<T: Send + Sync + 'static>(
+    mut field_query: Query<(Mutated<NumericField<T>>, &mut Text)>
+) {
+    for (field, mut text) in &mut field_query.iter() {
+        text.value = format!("{}", field.value);
+    }
+}
+
+/// Text which is shown behind its label and changed by other systems. T tells labels apart.
+pub struct Label<T> {
+    pub label: String,
+    pub text: String,
+    marker: PhantomData<T>
+}
+
+impl<T> Label<T> {
+    pub fn new(label: &str, text: &str) -> Label<T> {
+        Label { label: label.to_string(), text: text.to_string(), marker: PhantomData }
+    }
+}
+
+impl<T: Send + Sync + 'static> UiWidget<T> for Label<T> {
+    fn create(&self, child_builder: &mut ChildBuilder, materials: &Res<ButtonMaterials>, asset_server: &Res<AssetServer>) {
+        row(child_builder, &self.label, materials, asset_server, |parent| {
+            parent
+            .spawn(TextComponents {
+                text: ui_text(&self.text, asset_server),
+                ..Default::default()
+            })
+            .with(Label::<T>::new(&self.label, &self.text))
+            .with(PanelText);
+        });
+    }
+}
+
+pub fn label_display_system<T: Send + Sync + 'static>(
+    mut label_query: Query<(Mutated<Label<T>>, &mut Text)>
+) {
+    for (label, mut text) in &mut label_query.iter() {
+        text.value = label.text.clone();
+    }
+}
+
+/// Text which is typed after clicking the field. Enter or clicking somewhere else ends the
+/// input. T tells fields apart.
+pub struct TextField<T> {
+    pub label: String,
+    pub value: String,
+
+    /// Typed characters are added to the value
+    pub focused: bool,
+    marker: PhantomData<T>
+}
+
+/// Text field which receives the typed characters. No keyboard shortcuts are triggered while
+/// a text field is focused.
+#[derive(Default)]
+pub struct TextFocus(pub Option<Entity>);
+
+impl<T> TextField<T> {
+    pub fn new(label: &str, value: &str) -> TextField<T> {
+        TextField { label: label.to_string(), value: value.to_string(), focused: false, marker: PhantomData }
+    }
+
+    fn text(&self) -> String {
+        if self.focused {
+            format!("{}|", self.value)
+        } else {
+            self.value.clone()
+        }
+    }
+}
+
+pub struct TextFieldText<T>(PhantomData<T>);
+
+impl<T: Send + Sync + 'static> UiWidget<T> for TextField<T> {
+    fn create(&self, child_builder: &mut ChildBuilder, materials: &Res<ButtonMaterials>, asset_server: &Res<AssetServer>) {
+        row(child_builder, &self.label, materials, asset_server, |parent| {
+            parent
+            .spawn(ButtonComponents {
+                style: Style {
+                    justify_content: JustifyContent::FlexStart,
+                    padding: Rect::all(Val::Px(5.0)),
+                    ..button_style(FIELD_WIDTH)
+                },
+                material: materials.normal.clone(),
+                ..Default::default()
+            })
+            .with(TextField::<T>::new(&self.label, &self.value))
+            .with_children(|parent| {
+                parent
+                .spawn(TextComponents {
+                    text: ui_text(&self.value, asset_server),
+                    ..Default::default()
+                })
+                .with(TextFieldText::<T>(PhantomData))
+                .with(PanelText);
+            });
+        });
+    }
+}
+
+pub fn text_field_system<T: Send + Sync + 'static>(
+    mut character_reader: Local<EventReader<ReceivedCharacter>>,
+    characters: Res<Events<ReceivedCharacter>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_button_input: Res<Input<MouseButton>>,
+    mut text_focus: ResMut<TextFocus>,
+    mut field_query: Query<(Entity, &mut TextField<T>, &Interaction)>
+) {
+    let typed: String = character_reader
+        .iter(&characters)
+        .map(|event| event.char)
+        .filter(|character| !character.is_control())
+        .collect();
+
+    for (entity, mut field, interaction) in &mut field_query.iter() {
+        match (interaction, field.focused) {
+            (Interaction::Clicked, false) => field.focused = true,
+            (Interaction::None, true) if mouse_button_input.just_pressed(MouseButton::Left) => field.focused = false,
+            _ => ()
+        }
+
+        if keyboard_input.just_pressed(KeyCode::Return) && field.focused {
+            field.focused = false;
+        }
+
+        // fields of other types may have taken the focus already in this frame
+        if !field.focused {
+            if text_focus.0 == Some(entity) {
+                text_focus.0 = None;
+            }
+
+            continue;
+        }
+
+        if text_focus.0 != Some(entity) {
+            text_focus.0 = Some(entity);
+        }
+
+        if keyboard_input.just_pressed(KeyCode::Back) {
+            field.value.pop();
+        } else if !typed.is_empty() {
+            field.value.push_str(&typed);
+        }
+    }
+}
+
+pub fn text_field_display_system<T: Send + Sync + 'static>(
+    button_materials: Res<ButtonMaterials>,
+    mut field_query: Query<(Mutated<TextField<T>>, &Children, &mut Handle<ColorMaterial>)>,
+    mut text_query: Query<With<TextFieldText<T>, &mut Text>>
+) {
+    for (field, children, mut material) in &mut field_query.iter() {
+        *material = if field.focused {
+            button_materials.pressed.clone()
+        } else {
+            button_materials.normal.clone()
+        };
+
+        for child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut::<Text>(*child) {
+                text.value = field.text();
+            }
+        }
+    }
+}
+
+/// Selection of one of several values from a list which opens below it. The selected value
+/// is the resource of type T.
+pub struct Dropdown<T> {
+    pub label: String,
+    pub options: Vec<(T, String)>,
+
+    /// Entities of the open list
+    list: Vec<Entity>
+}
+
+impl<T> Dropdown<T> {
+    pub fn new(label: &str, options: Vec<(T, &str)>) -> Dropdown<T> {
+        Dropdown {
+            label: label.to_string(),
+            options: options.into_iter().map(|(value, label)| (value, label.to_string())).collect(),
+            list: Vec::new()
+        }
+    }
+}
+
+pub struct DropdownText<T>(PhantomData<T>);
+
+/// Entry of an open dropdown list
+pub struct DropdownOption<T>(T);
+
+impl<T: Copy + PartialEq + Send + Sync + 'static> UiWidget<T> for Dropdown<T> {
+    fn create(&self, child_builder: &mut ChildBuilder, materials: &Res<ButtonMaterials>, asset_server: &Res<AssetServer>) {
+        let options: Vec<(T, &str)> = self.options.iter().map(|(value, label)| (*value, label.as_str())).collect();
+
+        row(child_builder, &self.label, materials, asset_server, |parent| {
+            parent
+            .spawn(ButtonComponents {
+                style: button_style(FIELD_WIDTH),
+                material: materials.normal.clone(),
+                ..Default::default()
+            })
+            .with(Dropdown::new(&self.label, options))
+            .with(HoverFeedback)
+            .with_children(|parent| {
+                parent
+                .spawn(TextComponents {
+                    text: ui_text("", asset_server),
+                    ..Default::default()
+                })
+                .with(DropdownText::<T>(PhantomData))
+                .with(PanelText);
+            });
+        });
+    }
+}
+
+/// Opens and closes dropdown lists and selects the clicked option
+pub fn dropdown_system<T: Copy + PartialEq + Send + Sync + 'static>(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    button_materials: Res<ButtonMaterials>,
+    mut selected: ResMut<T>,
+    mut header_query: Query<With<Dropdown<T>, (Entity, Mutated<Interaction>, &Node, &Transform)>>,
+    mut dropdown_query: Query<(Entity, &mut Dropdown<T>)>,
+    mut option_query: Query<(&DropdownOption<T>, Mutated<Interaction>)>
+) {
+    let mut chosen = None;
+    for (option, interaction) in &mut option_query.iter() {
+        if let Interaction::Clicked = *interaction {
+            chosen = Some(option.0);
+        }
+    }
+
+    if let Some(value) = chosen {
+        if *selected != value {
+            *selected = value;
+        }
+    }
+
+    let mut clicked = Vec::new();
+    for (entity, interaction, node, transform) in &mut header_query.iter() {
+        if let Interaction::Clicked = *interaction {
+            clicked.push((entity, node.size, transform.translation()));
+        }
+    }
+
+    if clicked.is_empty() && chosen.is_none() {
+        return;
+    }
+
+    for (entity, mut dropdown) in &mut dropdown_query.iter() {
+        let header = clicked.iter().find(|(header, _, _)| *header == entity);
+
+        if !dropdown.list.is_empty() {
+            for entity in dropdown.list.drain(..) {
+                commands.despawn(entity);
+            }
+
+            continue;
+        }
+
+        let (size, translation) = match header {
+            Some((_, size, translation)) => (*size, *translation),
+            None => continue
+        };
+
+        // the list opens below the dropdown
+        let left = translation.x() - size.x() / 2.0;
+        let top = translation.y() - size.y() / 2.0;
+
+        for (index, (value, label)) in dropdown.options.clone().iter().enumerate() {
+            let bottom = top - (index + 1) as f32 * ROW_HEIGHT;
+
+            commands
+            .spawn(ButtonComponents {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: Rect { left: Val::Px(left), bottom: Val::Px(bottom), ..Default::default() },
+                    ..button_style(size.x())
+                },
+                material: button_materials.background.clone(),
+                ..Default::default()
+            })
+            .with(DropdownOption(*value))
+            .with(HoverFeedback);
+            dropdown.list.push(commands.current_entity().unwrap());
+
+            commands
+            .spawn(TextComponents {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: Rect { left: Val::Px(left + 5.0), bottom: Val::Px(bottom + 5.0), ..Default::default() },
+                    ..Default::default()
+                },
+                text: ui_text(label, &asset_server),
+                ..Default::default()
+            })
+            .with(PanelText);
+            dropdown.list.push(commands.current_entity().unwrap());
+        }
+    }
+}
+
+/// Shows the label of the selected value in the dropdowns
+pub fn dropdown_display_system<T: Copy + PartialEq + Send + Sync + 'static>(
+    selected: Res<T>,
+    mut dropdown_query: Query<(&Dropdown<T>, &Children)>,
+    mut text_query: Query<With<DropdownText<T>, &mut Text>>
+) {
+    for (dropdown, children) in &mut dropdown_query.iter() {
+        let label = dropdown.options
+            .iter()
+            .find(|(value, _)| *value == *selected)
+            .map_or("", |(_, label)| label.as_str());
+
+        for child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut::<Text>(*child) {
+                if text.value != label {
+                    text.value = label.to_string();
+                }
+            }
+        }
+    }
+}
+
+/// Highlights hovered and pressed buttons
+pub fn button_material_system(
+    button_materials: Res<ButtonMaterials>,
+    mut interaction_query: Query<With<HoverFeedback, (Mutated<Interaction>, &mut Handle<ColorMaterial>)>>
+) {
+    for (interaction, mut material) in &mut interaction_query.iter() {
+        *material = match *interaction {
+            Interaction::Clicked => button_materials.pressed.clone(),
+            Interaction::Hovered => button_materials.hovered.clone(),
+            Interaction::None => button_materials.normal.clone()
+        };
+    }
+}
+
+/// Titled panel built from a list of widgets which can be collapsed to its title
+pub struct Panel {
+    pub title: String,
+    pub position: Rect<Val>,
+    pub collapsible: bool,
+    widgets: Vec<Box<dyn Fn(&mut ChildBuilder, &Res<ButtonMaterials>, &Res<AssetServer>) + Send + Sync>>
+}
+
+impl Panel {
+    pub fn new(title: &str, position: Rect<Val>) -> Panel {
+        Panel {
+            title: title.to_string(),
+            position,
+            collapsible: false,
+            widgets: Vec::new()
+        }
+    }
+
+    pub fn collapsible(mut self) -> Panel {
+        self.collapsible = true;
+        self
+    }
+
+    /// Adds the widget below the previous ones
+    pub fn with<T, W: UiWidget<T> + Send + Sync + 'static>(mut self, widget: W) -> Panel {
+        self.widgets.push(Box::new(move |child_builder, materials, asset_server| {
+            widget.create(child_builder, materials, asset_server)
+        }));
+        self
+    }
+}
+
+/// Title of a collapsible panel which shows and hides its content when clicked
+pub struct PanelHeader {
+    pub title: String,
+    pub collapsed: bool,
+    pub collapsible: bool
+}
+
+impl PanelHeader {
+    fn text(&self) -> String {
+        if !self.collapsible {
+            return self.title.clone();
+        }
+
+        format!("{} {}", if self.collapsed { "+" } else { "-" }, self.title)
+    }
+}
+
+pub struct PanelContent;
+
+impl UiContainerWidget<()> for Panel {
+    fn create(&self, commands: &mut Commands, materials: &Res<ButtonMaterials>, asset_server: &Res<AssetServer>) {
+        commands
+        .spawn(NodeComponents {
+            style: Style {
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::FlexStart,
+                ..panel_style(self.position)
+            },
+            material: materials.background.clone(),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            let header = PanelHeader { title: self.title.clone(), collapsed: false, collapsible: self.collapsible };
+            let title = header.text();
+
+            parent
+            .spawn(ButtonComponents {
+                style: Style {
+                    size: Size::new(Val::Auto, Val::Px(ROW_HEIGHT)),
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                material: materials.background.clone(),
+                ..Default::default()
+            })
+            .with(header)
+            .with_children(|parent| {
+                parent
+                .spawn(TextComponents {
+                    text: ui_text(&title, asset_server),
+                    ..Default::default()
+                })
+                .with(PanelText);
+            });
+
+            parent
+            .spawn(NodeComponents {
+                style: Style {
+                    flex_direction: FlexDirection::ColumnReverse,
+                    align_items: AlignItems::FlexStart,
+                    ..Default::default()
+                },
+                material: materials.background.clone(),
+                ..Default::default()
+            })
+            .with(PanelContent)
+            .with_children(|parent| {
+                for widget in &self.widgets {
+                    widget(parent, materials, asset_server);
+                }
+            });
+        });
+    }
+}
+
+/// Collapses and expands panels when their title is clicked
+pub fn panel_system(
+    mut header_query: Query<(&mut PanelHeader, Mutated<Interaction>, &Parent, &Children)>,
+    mut children_query: Query<&Children>,
+    mut content_query: Query<With<PanelContent, &mut Style>>,
+    mut text_query: Query<&mut Text>
+) {
+    for (mut header, interaction, parent, header_children) in &mut header_query.iter() {
+        match *interaction {
+            Interaction::Clicked if header.collapsible => header.collapsed = !header.collapsed,
+            _ => continue
+        }
+
+        let siblings = match children_query.get::<Children>(parent.0) {
+            Ok(children) => children.iter().cloned().collect::<Vec<Entity>>(),
+            Err(_) => continue
+        };
+
+        for sibling in siblings {
+            if let Ok(mut style) = content_query.get_mut::<Style>(sibling) {
+                style.display = if header.collapsed { Display::None } else { Display::Flex };
+            }
+        }
+
+        for child in header_children.iter() {
+            if let Ok(mut text) = text_query.get_mut::<Text>(*child) {
+                text.value = header.text();
+            }
+        }
+    }
+}
+
+/// Registers the systems of the toggle groups selecting the resource T
+pub fn add_toggle_group<T: Copy + PartialEq + Default + Send + Sync + 'static>(app: &mut AppBuilder) {
+    app.add_system_to_stage("ui_handling", toggle_group_system::<T>.system());
+    app.add_system_to_stage("ui_handling", toggle_display_system::<T>.system());
+}
+
+/// Registers the systems of the sliders marked with T
+pub fn add_slider<T: Send + Sync + 'static>(app: &mut AppBuilder) {
+    app.add_system_to_stage("ui_handling", slider_system::<T>.system());
+    app.add_system_to_stage("ui_handling", slider_display_system::<T>.system());
+}
+
+/// Registers the systems of the checkboxes marked with T
+pub fn add_checkbox<T: Send + Sync + 'static>(app: &mut AppBuilder) {
+    app.add_system_to_stage("ui_handling", checkbox_system::<T>.system());
+    app.add_system_to_stage("ui_handling", checkbox_display_system::<T>.system());
+}
+
+/// Registers the systems of the numeric fields marked with T
+pub fn add_numeric_field<T: Send + Sync + 'static>(app: &mut AppBuilder) {
+    app.add_system_to_stage("ui_handling", numeric_field_system::<T>.system());
+    app.add_system_to_stage("ui_handling", numeric_display_system::<T>.system());
+}
+
+/// Registers the system of the labels marked with T
+pub fn add_label<T: Send + Sync + 'static>(app: &mut AppBuilder) {
+    app.add_system_to_stage("ui_handling", label_display_system::<T>.system());
+}
+
+/// Registers the systems of the text fields marked with T
+pub fn add_text_field<T: Send + Sync + 'static>(app: &mut AppBuilder) {
+    app.add_system_to_stage("ui_handling", text_field_system::<T>.system());
+    app.add_system_to_stage("ui_handling", text_field_display_system::<T>.system());
+}
+
+/// Registers the systems of the dropdowns selecting the resource T
+pub fn add_choice_widgets<T: Copy + PartialEq + Send + Sync + 'static>(app: &mut AppBuilder) {
+    app.add_system_to_stage("ui_handling", dropdown_system::<T>.system());
+    app.add_system_to_stage("ui_handling", dropdown_display_system::<T>.system());
+}
+
+/// Registers the events sent by the labelled buttons with values of type T
+pub fn add_button_events<T: Clone + Send + Sync + 'static>(app: &mut AppBuilder) {
+    app.add_event::<T>();
+    app.add_system_to_stage("ui_handling", button_event_system::<T>.system());
+}
+
+pub struct WidgetPlugin;
+
+impl Plugin for WidgetPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<TextFocus>();
+        app.add_system_to_stage("ui_handling", button_material_system.system());
+        app.add_system_to_stage("ui_handling", panel_system.system());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Marker;
+
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    enum Tool {
+        Select,
+        Build,
+        Demolish
+    }
+
+    impl Default for Tool {
+        fn default() -> Tool {
+            Tool::Select
+        }
+    }
+
+    #[test]
+    fn toggling_the_selected_value_selects_the_default() {
+        assert_eq!(toggle(Tool::Select, Tool::Build), Tool::Build);
+        assert_eq!(toggle(Tool::Build, Tool::Demolish), Tool::Demolish);
+        assert_eq!(toggle(Tool::Build, Tool::Build), Tool::Select);
+        assert_eq!(toggle(Tool::Select, Tool::Select), Tool::Select);
+    }
+
+    #[test]
+    fn clicks_on_different_values_in_one_frame_are_ignored() {
+        assert_eq!(single_click(Vec::<Tool>::new()), None);
+        assert_eq!(single_click(vec![Tool::Build]), Some(Tool::Build));
+        assert_eq!(single_click(vec![Tool::Build, Tool::Build]), Some(Tool::Build));
+        assert_eq!(single_click(vec![Tool::Build, Tool::Demolish]), None);
+        assert_eq!(single_click(vec![Tool::Demolish, Tool::Build]), None);
+    }
+
+    #[test]
+    fn slider_values_are_rounded_to_steps() {
+        let slider = Slider::<Marker>::new("Size", 50.0, 500.0, 10.0, 150.0);
+
+        assert!((slider.fraction() - 0.2222).abs() < 1e-3);
+        assert_eq!(slider.value_at(0.5), 280.0);
+        assert_eq!(slider.value_at(-1.0), 50.0);
+        assert_eq!(slider.value_at(2.0), 500.0);
+    }
+
+    #[test]
+    fn numeric_fields_stay_in_bounds() {
+        let mut field = NumericField::<Marker>::new("Count", 1.0, 3.0, 1.0, 2.0);
+
+        field.change(1.0);
+        assert_eq!(field.value, 3.0);
+        field.change(1.0);
+        assert_eq!(field.value, 3.0);
+        field.change(-5.0);
+        assert_eq!(field.value, 1.0);
+    }
+}
@@ -0,0 +1,19 @@
+use bevy::prelude::*;
+
+/// Part of the world shown by the main camera
+pub struct CameraView {
+    /// Position of the main camera in the world, the mouse position is relative to it
+    pub position: Vec2,
+
+    /// World units per pixel on the screen
+    pub zoom: f32
+}
+
+impl Default for CameraView {
+    fn default() -> CameraView {
+        CameraView {
+            position: Vec2::zero(),
+            zoom: 1.0
+        }
+    }
+}